@@ -1,204 +1,403 @@
 use crate::app_own_event::AppOwnEvent;
-use crate::elevator_installation::elevator_service::PassengerLiftActor;
+use crate::cli::CommandLine;
+use elevator_ratatui::elevator_installation::dispatch_strategy::{dispatch_strategy_named, DispatchStrategy};
+use crate::journal::{Journal, JournalEntry, JournalHeader};
+use crate::metrics::{MetricsReport, ServiceMetrics};
+use crate::replay::{DivergenceReport, Replay, SEEKING_SPEED};
+use elevator_ratatui::simulation_clock::{ClockMode, SimulationClock};
+use crate::ui_actor::{UiActor, UiVocabulary};
+use elevator_ratatui::elevator_installation::group_controller::{car_letter, GroupControllerActor, OperatingTimes};
+use elevator_ratatui::conversation::vocabulary::{CarIndex, ElevatorVocabulary, GroupVocabulary, Observer};
+use elevator_ratatui::FaultPolicy;
 use crate::{
-    
-    conversation::vocabulary::{ElevatorVocabulary, PulleyVocabulary},
-    elevator_infra::{ElevatorVisualInfra, MX_FLOORS},
-    elevator_installation::pulley_machinery::PulleyActor,
+    elevator_infra::ElevatorVisualInfra,
     tui::Tui,
     tui_layout::TuiLayout,
     ui::DisplayManager,
 };
 
-use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
-use ractor::{Actor, ActorRef};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ractor::{Actor, ActorRef, MessagingErr, SpawnErr};
 use ratatui::{
     backend::Backend,
     layout::Position,
     Terminal,
 };
-use std::{collections::VecDeque, error, time::Duration};
-use tokio::{
-    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
-    task::JoinHandle,
-};
+use std::{collections::VecDeque, fmt, io, time::Duration};
+use tokio::{sync::oneshot, task::JoinHandle};
 use tracing::info;
 
-pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
+pub use elevator_ratatui::AppResult;
+
+type ReplayTimer = JoinHandle<Result<(), MessagingErr<UiVocabulary>>>;
+
+/// What can go wrong in the operator's console: the terminal may fail it, and a group of cars may
+/// not start. Each error is handled by its own policy (see FaultPolicy).
+#[derive(Debug)]
+pub enum AppError {
+    Terminal(io::Error),           // the screen cannot be drawn on, nor handed back
+    UnknownStrategy(String),       // no car can be dispatched with it
+    GroupNotStarted(SpawnErr)      // the group of cars is not there
+}
+
+impl AppError {
+    pub fn policy(&self) -> FaultPolicy {
+        match self {
+            // A replay not started over, carries on where it is.
+            AppError::UnknownStrategy(_) | AppError::GroupNotStarted(_) => FaultPolicy::LogAndContinue,
+            // The operator sees nothing anymore.
+            AppError::Terminal(_) => FaultPolicy::Escalate
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Terminal(e) => write!(f, "the terminal has failed: {}", e),
+            AppError::UnknownStrategy(strategy) => write!(f, "there is no dispatch strategy ({})", strategy),
+            AppError::GroupNotStarted(e) => write!(f, "the group controller has not started: {}", e)
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        AppError::Terminal(e)
+    }
+}
 
 pub struct App<B: Backend> {
     pub inner_infra: ElevatorVisualInfra,
     to_quit: bool,
-    pub tick_count: i32,
-    pub tick_rate: f64,
-    pub frame_rate: f64,
+    operating_times: OperatingTimes,
+    strategy: String,
+    clock: SimulationClock,
     pub tui_wrapper: Tui<B>,
-    pub app_own_event_rx: UnboundedReceiver<AppOwnEvent>,
-    pub app_own_event_tx: UnboundedSender<AppOwnEvent>,
-    pub elev_event_tx: UnboundedSender<ElevatorVocabulary>,
-    pub elev_event_rx: UnboundedReceiver<ElevatorVocabulary>,
-    passenger_lift: (ActorRef<ElevatorVocabulary>, JoinHandle<()>),
-    pulley_machinery: (ActorRef<PulleyVocabulary>, JoinHandle<()>),
+    group_controller: (ActorRef<GroupVocabulary>, JoinHandle<()>),
+    group_generation: usize, // a group of cars started afresh gets a name of its own
     messages_for_ops: VecDeque<String>,
+    journal: Option<Journal>,
+    metrics: ServiceMetrics,
+    replay: Option<Replay>,
+    to_restart_replay: bool,
+    // When the console is woken up next, to feed a recorded command or stop the replay.
+    replay_timer: Option<(Duration, ReplayTimer)>,
 }
 
 impl<B: Backend> App<B> {
     pub async fn new(
         carriage_movement_area: ElevatorVisualInfra,
-        tick_rate: f64,
-        frame_rate: f64,
+        options: &CommandLine,
         terminal: Terminal<B>,
         tui_layout: TuiLayout,
         ui: DisplayManager,
-    ) -> Self {
-        let (app_own_event_tx, app_own_event_rx) = mpsc::unbounded_channel();
-
-        let tui = Tui::new(terminal, tui_layout, ui, app_own_event_tx.clone());
+        replay: Option<Replay>,
+    ) -> AppResult<Self> {
+        let (tick_rate, frame_rate) = (options.tick_rate, options.frame_rate);
+        let tui = Tui::new(terminal, tui_layout, ui, tick_rate, frame_rate);
+        let clock = SimulationClock::new(options.clock_mode());
 
-        let floor_setting = carriage_movement_area.get_carriage_displacement_map_per_floor((0, 0));
+        // A recorded run is played again as it was set up then.
+        let (operating_times, strategy) = match &replay {
+            Some(replay) => (replay.header.operating_times, replay.header.strategy.clone()),
+            None => (options.operating_times(), options.strategy.clone())
+        };
 
-        let (pulley_ref, pulley_handle) = Actor::spawn(
-            Some(String::from("Pulley_actor")),
-            PulleyActor,
-            floor_setting,
-        )
-        .await
-        .expect("Failed to create Pulley actor");
+        let journal = match &options.journal {
+            Some(path) => {
+                let header = JournalHeader::new(carriage_movement_area.building.clone(), &strategy, operating_times);
+                Some(Journal::create(path, &header)?)
+            },
+            None => None
+        };
 
-        let (elev_event_tx, elev_event_rx) = mpsc::unbounded_channel();
+        let metrics = ServiceMetrics::new(&carriage_movement_area.building);
 
-        let (elev_ref, elev_handle) = Actor::spawn(
-            Some(String::from("Elevator-Actor")),
-            PassengerLiftActor,
-            (MX_FLOORS, Some(elev_event_tx.clone()), pulley_ref.clone()),
-        )
-        .await
-        .expect("Failed to start actor");
+        let group_controller = Self::spawn_group(
+            String::from("Group-Controller"),
+            &carriage_movement_area,
+            operating_times,
+            &strategy,
+            &clock).await?;
 
-        Self {
+        Ok(Self {
             inner_infra: carriage_movement_area,
             to_quit: false,
-            tick_count: 0i32,
-            tick_rate,
-            frame_rate,
+            operating_times,
+            strategy,
+            clock,
             tui_wrapper: tui,
-            app_own_event_rx,
-            app_own_event_tx,
-            elev_event_tx,
-            elev_event_rx,
-            passenger_lift: (elev_ref, elev_handle),
-            pulley_machinery: (pulley_ref, pulley_handle),
+            group_controller,
+            group_generation: 0,
             messages_for_ops: VecDeque::with_capacity(1024),
+            journal,
+            metrics,
+            replay,
+            to_restart_replay: false,
+            replay_timer: None,
+        })
+    }
+
+    async fn spawn_group(
+        name: String,
+        carriage_movement_area: &ElevatorVisualInfra,
+        operating_times: OperatingTimes,
+        strategy: &str,
+        clock: &SimulationClock
+    ) -> Result<(ActorRef<GroupVocabulary>, JoinHandle<()>), AppError> {
+        // Every car gets a shaft of its own, on screen.
+        let dispatch_strategies = carriage_movement_area
+            .cars
+            .iter()
+            .map(|_| dispatch_strategy_named(strategy))
+            .collect::<Option<Vec<Box<dyn DispatchStrategy>>>>()
+            .ok_or_else(|| AppError::UnknownStrategy(String::from(strategy)))?;
+
+        let group_controller = Actor::spawn(
+            Some(name),
+            GroupControllerActor,
+            (carriage_movement_area.building.clone(), operating_times, clock.clone(), dispatch_strategies, None),
+        )
+        .await
+        .map_err(AppError::GroupNotStarted)?;
+
+        Ok(group_controller)
+    }
+
+    // Whatever is journaled, counts towards the service quality too.
+    fn record(&mut self, entry: JournalEntry) {
+        let at = self.clock.elapsed();
+        self.metrics.on_entry(at, &entry);
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record(at, entry);
         }
     }
 
-    pub fn quit(&mut self) -> () {
+    // Whatever the operator asks of the group of cars, is journaled on the way.
+    fn command_group(&mut self, message: GroupVocabulary) {
+        self.record(JournalEntry::ToGroup(message.clone()));
+        self.group_controller.0.send_message(message).unwrap_or_else(|e| {
+            info!("Group is not there anymore, {:?}", e);
+        });
+    }
+
+    // The console hears what the cars of the group tell their operator, for as long as it is the
+    // same group: once the group is started afresh, whatever the former cars have still to tell,
+    // is not heard anymore.
+    fn observe_group(&mut self, console: &ActorRef<UiVocabulary>) {
+        let group_generation = self.group_generation;
+        let observer = Observer::new(console.clone(), move |car, report| UiVocabulary::FromCar(group_generation, car, report));
+        self.group_controller.0.send_message(GroupVocabulary::Subscribe(observer)).unwrap_or_else(|e| {
+            info!("Group is not there anymore, {:?}", e);
+        });
+    }
+
+    pub fn quit(&mut self) {
         self.to_quit = true;
-        ()
     }
 
     pub fn should_quit_app(&self) -> bool {
         self.to_quit
     }
 
-    pub fn init(&mut self) -> AppResult<()> {
-        self.tui_wrapper.init()
-    }
-
-    pub fn start(&mut self) -> AppResult<()> {
-        self.tui_wrapper.start()
-    }
-
-    pub async fn run(&mut self) -> AppResult<()> {
-        loop {
-            tokio::select! {
-                from_elevator = self.elev_event_rx.recv() => {
-                    match from_elevator {
-                       Some(ElevatorVocabulary::MoveToGroundFloor) => {
-                        self.messages_for_ops.push_back(String::from("Elevator is moving to ground-floor."));
-                        self.inner_infra.set_carriage_ready();
-                       },
-                       Some(ElevatorVocabulary::MovingTo(f)) => {
-                        self.messages_for_ops.push_back(format!("Elevator is moving to floor({})",f));
-                        self.inner_infra.set_next_destination(f as u16);
-                       }
-                       Some(ElevatorVocabulary::CurrentCarriagePosn((x_posn,y_posn))) =>  {
-                        self.inner_infra.on_carriage_moving_to((x_posn,y_posn));
-                       },
-                       Some(ElevatorVocabulary::OpenTheDoor(f)) => {
-                        self.messages_for_ops.push_back(format!("Elevator has reached floor({}), door is open.",f));
-                        self.inner_infra.on_reaching_destination();
-
-                        // We are simulating the action of opening, waiting and closing the carriage-door.
-                        let app_event_channel_passed = self.app_own_event_tx.clone();
-                        let _ = tokio::spawn (async move {
-                            tokio::time::sleep(Duration::from_secs(2)).await;
-                            app_event_channel_passed.send(AppOwnEvent::AllPassengersAlighted(f)).unwrap();
-                        });
+    pub fn init(&mut self) -> Result<(), AppError> {
+        Ok(self.tui_wrapper.init()?)
+    }
 
-                       }
-                       Some(ElevatorVocabulary::ElevatorOutOfService) => {
-                        self.messages_for_ops.push_back(String::from("Elevator is not operating anymore!"));
-                        self.inner_infra.unset_carriage();
-                        self.messages_for_ops.push_back(String::from("Quitting application in 3 seconds."));
-                        // We are simulating the action of notifying that we are exiting.
-                        let app_event_channel_passed = self.app_own_event_tx.clone();
-                        let _ = tokio::spawn (async move {
-                            tokio::time::sleep(Duration::from_secs(3)).await;
-                            app_event_channel_passed.send(AppOwnEvent::Exit).unwrap();
-                        });
-                       },
-                       Some(ElevatorVocabulary::Stop(0)) => {},
-                       Some(_) => {},
-                       None => { todo!(); }
-
-                }},
-                app_own_event = self.app_own_event_rx.recv() => {
-                    self.handle_app_own_event(app_own_event).unwrap();
-                }
-            };
+    pub fn start(&mut self, console: &ActorRef<UiVocabulary>) -> Result<(), AppError> {
+        self.observe_group(console);
+        Ok(self.tui_wrapper.start(console.clone())?)
+    }
 
-            if self.should_quit_app() {
-                self.tui_wrapper.exit()?;
-                break Ok(());
+    // The error is handled as its policy says; only an error to escalate, is given back, once the
+    // terminal has been handed back to the operator.
+    pub async fn on_error(&mut self, error: AppError) -> Result<(), AppError> {
+        match error.policy() {
+            FaultPolicy::LogAndContinue => {
+                info!("Console: {}, carrying on", error);
+                self.messages_for_ops.push_back(format!("Console: {}.", error));
+                Ok(())
+            },
+            FaultPolicy::Escalate | FaultPolicy::EnterFaultState => {
+                info!("Console: {}, giving up", error);
+                let _ = self.tui_wrapper.exit().await;
+                Err(error)
             }
         }
     }
 
-    fn handle_app_own_event(&mut self, e: Option<AppOwnEvent>) -> AppResult<()> {
-        match e {
-            Some(AppOwnEvent::Init) => info!("app received init!"),
+    // The console runs as an actor of its own, until the operator quits (or the recorded run is
+    // over): what it has measured and, while a recorded run is played again, how the run differed
+    // from the recording, are then handed back.
+    pub async fn run(self) -> AppResult<(MetricsReport, Option<DivergenceReport>)>
+    where
+        B: Send + 'static
+    {
+        let (outcome_tx, outcome_rx) = oneshot::channel();
+        let (_, console) = Actor::spawn(Some(String::from("Operator-Console")), UiActor::new(), (self, outcome_tx)).await?;
+        console.await?;
+        Ok(outcome_rx.await?)
+    }
 
-            Some(AppOwnEvent::Tick) => {}
+    // Whatever a car tells its operator, is journaled, and shown.
+    pub fn on_report_from_car(&mut self, console: &ActorRef<UiVocabulary>, car: CarIndex, report: ElevatorVocabulary) {
+        self.record(JournalEntry::FromCar(car, report.clone()));
+        self.on_replayed_report(car, &report);
 
-            Some(AppOwnEvent::Exit) => self.quit(),
+        match report {
+            ElevatorVocabulary::MoveToHomeFloor(_) => {
+                self.messages_for_ops.push_back(format!("Car {}: elevator is moving to its home floor.", car_letter(car)));
+                self.inner_infra.set_carriage_ready(car);
+            },
+            ElevatorVocabulary::MovingTo(f) => {
+                self.messages_for_ops.push_back(format!("Car {}: elevator is moving to floor({})", car_letter(car), self.inner_infra.building.floor_name(f)));
+                self.inner_infra.set_next_destination(car, f as u16);
+            }
+            ElevatorVocabulary::CurrentCarriagePosn(level) =>  {
+                self.inner_infra.on_carriage_moving_to(car, level);
+            },
+            ElevatorVocabulary::OpenTheDoor(f) => {
+                self.messages_for_ops.push_back(format!("Car {}: elevator has reached floor({}), door is open.", car_letter(car), self.inner_infra.building.floor_name(f)));
+                self.inner_infra.on_reaching_destination(car);
+                self.inner_infra.mark_floor_on_reaching_destination(car, f as u16);
+            }
+            ElevatorVocabulary::DoorClosed(f) => {
+                self.messages_for_ops.push_back(format!("Car {}: door is closed at floor({}).", car_letter(car), self.inner_infra.building.floor_name(f)));
+            }
+            ElevatorVocabulary::DoorStateChanged(_, state) => {
+                self.inner_infra.on_door_state_changed(car, state, self.clock.elapsed());
+            }
+            ElevatorVocabulary::HallCallAnswered { floor, direction } => {
+                self.inner_infra.on_hall_call_answered(floor as u16, direction);
+            },
+            ElevatorVocabulary::DestinationAssigned { from_floor, to_floor } => {
+                self.messages_for_ops.push_back(
+                    format!("Passenger at floor({}) going to floor({}): please take car {}.",
+                        self.inner_infra.building.floor_name(from_floor),
+                        self.inner_infra.building.floor_name(to_floor),
+                        car_letter(car)));
+                self.inner_infra.on_destination_assigned(to_floor as u16, car);
+            },
+            ElevatorVocabulary::CarCall { floor } => {
+                self.inner_infra.register_car_call(car, floor as u16);
+            },
+            ElevatorVocabulary::LoadWeighed(load) => {
+                self.inner_infra.on_load_weighed(car, load);
+            },
+            ElevatorVocabulary::Overloaded(f) => {
+                self.messages_for_ops.push_back(format!("Car {}: overloaded at floor({}), door is kept open.", car_letter(car), self.inner_infra.building.floor_name(f)));
+                self.inner_infra.on_door_held_open(car, true);
+            },
+            ElevatorVocabulary::OverloadCleared(f) => {
+                self.messages_for_ops.push_back(format!("Car {}: not overloaded anymore at floor({}), door will close.", car_letter(car), self.inner_infra.building.floor_name(f)));
+                self.inner_infra.on_door_held_open(car, false);
+            },
+            ElevatorVocabulary::ElevatorOutOfService => {
+                self.messages_for_ops.push_back(format!("Car {}: elevator is not operating anymore!", car_letter(car)));
+                self.inner_infra.unset_carriage(car);
+
+                // A recorded run is over when the operator says so.
+                if !self.inner_infra.is_any_carriage_shown() && self.replay.is_none() {
+                    self.messages_for_ops.push_back(String::from("Quitting application in 3 seconds."));
+                    // We are simulating the action of notifying that we are exiting.
+                    self.clock.send_after(Duration::from_secs(3), console.get_cell(), || UiVocabulary::Terminal(AppOwnEvent::Exit));
+                }
+            },
+            ElevatorVocabulary::Stop(_) => {},
+            _ => {}
+        }
+    }
+
+    // Only the reports of the group of cars started last, are heard.
+    pub fn is_current_group(&self, group_generation: usize) -> bool {
+        group_generation == self.group_generation
+    }
+
+    // After every message: a recorded run played again from the start, if it has been sought back,
+    // and the console woken up again, when the replay has next something to do. False, once the
+    // operator has quit.
+    pub async fn carry_on(&mut self, console: &ActorRef<UiVocabulary>) -> Result<bool, AppError> {
+        if self.to_restart_replay {
+            self.restart_replay(console).await?;
+        }
 
-            Some(AppOwnEvent::AllPassengersAlighted(at_floor)) => {
-                self.messages_for_ops
-                    .push_back(format!("Passengers have alighted at floor ({}). Door is closed.",at_floor));
-                self.inner_infra
-                    .mark_floor_on_reaching_destination(at_floor as u16);
-                self.passenger_lift
-                    .0
-                    .send_message(ElevatorVocabulary::DoorClosed(at_floor))
-                    .unwrap();
+        if self.should_quit_app() {
+            self.tui_wrapper.exit().await?;
+            return Ok(false);
+        }
+
+        self.set_replay_timer(console);
+        Ok(true)
+    }
+
+    // While a recorded run is played again: when its next command is due, and when it is to stop
+    // (at the end of the recording, or where it was sought).
+    fn set_replay_timer(&mut self, console: &ActorRef<UiVocabulary>) {
+        let next_command_at = self.replay.as_ref().and_then(Replay::next_command_at);
+        let stop_at = self.replay.as_ref().and_then(Replay::stop_at);
+        let wake_up_at = next_command_at.into_iter().chain(stop_at).min();
+
+        if let Some((at, timer)) = &self.replay_timer {
+            if Some(*at) == wake_up_at && !timer.is_finished() {
+                return;
             }
+            timer.abort();
+        }
+        self.replay_timer = wake_up_at.map(|at| {
+            (at, self.clock.send_at(at, console.get_cell(), || UiVocabulary::ReplayIsDue))
+        });
+    }
+
+    // A timer set before may still wake the console up: only what is due by now, is done.
+    pub fn on_replay_due(&mut self) {
+        let now = self.clock.elapsed();
+        if self.replay.as_ref().and_then(Replay::next_command_at).is_some_and(|at| at <= now) {
+            self.feed_replayed_command();
+        }
+        if self.replay.as_ref().and_then(Replay::stop_at).is_some_and(|at| at <= now) {
+            self.on_replay_stop();
+        }
+    }
 
-            Some(AppOwnEvent::Render) => 
+    pub fn handle_app_own_event(&mut self, e: AppOwnEvent) -> Result<(), AppError> {
+        match e {
+            AppOwnEvent::Init => info!("app received init!"),
+
+            AppOwnEvent::Tick => {}
+
+            AppOwnEvent::Exit => self.quit(),
+
+            AppOwnEvent::Render => {
+                self.inner_infra.service = self.service_report();
+                self.inner_infra.now = self.clock.elapsed();
                 self.tui_wrapper
-                    .draw(&self.inner_infra, &self.messages_for_ops)?,
-            Some(AppOwnEvent::Key(key_event)) =>
-                self.on_inputs_from_users(AppOwnEvent::Key(key_event)),
-            e @ Some(AppOwnEvent::Mouse(_)) => 
-                self.on_inputs_from_users(e.unwrap()),
-            None => {}
+                    .draw(&self.inner_infra, &self.messages_for_ops)?
+            },
+            // While a recorded run is played again, the keys control the playing, and the mouse
+            // does nothing: every call is made by the recording.
+            AppOwnEvent::Key(key_event) if self.replay.is_some() => self.on_replay_controls(key_event),
+            AppOwnEvent::Mouse(_) if self.replay.is_some() => {},
+            AppOwnEvent::Key(key_event) => {
+                self.record(JournalEntry::Input(AppOwnEvent::Key(key_event)));
+                self.on_inputs_from_users(AppOwnEvent::Key(key_event))
+            },
+            e @ AppOwnEvent::Mouse(_) => {
+                // Only the clicks: the mouse merely moving over the screen is no input.
+                if let AppOwnEvent::Mouse(MouseEvent { kind: MouseEventKind::Down(_), .. }) = e {
+                    self.record(JournalEntry::Input(e));
+                }
+                self.on_inputs_from_users(e)
+            },
             _ => {}
         }
 
         Ok(())
     }
 
-    pub fn on_inputs_from_users(&mut self, app_event: AppOwnEvent) -> () {
+    pub fn on_inputs_from_users(&mut self, app_event: AppOwnEvent) {
         match app_event {
             AppOwnEvent::Key(key) => match key.code {
                 KeyCode::Char('q') => {
@@ -208,60 +407,317 @@ impl<B: Backend> App<B> {
                     self.quit()
                 }
 
+                KeyCode::Char(key) if key.is_ascii_alphanumeric() && self.inner_infra.kiosk.is_active => {
+                    self.inner_infra.on_kiosk_key(key);
+                }
+
+                // A passenger (of average weight) boards the selected car, or alights from it.
+                KeyCode::Char('+') => {
+                    let car = self.inner_infra.selected_car;
+                    let load = self.inner_infra.load_with_passenger_boarding();
+                    self.messages_for_ops
+                        .push_back(format!("Passenger boards car {}.", car_letter(car)));
+                    self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::LoadWeighed(load)));
+                }
+
+                KeyCode::Char('-') => {
+                    let car = self.inner_infra.selected_car;
+                    if let Some(load) = self.inner_infra.load_with_passenger_alighting() {
+                        self.messages_for_ops
+                            .push_back(format!("Passenger alights from car {}.", car_letter(car)));
+                        self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::LoadWeighed(load)));
+                    }
+                }
+
+                // The door of the selected car: its open and close buttons, and somebody stepping
+                // into the doorway (breaking the beam of the photo-eye).
+                KeyCode::Char('<') => {
+                    let car = self.inner_infra.selected_car;
+                    self.messages_for_ops
+                        .push_back(format!("Passenger in car {} presses door-open.", car_letter(car)));
+                    self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::DoorOpenButton));
+                }
+
+                KeyCode::Char('>') => {
+                    let car = self.inner_infra.selected_car;
+                    self.messages_for_ops
+                        .push_back(format!("Passenger in car {} presses door-close.", car_letter(car)));
+                    self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::DoorCloseButton));
+                }
+
+                KeyCode::Char('!') => {
+                    let car = self.inner_infra.selected_car;
+                    self.messages_for_ops
+                        .push_back(format!("Somebody steps into the doorway of car {}.", car_letter(car)));
+                    self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::DoorObstructed));
+                }
+
+                KeyCode::Backspace if self.inner_infra.kiosk.is_active => {
+                    self.inner_infra.on_kiosk_backspace();
+                }
+
+                KeyCode::Enter if self.inner_infra.kiosk.is_active => {
+                    let lobby_floor = self.inner_infra.building.lobby_floor();
+                    match self.inner_infra.take_kiosk_entry() {
+                        Some(to_floor) if to_floor != lobby_floor as u16 => {
+                            self.messages_for_ops
+                                .push_back(format!("Passenger at the lobby wants floor {}.", self.inner_infra.building.floor_name(to_floor as u8)));
+                            self.command_group(GroupVocabulary::DestinationCall {
+                                from_floor: lobby_floor,
+                                to_floor: to_floor as u8,
+                            });
+                        }
+                        _ => self.messages_for_ops
+                                .push_back(String::from("Kiosk: no such floor to go to.")),
+                    }
+                }
+
                 _ => {}
             },
 
             AppOwnEvent::Mouse(m) => {
-                match m.kind {
-                    MouseEventKind::Down(MouseButton::Left) => {
-                        if let Some(floor_no) = self
-                            .inner_infra
-                            .is_passenger_waiting_at_reachable_floor(Position {
-                                x: m.column,
-                                y: m.row,
-                            })
-                        {
-                            self.messages_for_ops
-                                .push_back(format!("Passenger is waiting at {}!", floor_no));
-                            self.inner_infra.serve_passenger_at(floor_no);
-                            self.passenger_lift
-                                .0
-                                .send_message(
-                                    ElevatorVocabulary::MoveToFloor(floor_no as u8), // TODO: do we need u16?
-                                )
-                                .unwrap();
-                        } else if self.has_operator_pressed_start_button(Position {
-                            x: m.column,
-                            y: m.row,
-                        }) {
-                            info!("Elevator is starting!");
-                            self.messages_for_ops
-                                .push_back(String::from("Elevaror is starting!"));
-                            self.passenger_lift
-                                .0
-                                .send_message(ElevatorVocabulary::PowerOn)
-                                .unwrap();
-                        } else if self.has_operator_pressed_button_stop_button(Position {
+                if let MouseEventKind::Down(MouseButton::Left) = m.kind {
+                    if let Some((floor_no, direction)) = self
+                        .inner_infra
+                        .is_passenger_waiting_at_reachable_floor(Position {
                             x: m.column,
                             y: m.row,
-                        }) {
-                            info!("Elevator is stopping!");
-                            self.messages_for_ops
-                                .push_back(String::from("Elevator is stopping!"));
-                            self.passenger_lift
-                                .0
-                                .send_message(ElevatorVocabulary::PowerOff)
-                                .unwrap();
+                        })
+                    {
+                        self.messages_for_ops
+                            .push_back(format!("Passenger is waiting at {}, to go {:?}!", self.inner_infra.building.floor_name(floor_no as u8), direction));
+                        self.inner_infra.serve_passenger_at(floor_no, direction);
+                        self.command_group(
+                            GroupVocabulary::HallCall { floor: floor_no as u8, direction }, // TODO: do we need u16?
+                        );
+                    } else if let Some(floor_no) = self.has_passenger_pressed_car_button(Position {
+                        x: m.column,
+                        y: m.row,
+                    }) {
+                        let car = self.inner_infra.selected_car;
+                        self.messages_for_ops
+                            .push_back(format!("Passenger in car {} wants floor {}!", car_letter(car), self.inner_infra.building.floor_name(floor_no as u8)));
+                        self.inner_infra.register_car_call(car, floor_no);
+                        self.command_group(GroupVocabulary::ToCar(car, ElevatorVocabulary::CarCall { floor: floor_no as u8 }));
+                    } else if let Some(car) = self.inner_infra.is_car_shaft_clicked(Position {
+                        x: m.column,
+                        y: m.row,
+                    }) {
+                        self.messages_for_ops
+                            .push_back(format!("Car panel shows car {}.", car_letter(car)));
+                        self.inner_infra.select_car(car);
+                    } else if self.has_passenger_pressed_kiosk(Position {
+                        x: m.column,
+                        y: m.row,
+                    }) {
+                        let is_active = self.inner_infra.toggle_destination_dispatch();
+                        self.messages_for_ops.push_back(if is_active {
+                            String::from("Destination dispatch: type the floor at the kiosk, then press Enter.")
                         } else {
-                        }
+                            String::from("Destination dispatch is off.")
+                        });
+                    } else if self.has_operator_pressed_start_button(Position {
+                        x: m.column,
+                        y: m.row,
+                    }) {
+                        info!("Elevator is starting!");
+                        self.messages_for_ops
+                            .push_back(String::from("Elevaror is starting!"));
+                        self.command_group(GroupVocabulary::PowerOn);
+                    } else if self.has_operator_pressed_button_stop_button(Position {
+                        x: m.column,
+                        y: m.row,
+                    }) {
+                        info!("Elevator is stopping!");
+                        self.messages_for_ops
+                            .push_back(String::from("Elevator is stopping!"));
+                        self.command_group(GroupVocabulary::PowerOff);
                     }
-                    _ => {} // ignore other mouse events
-                };
+                }
             }
             _ => {} //ignore other App events
         };
     }
 
+    pub fn service_report(&self) -> MetricsReport {
+        self.metrics.report(self.clock.elapsed())
+    }
+
+    pub fn divergence(&self) -> Option<DivergenceReport> {
+        self.replay.as_ref().map(Replay::divergence)
+    }
+
+    fn feed_replayed_command(&mut self) {
+        let Some(command) = self.replay.as_mut().and_then(Replay::take_next_command) else {
+            return;
+        };
+
+        // What the operator's clicks had shown on screen, when the command was given.
+        match command {
+            GroupVocabulary::HallCall { floor, direction } =>
+                self.inner_infra.serve_passenger_at(floor as u16, direction),
+            GroupVocabulary::ToCar(car, ElevatorVocabulary::CarCall { floor }) =>
+                self.inner_infra.register_car_call(car, floor as u16),
+            _ => {}
+        }
+        self.messages_for_ops.push_back(format!("Replay ({:.1})s: {:?}", self.clock.elapsed().as_secs_f64(), command));
+        self.command_group(command);
+        self.pause_if_stepping();
+    }
+
+    fn on_replayed_report(&mut self, car: CarIndex, report: &ElevatorVocabulary) {
+        let at = self.clock.elapsed();
+        if self.replay.as_mut().is_some_and(|replay| replay.on_live_report(at, car, report)) {
+            self.pause_if_stepping();
+        }
+    }
+
+    fn pause_if_stepping(&mut self) {
+        if let Some(replay) = self.replay.as_mut().filter(|replay| replay.stepping) {
+            replay.stepping = false;
+            self.clock.pause();
+        }
+    }
+
+    fn on_replay_stop(&mut self) {
+        let now = self.clock.elapsed();
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+
+        self.clock.pause();
+        if !replay.has_ended && now >= replay.ends_at() {
+            replay.has_ended = true;
+            replay.seeking_to = None;
+            self.messages_for_ops.push_back(String::from("Replay: the recording is over."));
+            self.messages_for_ops.extend(replay.divergence().lines());
+        } else if replay.seeking_to.take().is_some() {
+            self.messages_for_ops.push_back(format!("Replay: paused at ({:.1})s.", now.as_secs_f64()));
+        }
+    }
+
+    // Space pauses (or goes on), 'n' goes on to the next event, '+' and '-' play faster and
+    // slower, '[' and ']' seek 10s back and forth, 'g' seeks to the second typed in.
+    fn on_replay_controls(&mut self, key: KeyEvent) {
+        let now = self.clock.elapsed();
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+
+        if let Some(entry) = replay.seek_entry.as_mut() {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => entry.push(c),
+                KeyCode::Backspace => { entry.pop(); },
+                KeyCode::Enter => match replay.seek_entry.take().and_then(|entry| entry.parse::<f64>().ok()) {
+                    Some(seconds) if seconds.is_finite() => self.seek(Duration::from_secs_f64(seconds.max(0.0))),
+                    _ => self.messages_for_ops.push_back(String::from("Replay: no such second to seek."))
+                },
+                KeyCode::Esc => replay.seek_entry = None,
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => {
+                info!("'q' pressed, replay is over.");
+                self.quit()
+            },
+            KeyCode::Char(' ') if self.clock.is_paused() => {
+                replay.seeking_to = None;
+                self.clock.set_speed(replay.speed);
+                self.messages_for_ops.push_back(format!("Replay: playing from ({:.1})s.", now.as_secs_f64()));
+            },
+            KeyCode::Char(' ') => {
+                replay.seeking_to = None;
+                self.clock.pause();
+                self.messages_for_ops.push_back(format!("Replay: paused at ({:.1})s.", now.as_secs_f64()));
+            },
+            KeyCode::Char('n') => {
+                replay.stepping = true;
+                replay.seeking_to = None;
+                self.clock.set_speed(replay.speed);
+            },
+            KeyCode::Char(c @ ('+' | '-')) => {
+                let speed = if c == '+' { replay.faster() } else { replay.slower() };
+                if !self.clock.is_paused() && replay.seeking_to.is_none() {
+                    self.clock.set_speed(speed);
+                }
+                self.messages_for_ops.push_back(format!("Replay: ({})x as fast as recorded.", speed));
+            },
+            KeyCode::Char('[') => self.seek(now.saturating_sub(Duration::from_secs(10))),
+            KeyCode::Char(']') => self.seek(now + Duration::from_secs(10)),
+            KeyCode::Char('g') => {
+                replay.seek_entry = Some(String::new());
+                self.messages_for_ops.push_back(String::from("Replay: type the second to seek, then press Enter."));
+            },
+            _ => {}
+        }
+    }
+
+    // Time only goes forward: to seek back, the run is played again from the start, by a fresh
+    // group of cars. Either way, the run is played fast up to where it was sought, then paused.
+    fn seek(&mut self, to: Duration) {
+        let now = self.clock.elapsed();
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+
+        replay.stepping = false;
+        replay.seeking_to = Some(to);
+        if to < now {
+            self.to_restart_replay = true;
+        } else {
+            self.clock.set_speed(SEEKING_SPEED);
+        }
+        self.messages_for_ops.push_back(format!("Replay: seeking ({:.1})s.", to.as_secs_f64()));
+    }
+
+    async fn restart_replay(&mut self, console: &ActorRef<UiVocabulary>) -> Result<(), AppError> {
+        self.to_restart_replay = false;
+
+        // The group of cars is started afresh first: without it, the replay carries on as it is.
+        let clock = SimulationClock::new(ClockMode::Scaled(SEEKING_SPEED));
+        let group_controller = Self::spawn_group(
+            format!("Group-Controller-{}", self.group_generation + 1),
+            &self.inner_infra,
+            self.operating_times,
+            &self.strategy,
+            &clock).await?;
+
+        if let Some(replay) = self.replay.as_mut() {
+            replay.start_over();
+        }
+
+        self.clock = clock;
+        if let Some((_, timer)) = self.replay_timer.take() {
+            timer.abort();
+        }
+        self.inner_infra = ElevatorVisualInfra::new(self.inner_infra.carriage_playground, &self.inner_infra.building, self.inner_infra.floor_levels.clone(), self.inner_infra.door_times);
+        self.metrics = ServiceMetrics::new(&self.inner_infra.building);
+
+        // Whatever the former cars have still to tell, is not heard anymore.
+        self.group_generation += 1;
+        let (former_group, former_handle) = std::mem::replace(&mut self.group_controller, group_controller);
+        former_group.kill();
+        let _ = former_handle.await;
+        self.observe_group(console);
+
+        Ok(())
+    }
+
+    fn has_passenger_pressed_car_button(&self, p: Position) -> Option<u16> {
+        self.tui_wrapper
+            .layout
+            .car_panel_buttons(self.inner_infra.building.floor_count())
+            .iter()
+            .position(|button| button.contains(p))
+            .map(|floor_no| floor_no as u16)
+    }
+
+    fn has_passenger_pressed_kiosk(&self, p: Position) -> bool {
+        self.tui_wrapper.layout.button_windows[self.tui_wrapper.layout.kiosk_index as usize].contains(p)
+    }
 
     fn has_operator_pressed_start_button(&self, p: Position) -> bool {
         self.tui_wrapper.layout.button_windows[0].contains(p)
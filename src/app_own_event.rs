@@ -1,9 +1,10 @@
 use crossterm::event::{KeyEvent, MouseEvent};
+use serde::{Deserialize, Serialize};
 
 
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum AppOwnEvent {
     Tick,
     Key(KeyEvent),
@@ -11,7 +12,6 @@ pub enum AppOwnEvent {
     Init,
     Error,
     Render,
-    AllPassengersAlighted(u8),
     Exit
 }
 
@@ -0,0 +1,303 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::AppResult;
+use crate::conversation::vocabulary::CarLoad;
+
+const DEFAULT_FLOOR_COUNT: u16 = 8;
+const DEFAULT_CAR_COUNT: usize = 2;
+const MX_CARS: usize = 8;
+const DEFAULT_RATED_LOAD: RatedLoad = RatedLoad { persons: 13, kg: 1000 };
+
+// A car loaded to this share of its rated load, is full: it answers no more hall calls.
+const FULL_LOAD_SHARE: f64 = 0.8;
+
+// The building, as described in a TOML (or JSON) file. Every entry is optional, e.g.:
+//
+//      floor_names = ["B2", "B1", "G", "M", "1", "2", "3"]
+//      lobby       = "G"
+//      home        = "B1"
+//      cars        = 3
+//      capacity    = { persons = 13, kg = 1000 }  # of every car
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuildingDescription {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    floor_count: Option<u16>,
+    floor_names: Option<Vec<String>>, // lowest floor first
+    lobby: Option<String>,
+    home: Option<String>,             // where the cars are parked, when out of service
+    cars: Option<usize>,
+    capacity: Option<RatedLoad>
+}
+
+/// How much a car may carry: so many persons, and so many kilograms.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RatedLoad {
+    pub persons: u16,
+    pub kg: u32
+}
+
+impl RatedLoad {
+    pub fn is_full(&self, load: &CarLoad) -> bool {
+        load.persons >= self.persons || load.kg as f64 >= self.kg as f64 * FULL_LOAD_SHARE
+    }
+
+    pub fn is_overloaded(&self, load: &CarLoad) -> bool {
+        load.persons > self.persons || load.kg > self.kg
+    }
+}
+
+/// The floors of the building, from the lowest (index 0) to the top, and the cars serving them.
+/// It is (de)serialized as its description, and checked alike.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "BuildingDescription", try_from = "BuildingDescription")]
+pub struct Building {
+    floor_names: Vec<String>,
+    lobby_floor: u8,
+    home_floor: u8,
+    car_count: usize,
+    rated_load: RatedLoad
+}
+
+impl Default for Building {
+    fn default() -> Self {
+        Building::with_floor_count(DEFAULT_FLOOR_COUNT).expect("The default floor count is in range")
+    }
+}
+
+impl TryFrom<BuildingDescription> for Building {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(description: BuildingDescription) -> AppResult<Building> {
+        Building::from_description(description)
+    }
+}
+
+impl From<Building> for BuildingDescription {
+    fn from(building: Building) -> Self {
+        BuildingDescription {
+            floor_count: None,
+            lobby: Some(building.floor_name(building.lobby_floor).to_owned()),
+            home: Some(building.floor_name(building.home_floor).to_owned()),
+            floor_names: Some(building.floor_names),
+            cars: Some(building.car_count),
+            capacity: Some(building.rated_load)
+        }
+    }
+}
+
+impl Building {
+    // Ground floor ('G') at the bottom, the others numbered upwards; the lobby and the home floor
+    // are both at the ground floor.
+    pub fn with_floor_count(floor_count: u16) -> AppResult<Building> {
+        Building::from_description(BuildingDescription { floor_count: Some(floor_count), ..BuildingDescription::default() })
+    }
+
+    // A file ending with '.json' is read as JSON; any other, as TOML.
+    pub fn load(path: &Path) -> AppResult<Building> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read building description ({}): {}", path.display(), e))?;
+
+        if path.extension().is_some_and(|extension| extension == "json") {
+            Building::from_json(&text)
+        } else {
+            Building::from_toml(&text)
+        }
+    }
+
+    pub fn from_toml(text: &str) -> AppResult<Building> {
+        Building::from_description(toml::from_str(text)?)
+    }
+
+    pub fn from_json(text: &str) -> AppResult<Building> {
+        Building::from_description(serde_json::from_str(text)?)
+    }
+
+    fn from_description(description: BuildingDescription) -> AppResult<Building> {
+        let floor_names = match (description.floor_names, description.floor_count) {
+            (Some(names), Some(count)) if names.len() != count as usize =>
+                return Err(format!("floor_count ({}) does not match the ({}) floor_names", count, names.len()).into()),
+            (Some(names), _) => names,
+            (None, count) => Building::default_floor_names(count.unwrap_or(DEFAULT_FLOOR_COUNT))
+        };
+
+        if floor_names.len() < 2 || floor_names.len() > u8::MAX as usize {
+            return Err(format!("A building must have 2 to {} floors, not ({})", u8::MAX, floor_names.len()).into());
+        }
+        if let Some(name) = floor_names.iter().enumerate().find_map(|(index, name)| {
+            floor_names[..index].iter().any(|earlier| earlier.eq_ignore_ascii_case(name)).then_some(name)
+        }) {
+            return Err(format!("Floor ({}) is named more than once", name).into());
+        }
+
+        let building = Building {
+            floor_names,
+            lobby_floor: 0,
+            home_floor: 0,
+            car_count: DEFAULT_CAR_COUNT,
+            rated_load: DEFAULT_RATED_LOAD
+        };
+        let floor_named = |name: Option<String>, default_floor: u8| match name {
+            Some(name) => building.floor_named(&name).ok_or(format!("There is no floor named ({})", name)),
+            None => Ok(default_floor)
+        };
+
+        let lobby_floor = floor_named(description.lobby, building.floor_named("G").unwrap_or(0))?;
+        let home_floor = floor_named(description.home, lobby_floor)?;
+        let car_count = description.cars.unwrap_or(DEFAULT_CAR_COUNT);
+
+        if car_count == 0 || car_count > MX_CARS {
+            return Err(format!("A building must have 1 to {} cars, not ({})", MX_CARS, car_count).into());
+        }
+
+        let rated_load = description.capacity.unwrap_or(DEFAULT_RATED_LOAD);
+        if rated_load.persons == 0 || rated_load.kg == 0 {
+            return Err(format!("A car must be able to carry somebody, not ({:?})", rated_load).into());
+        }
+
+        Ok(Building { lobby_floor, home_floor, car_count, rated_load, ..building })
+    }
+
+    fn default_floor_names(floor_count: u16) -> Vec<String> {
+        (0..floor_count)
+            .map(|floor| if floor == 0 { String::from("G") } else { floor.to_string() })
+            .collect()
+    }
+
+    pub fn floor_count(&self) -> u16 {
+        self.floor_names.len() as u16
+    }
+
+    pub fn car_count(&self) -> usize {
+        self.car_count
+    }
+
+    pub fn rated_load(&self) -> RatedLoad {
+        self.rated_load
+    }
+
+    pub fn lobby_floor(&self) -> u8 {
+        self.lobby_floor
+    }
+
+    pub fn home_floor(&self) -> u8 {
+        self.home_floor
+    }
+
+    pub fn floor_name(&self, floor: u8) -> &str {
+        &self.floor_names[floor as usize]
+    }
+
+    // Floor names are matched regardless of case: 'b1' is 'B1'.
+    pub fn floor_named(&self, name: &str) -> Option<u8> {
+        self.floor_names
+            .iter()
+            .position(|floor_name| floor_name.eq_ignore_ascii_case(name.trim()))
+            .map(|floor| floor as u8)
+    }
+
+    pub fn floor_label(&self, floor: u8) -> String {
+        if floor == self.lobby_floor {
+            format!("Floor {} (lobby)", self.floor_name(floor))
+        } else {
+            format!("Floor {}", self.floor_name(floor))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_nothing_is_described_then_ground_floor_is_lobby_and_home() {
+        let building = Building::from_toml("").unwrap();
+
+        assert_eq!(building, Building::default());
+        assert_eq!(building.floor_count(), 8);
+        assert_eq!(building.floor_name(0), "G");
+        assert_eq!(building.floor_label(3), "Floor 3");
+        assert_eq!(building.floor_label(0), "Floor G (lobby)");
+    }
+
+    #[test]
+    fn when_basements_are_described_then_lobby_is_above_them() {
+        let building = Building::from_toml(r#"
+            floor_names = ["B2", "B1", "G", "M", "1", "2"]
+            home        = "b1"
+            cars        = 3
+        "#).unwrap();
+
+        assert_eq!(building.floor_count(), 6);
+        assert_eq!(building.lobby_floor(), 2);
+        assert_eq!(building.home_floor(), 1);
+        assert_eq!(building.car_count(), 3);
+        assert_eq!(building.floor_named("M"), Some(3));
+        assert_eq!(building.rated_load(), DEFAULT_RATED_LOAD);
+    }
+
+    #[test]
+    fn when_capacity_is_described_then_cars_are_full_before_they_are_overloaded() {
+        let building = Building::from_toml(r#"capacity = { persons = 8, kg = 630 }"#).unwrap();
+        let rated_load = building.rated_load();
+
+        assert_eq!(rated_load, RatedLoad { persons: 8, kg: 630 });
+        assert!(!rated_load.is_full(&CarLoad { persons: 6, kg: 490 }));
+        assert!(rated_load.is_full(&CarLoad { persons: 7, kg: 520 }));
+        assert!(!rated_load.is_overloaded(&CarLoad { persons: 8, kg: 630 }));
+        assert!(rated_load.is_overloaded(&CarLoad { persons: 8, kg: 631 }));
+        assert!(Building::from_toml(r#"capacity = { persons = 0, kg = 630 }"#).is_err());
+    }
+
+    #[test]
+    fn when_described_in_json_then_it_is_read_alike() {
+        let building = Building::from_json(r#"{ "floor_count": 4, "lobby": "1" }"#).unwrap();
+
+        assert_eq!(building.floor_count(), 4);
+        assert_eq!(building.lobby_floor(), 1);
+        assert_eq!(building.home_floor(), 1);
+    }
+
+    #[test]
+    fn when_shipped_description_is_read_then_it_is_the_default_building() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("building.toml");
+
+        assert_eq!(Building::load(&path).unwrap(), Building::default());
+    }
+
+    #[test]
+    fn when_written_out_then_it_is_read_back_alike() {
+        let building = Building::from_toml(r#"
+            floor_names = ["B1", "G", "1"]
+            home        = "B1"
+            cars        = 1
+        "#).unwrap();
+
+        let text = serde_json::to_string(&building).unwrap();
+
+        assert_eq!(serde_json::from_str::<Building>(&text).unwrap(), building);
+        assert!(serde_json::from_str::<Building>(r#"{ "floor_names": ["G"] }"#).is_err());
+    }
+
+    #[test]
+    fn when_description_is_inconsistent_then_it_is_refused() {
+        assert!(Building::from_toml(r#"floor_names = ["G", "1"]
+                                        floor_count = 3"#).is_err());
+        assert!(Building::from_toml(r#"floor_names = ["G", "g"]"#).is_err());
+        assert!(Building::from_toml(r#"lobby = "Roof""#).is_err());
+        assert!(Building::from_toml(r#"floor_count = 1"#).is_err());
+        assert!(Building::from_toml(r#"cars = 9"#).is_err());
+        assert!(Building::from_toml(r#"basements = 2"#).is_err());
+        assert!(Building::from_toml(r#"capacity = { persons = 8 }"#).is_err());
+    }
+
+    #[test]
+    fn when_floor_count_is_out_of_range_then_it_is_refused() {
+        assert!(Building::with_floor_count(1).is_err());
+        assert!(Building::with_floor_count(256).is_err());
+        assert_eq!(Building::with_floor_count(255).unwrap().floor_name(254), "254");
+    }
+}
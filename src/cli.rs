@@ -0,0 +1,279 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use tracing::info;
+
+use crate::app::AppResult;
+use elevator_ratatui::building::Building;
+use elevator_ratatui::elevator_installation::dispatch_strategy::DISPATCH_STRATEGY_NAMES;
+use elevator_ratatui::elevator_installation::group_controller::OperatingTimes;
+use elevator_ratatui::elevator_installation::motion_profile::MotionProfile;
+use elevator_ratatui::elevator_installation::state_diagram::DIAGRAM_FORMATS;
+use crate::headless::{parse_script, random_traffic, HeadlessRun};
+use elevator_ratatui::passenger::{traffic_pattern_named, TrafficGenerator, TRAFFIC_PATTERN_NAMES};
+use elevator_ratatui::simulation_clock::ClockMode;
+
+// Without '--building', this file is read if it is in the current directory.
+const DEFAULT_BUILDING_DESCRIPTION: &str = "building.toml";
+
+/// Simulates the elevators of a building, on the terminal.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct CommandLine {
+    /// Describes the building (TOML, or JSON if the file ends with '.json') [default: ./building.toml, if any]
+    #[arg(short, long, value_name = "FILE")]
+    pub building: Option<PathBuf>,
+
+    /// A building of these many floors (G, 1, 2 ...), instead of the one described in a file
+    #[arg(short, long, conflicts_with = "building", value_parser = clap::value_parser!(u16).range(2..=255))]
+    pub floors: Option<u16>,
+
+    /// How the cars decide which call to answer next
+    #[arg(short, long, default_value = "look", value_parser = DISPATCH_STRATEGY_NAMES)]
+    pub strategy: String,
+
+    /// Milliseconds between two positions of a moving carriage
+    #[arg(long, value_name = "MILLIS", default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    pub step_interval: u64,
+
+    /// The fastest a carriage moves, in m/s
+    #[arg(long, value_name = "M/S", default_value_t = 1.6, value_parser = rate_per_second)]
+    pub rated_speed: f64,
+
+    /// How hard a carriage speeds up, in m/s²
+    #[arg(long, value_name = "M/S²", default_value_t = 1.0, value_parser = rate_per_second)]
+    pub acceleration: f64,
+
+    /// How hard a carriage brakes, in m/s²
+    #[arg(long, value_name = "M/S²", default_value_t = 1.0, value_parser = rate_per_second)]
+    pub deceleration: f64,
+
+    /// How fast the acceleration of a carriage may change, in m/s³
+    #[arg(long, value_name = "M/S³", default_value_t = 1.5, value_parser = rate_per_second)]
+    pub jerk: f64,
+
+    /// Metres from one floor to the next
+    #[arg(long, value_name = "METRES", default_value_t = 3.5, value_parser = rate_per_second)]
+    pub floor_height: f64,
+
+    /// Milliseconds the door takes to open
+    #[arg(long, value_name = "MILLIS", default_value_t = 1000)]
+    pub door_opening: u64,
+
+    /// Milliseconds the door is held open at every stop
+    #[arg(long, value_name = "MILLIS", default_value_t = 2000)]
+    pub door_dwell: u64,
+
+    /// Milliseconds the door takes to close
+    #[arg(long, value_name = "MILLIS", default_value_t = 1000)]
+    pub door_closing: u64,
+
+    /// Runs the simulation this many times faster than real time
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = rate_per_second)]
+    pub speed: f64,
+
+    /// Does not wait for anything: time jumps straight to whatever happens next (headless only)
+    #[arg(long, requires = "headless", conflicts_with = "speed")]
+    pub discrete_time: bool,
+
+    /// Ticks per second
+    #[arg(long, default_value_t = 1.0, value_parser = rate_per_second)]
+    pub tick_rate: f64,
+
+    /// Frames drawn per second
+    #[arg(long, default_value_t = 30.0, value_parser = rate_per_second)]
+    pub frame_rate: f64,
+
+    /// Where the log is written
+    #[arg(long, value_name = "FILE", default_value = "elevator.log")]
+    pub log_file: PathBuf,
+
+    /// What is logged, e.g. 'info' or 'elevator_ratatui=debug' [default: $RUST_LOG]
+    #[arg(long, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    /// Records every input, and everything the cars tell their operator, to this JSON-lines file
+    #[arg(long, value_name = "FILE")]
+    pub journal: Option<PathBuf>,
+
+    /// Plays a recorded journal again, on the same building: space pauses, 'n' steps, '+'/'-' change the speed, '['/']'/'g' seek
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "building", "floors", "strategy"])]
+    pub replay: Option<PathBuf>,
+
+    /// Writes the service quality (wait, ride and journey times ...) to this file at shutdown: CSV, or JSON if the file ends with '.json'
+    #[arg(long, value_name = "FILE")]
+    pub metrics: Option<PathBuf>,
+
+    /// Starts right away, without waiting for a key to be pressed
+    #[arg(short = 'y', long)]
+    pub no_prompt: bool,
+
+    /// Runs without a terminal, and writes out what the cars do [default: random traffic]
+    #[arg(long)]
+    pub headless: bool,
+
+    /// The calls made in a headless run, one per line: e.g. '500 hall 3 down', '900 car A 5', '1200 dest G 6'
+    #[arg(long, value_name = "FILE", requires = "headless")]
+    pub script: Option<PathBuf>,
+
+    /// Passengers turn up at the floors of this pattern, and press the buttons themselves [default: at the kiosk, anywhere]
+    #[arg(long, value_name = "PATTERN", conflicts_with = "script", value_parser = TRAFFIC_PATTERN_NAMES)]
+    pub traffic: Option<String>,
+
+    /// Passengers turning up in a headless run, without a script
+    #[arg(long, default_value_t = 20, conflicts_with = "script")]
+    pub passengers: usize,
+
+    /// Milliseconds between passengers turning up, on average
+    #[arg(long, value_name = "MILLIS", default_value_t = 3000, conflicts_with = "script")]
+    pub arrival_interval: u64,
+
+    /// Makes the same passengers turn up, run after run [default: a seed of its own, for every run]
+    #[arg(long, conflicts_with = "script")]
+    pub seed: Option<u64>,
+
+    /// Seconds a headless run may last, at most
+    #[arg(long, value_name = "SECS", default_value_t = 600)]
+    pub time_limit: u64,
+
+    /// Writes out the carriage positions too, in a headless run
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Writes out the state machine of an elevator, every transition of it, and does nothing else
+    #[arg(long, value_name = "FORMAT", value_parser = DIAGRAM_FORMATS)]
+    pub fsm_diagram: Option<String>
+}
+
+fn rate_per_second(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!("({}) is not a positive number", value))
+    }
+}
+
+impl CommandLine {
+    pub fn building(&self) -> AppResult<Building> {
+        if let Some(floor_count) = self.floors {
+            return Building::with_floor_count(floor_count);
+        }
+
+        match &self.building {
+            Some(path) => Building::load(path),
+            None => {
+                let path = PathBuf::from(DEFAULT_BUILDING_DESCRIPTION);
+                if path.exists() { Building::load(&path) } else { Ok(Building::default()) }
+            }
+        }
+    }
+
+    // The calls of a headless run: from the script, or else from passengers turning up at random,
+    // either entering their destination at a kiosk, or pressing the buttons of the traffic pattern.
+    pub fn headless_run(&self, building: Building) -> AppResult<HeadlessRun> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let arrival_interval = Duration::from_millis(self.arrival_interval);
+        let (calls, passengers) = match (&self.script, &self.traffic) {
+            (Some(path), _) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Cannot read script ({}): {}", path.display(), e))?;
+                (parse_script(&text, &building)?, Vec::new())
+            },
+            (None, Some(pattern)) => {
+                info!("Headless run: {} traffic, seed ({})", pattern, seed);
+                let pattern = traffic_pattern_named(pattern).ok_or(format!("Unknown traffic pattern ({})", pattern))?;
+                (Vec::new(), TrafficGenerator::new(pattern, arrival_interval, seed).passengers(&building, self.passengers))
+            },
+            (None, None) => {
+                info!("Headless run: random traffic, seed ({})", seed);
+                (random_traffic(&building, self.passengers, arrival_interval, seed), Vec::new())
+            }
+        };
+
+        Ok(HeadlessRun {
+            building,
+            operating_times: self.operating_times(),
+            strategy: self.strategy.clone(),
+            calls,
+            passengers,
+            time_limit: Duration::from_secs(self.time_limit),
+            clock_mode: self.clock_mode(),
+            journal: self.journal.clone(),
+            verbose: self.verbose
+        })
+    }
+
+    pub fn clock_mode(&self) -> ClockMode {
+        if self.discrete_time { ClockMode::DiscreteEvent }
+        else if self.speed != 1.0 { ClockMode::Scaled(self.speed) }
+        else { ClockMode::RealTime }
+    }
+
+    pub fn operating_times(&self) -> OperatingTimes {
+        OperatingTimes {
+            pulley_step_interval: Duration::from_millis(self.step_interval),
+            door_opening: Duration::from_millis(self.door_opening),
+            door_dwell: Duration::from_millis(self.door_dwell),
+            door_closing: Duration::from_millis(self.door_closing),
+            motion: MotionProfile {
+                rated_speed: self.rated_speed,
+                acceleration: self.acceleration,
+                deceleration: self.deceleration,
+                jerk: self.jerk,
+                floor_height: self.floor_height
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn when_nothing_is_given_then_defaults_are_as_before() {
+        CommandLine::command().debug_assert();
+
+        let options = CommandLine::parse_from(["elevator-ratatui"]);
+
+        assert_eq!(options.operating_times(), OperatingTimes::default());
+        assert_eq!((options.tick_rate, options.frame_rate), (1.0, 30.0));
+        assert_eq!(options.strategy, "look");
+        assert!(!options.no_prompt);
+        assert_eq!(options.clock_mode(), ClockMode::RealTime);
+    }
+
+    #[test]
+    fn when_floors_are_given_then_no_description_is_read() {
+        let options = CommandLine::parse_from(["elevator-ratatui", "--floors", "12", "-y", "--step-interval", "20"]);
+
+        assert_eq!(options.building().unwrap().floor_count(), 12);
+        assert_eq!(options.operating_times().pulley_step_interval, Duration::from_millis(20));
+        assert!(options.no_prompt);
+    }
+
+    #[test]
+    fn when_the_motion_is_given_then_the_carriages_move_so() {
+        let options = CommandLine::parse_from(["elevator-ratatui", "--rated-speed", "2.5", "--jerk", "2", "--floor-height", "4"]);
+
+        assert_eq!(options.operating_times().motion, MotionProfile {
+            rated_speed: 2.5,
+            jerk: 2.0,
+            floor_height: 4.0,
+            ..MotionProfile::default()
+        });
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--acceleration", "0"]).is_err());
+    }
+
+    #[test]
+    fn when_options_are_nonsensical_then_they_are_refused() {
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--floors", "1"]).is_err());
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--frame-rate", "0"]).is_err());
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--strategy", "random"]).is_err());
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--floors", "4", "--building", "b.toml"]).is_err());
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--discrete-time"]).is_err());
+        assert!(CommandLine::try_parse_from(["elevator-ratatui", "--replay", "run.jsonl", "--headless"]).is_err());
+    }
+}
@@ -1 +1,2 @@
-pub(crate) mod vocabulary;
+/// What the actors tell each other: every actor has a vocabulary of its own.
+pub mod vocabulary;
@@ -1,27 +1,140 @@
-use ractor::ActorRef;
+use std::fmt;
+use std::sync::Arc;
+
+use ractor::{ActorId, ActorRef, Message};
 use ractor_cluster::RactorMessage;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CallDirection {
+    Up,
+    Down
+}
+
+// What the load-weighing device of a car reads: so many persons, of so many kilograms in all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CarLoad {
+    pub persons: u16,
+    pub kg: u32
+}
+
+// Where the door of a car is, in its cycle at every stop. A door nudging is closing slowly, with
+// a buzzer, paying the photo-eye no heed: it has been re-opened too often.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+    Nudging
+}
+
+#[derive(RactorMessage)]
+pub enum DoorVocabulary {
+    Open(u8),     // from the elevator: the carriage has stopped at this floor
+    Obstructed,   // the photo-eye sees somebody in the doorway
+    OpenButton,   // pressed inside the carriage
+    CloseButton,  // pressed inside the carriage
+    Hold,         // from the elevator: the car is overloaded, the door must not close
+    Release,      // from the elevator: the load is reduced, the door may close after the dwell
+    TimeIsUp(u64), // the door has moved, or waited, as long as it had to (unless told otherwise since)
+    PowerOn(ActorRef<ElevatorVocabulary>),
+    PowerOff
+}
 
 #[derive(RactorMessage)]
 pub enum PulleyVocabulary {
     MoveToFloor(u8),
+    DivertToFloor(u8), // while moving, stop at this floor instead, if there is room to brake
     PulleyHasMoved,
     PowerOn(ActorRef<ElevatorVocabulary>),
     PowerOff
 }
 
-#[derive(RactorMessage, Clone, Debug, PartialEq)]
+#[derive(RactorMessage, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ElevatorVocabulary {
     DoorClosed(u8),
     OpenTheDoor(u8),
     MoveToFloor(u8),
+    HallCall { floor: u8, direction: CallDirection }, // pressed on the landing, outside the carriage
+    CarCall { floor: u8 },                            // pressed on the panel, inside the carriage
+    HallCallAnswered { floor: u8, direction: CallDirection }, // for information, from elevator to operator
+    DestinationAssigned { from_floor: u8, to_floor: u8 }, // for information, from group to operator
     Stop(u8),
     Stay(u8),
-    CurrentCarriagePosn((f64 /* x */, f64 /* y  */)),
+    CurrentCarriagePosn(Millimetres /* up the shaft, from the floor of the pit */),
     PowerOn,
     PowerOff,
-    MoveToGroundFloor, // emergency or regular shutdown
+    MoveToHomeFloor(u8), // on power on, emergency or regular shutdown
     MovingTo(u8), // for information, from elevator to operator
-    ElevatorOutOfService
+    DivertedTo(u8), // from pulley to elevator, the carriage will now stop at this floor
+    ElevatorOutOfService,
+    LoadWeighed(CarLoad), // from the load-weighing device to the elevator; passed on to operator, for information
+    Overloaded(u8),       // for information: the door is kept open at this floor, and the car does not move
+    OverloadCleared(u8),  // for information: the load is reduced, the door may close again
+    DoorObstructed,       // from the operator: somebody stands in the doorway of the car
+    DoorOpenButton,       // from the operator: the door-open button in the car is pressed
+    DoorCloseButton,      // from the operator: the door-close button in the car is pressed
+    DoorStateChanged(u8, DoorState) // from door to elevator; passed on to operator, for information
 }
 
 
+
+// Cars in a group are known by their position in the group: 0 is car 'A', 1 is car 'B', and so on.
+pub type CarIndex = usize;
+
+// Where a carriage is in its shaft, whatever the size of the screen it is drawn on.
+pub type Millimetres = u32;
+
+#[derive(RactorMessage, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GroupVocabulary {
+    HallCall { floor: u8, direction: CallDirection }, // the group decides which car answers it
+    DestinationCall { from_floor: u8, to_floor: u8 }, // entered at a kiosk; the group decides the car
+    ToCar(CarIndex, ElevatorVocabulary),              // car calls, door buttons, for one car only
+    FromCar(CarIndex, ElevatorVocabulary),            // whatever a car tells its operator
+    PowerOn,
+    PowerOff,
+    #[serde(skip)]
+    Subscribe(Observer)                               // from then on, the observer hears it too
+}
+
+/// Whoever wants to hear what the cars of a group tell their operator: a console, a recorder ...
+/// Each report reaches the observer as a message of its own vocabulary. Any number of observers
+/// may subscribe to one group.
+#[derive(Clone)]
+pub struct Observer {
+    id: ActorId,
+    name: Option<String>,
+    tell: Arc<dyn Fn(CarIndex, ElevatorVocabulary) -> bool + Send + Sync>
+}
+
+impl Observer {
+    pub fn new<TMsg, F>(observer: ActorRef<TMsg>, report: F) -> Self
+    where
+        TMsg: Message,
+        F: Fn(CarIndex, ElevatorVocabulary) -> TMsg + Send + Sync + 'static
+    {
+        Observer {
+            id: observer.get_id(),
+            name: observer.get_name(),
+            tell: Arc::new(move |car, message| observer.send_message(report(car, message)).is_ok())
+        }
+    }
+
+    // False, once the observer is not there anymore.
+    pub fn tell(&self, car: CarIndex, report: ElevatorVocabulary) -> bool {
+        (self.tell)(car, report)
+    }
+}
+
+impl PartialEq for Observer {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observer({:?})", self.name.as_deref().unwrap_or("unnamed"))
+    }
+}
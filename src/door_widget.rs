@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use ratatui::{
+    style::Color,
+    widgets::canvas::{Painter, Shape},
+};
+
+use elevator_ratatui::conversation::vocabulary::DoorState;
+use elevator_ratatui::elevator_installation::door_machinery::{DoorTimes, NUDGING_SLOWDOWN};
+
+/// What is drawn of the door of one car: how far its panels are apart, worked out from the last
+/// state the door reported and how long ago, so that they slide in between reports too.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DoorPanels {
+    state: DoorState,
+    since: Duration,     // simulated time
+    openness_then: f64,  // 0.0 closed, 1.0 open
+    held_open: bool,     // by an overload
+    obstructed: bool     // since the photo-eye re-opened it, till it is closed
+}
+
+impl Default for DoorPanels {
+    fn default() -> Self {
+        DoorPanels {
+            state: DoorState::Closed,
+            since: Duration::ZERO,
+            openness_then: 0.0,
+            held_open: false,
+            obstructed: false
+        }
+    }
+}
+
+impl DoorPanels {
+    pub fn on_state_changed(&mut self, state: DoorState, at: Duration, times: &DoorTimes) {
+        let openness = self.openness(at, times);
+        match (self.state, state) {
+            (DoorState::Closing | DoorState::Nudging, DoorState::Opening) => self.obstructed = true,
+            (_, DoorState::Nudging) => self.obstructed = true,
+            (_, DoorState::Closed) => self.obstructed = false,
+            _ => {}
+        }
+        self.state = state;
+        self.since = at;
+        self.openness_then = openness;
+    }
+
+    pub fn on_held_open(&mut self, held_open: bool) {
+        self.held_open = held_open;
+    }
+
+    // How far apart the panels are at the time: a re-opening door starts from where it was.
+    pub fn openness(&self, now: Duration, times: &DoorTimes) -> f64 {
+        let elapsed = now.saturating_sub(self.since);
+        let done = |takes: Duration| if takes.is_zero() { 1.0 } else { elapsed.as_secs_f64() / takes.as_secs_f64() };
+        match self.state {
+            DoorState::Closed  => 0.0,
+            DoorState::Open    => 1.0,
+            DoorState::Opening => (self.openness_then + done(times.opening)).min(1.0),
+            DoorState::Closing => (self.openness_then - done(times.closing)).max(0.0),
+            DoorState::Nudging => (self.openness_then - done(times.closing * NUDGING_SLOWDOWN)).max(0.0)
+        }
+    }
+
+    // Red while an overload keeps the door open, yellow while something stands in its way.
+    pub fn lamp(&self) -> Option<Color> {
+        if self.held_open { Some(Color::Red) }
+        else if self.obstructed { Some(Color::Yellow) }
+        else { None }
+    }
+}
+
+/// A carriage with its door: the frame, two panels sliding apart from the middle and, along the
+/// top, the lamp of the door (if it is lit).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarriageWithDoors {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub openness: f64,
+    pub lamp: Option<Color>
+}
+
+const FRAME_COLOUR: Color = Color::LightGreen;
+const PANEL_COLOUR: Color = Color::Gray;
+
+impl Shape for CarriageWithDoors {
+    fn draw(&self, painter: &mut Painter) {
+        let (Some((left, top)), Some((right, bottom))) = (
+            painter.get_point(self.x, self.y + self.height),
+            painter.get_point(self.x + self.width, self.y)
+        ) else {
+            return;
+        };
+
+        for x in left..=right {
+            painter.paint(x, top, self.lamp.unwrap_or(FRAME_COLOUR));
+            painter.paint(x, bottom, FRAME_COLOUR);
+        }
+        for y in top + 1..bottom {
+            painter.paint(left, y, FRAME_COLOUR);
+            painter.paint(right, y, FRAME_COLOUR);
+        }
+
+        // Each panel covers half the doorway when closed, and none of it when open.
+        let doorway = right.saturating_sub(left + 1);
+        let panel = ((doorway as f64 / 2.0) * (1.0 - self.openness.clamp(0.0, 1.0))).round() as usize;
+        for y in top + 1..bottom {
+            for x in (left + 1..left + 1 + panel).chain(right - panel..right) {
+                painter.paint(x, y, PANEL_COLOUR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{buffer::Buffer, layout::Rect, symbols::Marker, widgets::{canvas::Canvas, Widget}};
+
+    use super::*;
+
+    const TIMES: DoorTimes = DoorTimes {
+        opening: Duration::from_secs(1),
+        dwell: Duration::from_secs(3),
+        closing: Duration::from_secs(2)
+    };
+
+    fn at(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn when_the_door_is_obstructed_while_closing_then_it_reopens_from_where_it_was_and_the_lamp_is_lit() {
+        let mut door = DoorPanels::default();
+
+        door.on_state_changed(DoorState::Opening, at(0), &TIMES);
+        assert_eq!(door.openness(at(500), &TIMES), 0.5);
+        door.on_state_changed(DoorState::Open, at(1_000), &TIMES);
+        door.on_state_changed(DoorState::Closing, at(4_000), &TIMES);
+        assert_eq!(door.openness(at(5_000), &TIMES), 0.5);
+        assert_eq!(door.lamp(), None);
+
+        door.on_state_changed(DoorState::Opening, at(5_500), &TIMES);
+        assert_eq!(door.openness(at(5_500), &TIMES), 0.25);
+        assert_eq!(door.openness(at(6_000), &TIMES), 0.75);
+        assert_eq!(door.openness(at(9_000), &TIMES), 1.0);
+        assert_eq!(door.lamp(), Some(Color::Yellow));
+
+        door.on_held_open(true);
+        assert_eq!(door.lamp(), Some(Color::Red));
+        door.on_held_open(false);
+
+        door.on_state_changed(DoorState::Nudging, at(10_000), &TIMES);
+        assert_eq!(door.openness(at(11_000), &TIMES), 0.75);
+        door.on_state_changed(DoorState::Closed, at(14_000), &TIMES);
+        assert_eq!(door.openness(at(14_000), &TIMES), 0.0);
+        assert_eq!(door.lamp(), None);
+    }
+
+    // Twelve columns and six rows of half blocks: twelve pixels each way, the carriage over all of them.
+    fn drawn(carriage: CarriageWithDoors) -> Buffer {
+        let area = Rect::new(0, 0, 12, 6);
+        let mut buffer = Buffer::empty(area);
+        Canvas::default()
+            .marker(Marker::HalfBlock)
+            .x_bounds([0.0, 12.0])
+            .y_bounds([0.0, 12.0])
+            .paint(|ctx| ctx.draw(&carriage))
+            .render(area, &mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn when_the_door_opens_then_its_panels_slide_apart_from_the_middle() {
+        let carriage = CarriageWithDoors { x: 0.0, y: 0.0, width: 12.0, height: 12.0, openness: 0.0, lamp: None };
+
+        let closed = drawn(carriage.clone());
+        let half_open = drawn(CarriageWithDoors { openness: 0.5, ..carriage.clone() });
+        let open = drawn(CarriageWithDoors { openness: 1.0, lamp: Some(Color::Red), ..carriage });
+
+        assert_eq!(closed.get(5, 2).fg, PANEL_COLOUR);
+        assert_eq!(half_open.get(2, 2).fg, PANEL_COLOUR);
+        assert_eq!(half_open.get(5, 2).fg, Color::Reset);
+        assert_eq!(half_open.get(9, 2).fg, PANEL_COLOUR);
+        assert_eq!(open.get(2, 2).fg, Color::Reset);
+        assert_eq!(open.get(0, 2).fg, FRAME_COLOUR);
+        assert_eq!(open.get(5, 0).fg, Color::Red);
+        assert_eq!(closed.get(5, 0).fg, FRAME_COLOUR);
+    }
+}
@@ -1,66 +1,82 @@
 
 
 
-use ratatui::{layout::{Margin, Position}, prelude::{Color, Rect}, widgets::canvas::Rectangle};
-use tracing::info;
+use std::time::Duration;
 
-pub const MX_FLOORS: u16 = 8;
+use ratatui::{layout::{Margin, Position}, prelude::Rect};
 
+use elevator_ratatui::building::Building;
+use elevator_ratatui::conversation::vocabulary::{CallDirection, CarIndex, CarLoad, DoorState, Millimetres};
+use crate::door_widget::DoorPanels;
+use elevator_ratatui::elevator_installation::door_machinery::DoorTimes;
+use elevator_ratatui::elevator_installation::shaft::FloorLevels;
+use crate::metrics::MetricsReport;
+use elevator_ratatui::passenger::AVERAGE_PASSENGER_KG;
+
+
+// Where the carriage of a car is drawn in its shaft, on screen: only its height above the lowest
+// floor changes, and that is worked out from its level in the shaft, as it is drawn.
 #[derive(Debug)]
 pub struct CarriageBox {
     pub  bottom_left_x_offset_from_origin: f64,
-    pub  bottom_left_y_offset_from_origin: f64,
     pub  width:  f64,
     pub  height: f64
 }
 
-impl CarriageBox {
-    pub fn _move_up(&mut self, displacement: f64) -> &mut Self {
-        self.bottom_left_y_offset_from_origin += displacement;
-        self
-    }
-
-    pub fn _move_down(&mut self, displacement: f64) -> &mut Self {
-        self.bottom_left_y_offset_from_origin -= displacement;
-        self
-    }
-
-    pub fn move_to_ground(&mut self) -> &mut Self {
-        self.bottom_left_y_offset_from_origin = 0.0;
-        self
-    }
+// Everything drawn for one car: its carriage in its own shaft, and the lamps of its car panel.
+#[derive(Debug)]
+pub struct CarVisualInfra {
+    pub carriage_box: CarriageBox,
+    pub level: Millimetres, // as last told by the pulley
+    pub car_calls: Vec<bool>,
+    pub dest_floor: Option<u16>,
+    pub current_floor: Option<u16>,
+    pub load: CarLoad, // as last weighed
+    pub door: DoorPanels,
+    show_carriage_box: bool,
+    destination_reached: bool
 }
 
-#[derive(Debug)]
-pub struct Separator {
-    pub start_x: f64,
-    pub start_y: f64,
-    pub end_x:   f64,
-    pub end_y:   f64
+// In destination-dispatch mode, a passenger at the lobby enters the floor to go to, at the kiosk,
+// and is told which car to take.
+#[derive(Debug, Default)]
+pub struct DestinationKiosk {
+    pub is_active: bool,
+    pub entry: String,
+    pub last_assignment: Option<(u16, CarIndex)>
 }
 
 #[derive(Debug)]
 pub struct ElevatorVisualInfra {
-    pub carriage_box: CarriageBox,
+    pub building: Building,
+    pub floor_levels: FloorLevels,
+    pub door_times: DoorTimes,
     pub carriage_playground: Rect,
     pub each_floor_height: u16,
+    pub each_floor_width: u16,
+    pub each_shaft_width: u16,
     pub floor_as_rects: Vec<Rect>,
-    pub floors_having_passengers: Vec<bool>,
-    pub dest_floor: Option<u16>,
-    pub current_floor: Option<u16>,
-    show_carriage_box: bool,
-    destination_reached: bool,
+    pub hall_calls_up: Vec<bool>,
+    pub hall_calls_down: Vec<bool>,
+    pub cars: Vec<CarVisualInfra>,
+    pub selected_car: CarIndex,
+    pub kiosk: DestinationKiosk,
+    pub service: MetricsReport, // as of the last frame drawn
+    pub now: Duration // simulated time, as of the last frame drawn
 
 }
 
 impl  ElevatorVisualInfra {
-    pub(crate) fn new(movement_area: Rect) -> Self {
+    pub(crate) fn new(movement_area: Rect, building: &Building, floor_levels: FloorLevels, door_times: DoorTimes) -> Self {
+
+        let mx_floors = building.floor_count();
+        let mx_cars = building.car_count();
 
         let carriage_playground = movement_area.inner(&Margin{ horizontal: 1, vertical: 1});
 
         let each_floor_height = 
             f64::floor(
-                (carriage_playground.height as f64) / MX_FLOORS as f64
+                (carriage_playground.height as f64) / mx_floors as f64
             ) 
             as u16;
 
@@ -69,17 +85,17 @@ impl  ElevatorVisualInfra {
         //  Leave a little space from left border
         let each_floor_top_left_x: u16 = carriage_playground.x; 
 
-        //  Measured from the wall separating the tunnel and floors
-        //  Leave a little space from the right border
-        let each_floor_width = (carriage_playground.width as f64 / 2.0) as u16 ;  
+        //  Measured from the wall separating the tunnels and floors
+        //  The floors and every car's tunnel (shaft) get an equal share of the width
+        let each_floor_width = (carriage_playground.width as f64 / (mx_cars + 1) as f64) as u16 ;  
+        let each_shaft_width = (carriage_playground.width - each_floor_width) / mx_cars as u16;
 
         //  Obviously, every floor will have a different top_left_y
         let floor_specific_top_left_y: Vec<u16> = 
-                        (0..MX_FLOORS)
+                        (0..mx_floors)
                         .rev()
-                        .into_iter()
                         .map(|next_floor| {
-                            carriage_playground.y as u16 + (each_floor_height * next_floor)
+                            carriage_playground.y + (each_floor_height * next_floor)
                         })
                         .collect()
                         ;
@@ -97,88 +113,223 @@ impl  ElevatorVisualInfra {
                 .collect()
                 ;        
 
-        let carriage_box = CarriageBox {
-            bottom_left_x_offset_from_origin:  carriage_playground.width as f64/2.0,
-            bottom_left_y_offset_from_origin:  0.0,
-            width:                             carriage_playground.width as f64/2.0,
-            height:                            each_floor_height as f64
-        };
+        let cars = (0..mx_cars)
+                .map(|car| CarVisualInfra {
+                    carriage_box: CarriageBox {
+                        bottom_left_x_offset_from_origin:  (each_floor_width + car as u16 * each_shaft_width) as f64,
+                        width:                             each_shaft_width as f64,
+                        height:                            each_floor_height as f64
+                    },
+                    level: floor_levels.level(0),
+                    car_calls: vec![false; mx_floors as usize],
+                    show_carriage_box: false, // TODO: use a flag to indicate if elev is operation (Start/Stop)
+                    dest_floor: None,
+                    current_floor: None,
+                    load: CarLoad::default(),
+                    door: DoorPanels::default(),
+                    destination_reached: false
+                })
+                .collect();
 
         //info!("carriage box {:?}", carriage_box);
 
-        let floors_having_passengers: Vec<bool> = vec![false; MX_FLOORS as usize];
 
         ElevatorVisualInfra {
-            carriage_box,
+            building: building.clone(),
+            floor_levels,
+            door_times,
             carriage_playground,
             each_floor_height,
+            each_floor_width,
+            each_shaft_width,
             floor_as_rects: all_floors_represented_as_rects,
-            floors_having_passengers: floors_having_passengers,
-            show_carriage_box: false, // TODO: use a flag to indicate if elev is operation (Start/Stop)
-            dest_floor: None,
-            current_floor: None,
-            destination_reached: false
+            hall_calls_up: vec![false; mx_floors as usize],
+            hall_calls_down: vec![false; mx_floors as usize],
+            cars,
+            selected_car: 0,
+            kiosk: DestinationKiosk::default(),
+            service: MetricsReport::default(),
+            now: Duration::ZERO
 
         }
     }
 
-    pub fn set_carriage_ready(&mut self) -> () {
-        self.carriage_box.move_to_ground();
-        self.show_carriage_box = true;
+    pub fn set_carriage_ready(&mut self, car: CarIndex) {
+        self.cars[car].level = self.floor_levels.level(0);
+        self.cars[car].show_carriage_box = true;
     }
 
-    pub fn unset_carriage(&mut self) -> () {
-        self.show_carriage_box =  false;
-        self.current_floor = None;
+    pub fn unset_carriage(&mut self, car: CarIndex) {
+        self.cars[car].show_carriage_box =  false;
+        self.cars[car].current_floor = None;
     }
 
-    pub fn should_show_carriage(&self) -> bool {
-        self.show_carriage_box
+    pub fn should_show_carriage(&self, car: CarIndex) -> bool {
+        self.cars[car].show_carriage_box
     }
 
-    pub fn get_carriage_displacement_map_per_floor(&self,origin: (u16,u16)) -> Vec<(f64,f64)> {
-        self.floor_as_rects.iter().enumerate()
-                .map(|(floor_index,rect)| {
-                    let left_bottom_y = origin.1 + (floor_index as u16 * rect.height);
-                    (0.0,left_bottom_y as f64)    // X coordinates for all floors remain unchanged
-                })
-                .collect()  
+    pub fn is_any_carriage_shown(&self) -> bool {
+        self.cars.iter().any(|car| car.show_carriage_box)
+    }
+
+    // How high the carriage is drawn above the lowest floor: as far between two floors on screen,
+    // as it is between them in the shaft.
+    pub fn carriage_offset_from_origin(&self, car: CarIndex) -> f64 {
+        self.floor_levels.floors_above_lowest(self.cars[car].level) * self.each_floor_height as f64
     }
 
-    pub fn is_passenger_waiting_at_reachable_floor(&self,mouse_click_position: Position) -> Option<u16> {
+    // Every floor has two buttons on its landing: the left half of the floor is for going up and
+    // the right half, for going down. The top floor can only go down, and the bottom floor, only up.
+    pub fn is_passenger_waiting_at_reachable_floor(&self,mouse_click_position: Position) -> Option<(u16, CallDirection)> {
+
+        let top_floor = (self.floor_as_rects.len() - 1) as u16;
 
         for next_floor in self.floor_as_rects.iter().enumerate() {
             if next_floor.1.contains(mouse_click_position) {
-                return Some(next_floor.0 as u16);
+                let floor_no = next_floor.0 as u16;
+                let on_left_half = mouse_click_position.x < next_floor.1.x + next_floor.1.width / 2;
+                let direction = 
+                    if floor_no == top_floor { CallDirection::Down }
+                    else if floor_no == 0 || on_left_half { CallDirection::Up }
+                    else { CallDirection::Down };
+                return Some((floor_no, direction));
             }     
         }
 
         None
     }
 
-    pub fn serve_passenger_at(&mut self, at_floor: u16) -> () {
-        self.floors_having_passengers[at_floor as usize] = true;
+    // Shafts stand side by side, to the right of the floors; clicking on one selects its car.
+    pub fn is_car_shaft_clicked(&self, mouse_click_position: Position) -> Option<CarIndex> {
+        (0..self.cars.len())
+            .map(|car| Rect {
+                x:      self.carriage_playground.x + self.each_floor_width + car as u16 * self.each_shaft_width,
+                y:      self.carriage_playground.y,
+                width:  self.each_shaft_width,
+                height: self.carriage_playground.height
+            })
+            .position(|shaft| shaft.contains(mouse_click_position))
     }
 
+    pub fn select_car(&mut self, car: CarIndex) {
+        self.selected_car = car;
+    }
 
+    pub fn toggle_destination_dispatch(&mut self) -> bool {
+        self.kiosk = DestinationKiosk { is_active: !self.kiosk.is_active, ..DestinationKiosk::default() };
+        self.kiosk.is_active
+    }
 
-    pub fn mark_floor_on_reaching_destination(&mut self, dest_floor: u16) -> () {
-        self.floors_having_passengers[dest_floor as usize] = false;
+    pub fn on_kiosk_key(&mut self, key: char) {
+        if self.kiosk.entry.len() < 3 {
+            self.kiosk.entry.push(key.to_ascii_uppercase());
+        }
     }
 
-    pub fn set_next_destination(&mut self, to_floor: u16) {
-        self.dest_floor = Some(to_floor);
+    pub fn on_kiosk_backspace(&mut self) {
+        self.kiosk.entry.pop();
     }
 
-    pub fn on_reaching_destination(&mut self) -> () {
-        self.destination_reached = true;
-        self.current_floor = self.dest_floor;
-        self.dest_floor = None;
+    // The floor entered so far (by its name), if there is such a floor.
+    pub fn take_kiosk_entry(&mut self) -> Option<u16> {
+        self.building
+            .floor_named(&std::mem::take(&mut self.kiosk.entry))
+            .map(|floor| floor as u16)
     }
 
-    pub fn on_carriage_moving_to(&mut self,  move_to: (f64,f64)) {
-        self.carriage_box.bottom_left_y_offset_from_origin = move_to.1;
+    pub fn on_destination_assigned(&mut self, to_floor: u16, car: CarIndex) {
+        self.kiosk.last_assignment = Some((to_floor, car));
     }
 
+    pub fn serve_passenger_at(&mut self, at_floor: u16, direction: CallDirection) {
+        match direction {
+            CallDirection::Up   => self.hall_calls_up[at_floor as usize] = true,
+            CallDirection::Down => self.hall_calls_down[at_floor as usize] = true
+        }
+    }
 
+    pub fn on_hall_call_answered(&mut self, at_floor: u16, direction: CallDirection) {
+        match direction {
+            CallDirection::Up   => self.hall_calls_up[at_floor as usize] = false,
+            CallDirection::Down => self.hall_calls_down[at_floor as usize] = false
+        }
+    }
+
+    pub fn register_car_call(&mut self, car: CarIndex, to_floor: u16) {
+        self.cars[car].car_calls[to_floor as usize] = true;
+    }
+
+    pub fn mark_floor_on_reaching_destination(&mut self, car: CarIndex, dest_floor: u16) {
+        self.cars[car].car_calls[dest_floor as usize] = false;
+    }
+
+    pub fn set_next_destination(&mut self, car: CarIndex, to_floor: u16) {
+        self.cars[car].dest_floor = Some(to_floor);
+    }
+
+    pub fn on_reaching_destination(&mut self, car: CarIndex) {
+        let car = &mut self.cars[car];
+        car.destination_reached = true;
+        car.current_floor = car.dest_floor;
+        car.dest_floor = None;
+    }
+
+    pub fn on_load_weighed(&mut self, car: CarIndex, load: CarLoad) {
+        self.cars[car].load = load;
+    }
+
+    // The load of the selected car, should a passenger (of average weight) board it, or alight from it.
+    pub fn load_with_passenger_boarding(&self) -> CarLoad {
+        let load = self.cars[self.selected_car].load;
+        CarLoad { persons: load.persons + 1, kg: load.kg + AVERAGE_PASSENGER_KG }
+    }
+
+    pub fn load_with_passenger_alighting(&self) -> Option<CarLoad> {
+        let load = self.cars[self.selected_car].load;
+        (load.persons > 0).then(|| CarLoad { persons: load.persons - 1, kg: load.kg.saturating_sub(AVERAGE_PASSENGER_KG) })
+    }
+
+    pub fn on_carriage_moving_to(&mut self, car: CarIndex, level: Millimetres) {
+        self.cars[car].level = level;
+    }
+
+    pub fn on_door_state_changed(&mut self, car: CarIndex, state: DoorState, at: Duration) {
+        let door_times = self.door_times;
+        self.cars[car].door.on_state_changed(state, at, &door_times);
+    }
+
+    pub fn on_door_held_open(&mut self, car: CarIndex, held_open: bool) {
+        self.cars[car].door.on_held_open(held_open);
+    }
+
+    // How far apart the panels of the door of the car are drawn, in the frame being drawn.
+    pub fn door_openness(&self, car: CarIndex) -> f64 {
+        self.cars[car].door.openness(self.now, &self.door_times)
+    }
+
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elevator_ratatui::elevator_installation::group_controller::OperatingTimes;
+    use elevator_ratatui::elevator_installation::shaft::PIT_DEPTH;
+
+    #[test]
+    fn when_the_terminal_is_another_size_then_the_carriage_is_drawn_as_far_up_the_shaft() {
+        let building = Building::with_floor_count(4).unwrap();
+        let floor_levels = FloorLevels::evenly_spaced(4, 3.5);
+        let door_times = OperatingTimes::default().door_times();
+        let small = &mut ElevatorVisualInfra::new(Rect::new(0, 0, 40, 18), &building, floor_levels.clone(), door_times);
+        let large = &mut ElevatorVisualInfra::new(Rect::new(0, 0, 120, 42), &building, floor_levels, door_times);
+
+        for infra in [&mut *small, &mut *large] {
+            infra.on_carriage_moving_to(0, PIT_DEPTH + 8_750); // half way from floor 2 to floor 3
+        }
+
+        assert_eq!(small.carriage_offset_from_origin(0), 2.5 * small.each_floor_height as f64);
+        assert_eq!(large.carriage_offset_from_origin(0), 2.5 * large.each_floor_height as f64);
+        assert_eq!(small.cars[0].level, large.cars[0].level);
+    }
 }
@@ -1,4 +1,18 @@
-mod carriage_machinery;
-mod elevator_operations;
-pub(crate) mod elevator_service;
-pub(crate) mod pulley_machinery;
\ No newline at end of file
+pub mod carriage_machinery;
+mod destination_dispatch;
+pub mod dispatch_strategy;
+pub mod door_machinery;
+pub mod elevator_operations;
+pub mod fault_policy;
+pub mod motion_profile;
+mod pending_calls;
+pub mod elevator_service;
+pub mod group_controller;
+pub mod pulley_machinery;
+pub mod shaft;
+pub mod state_diagram;
+pub mod travel_scheduler;
+#[cfg(test)]
+mod model_checking;
+#[cfg(test)]
+mod generators;
@@ -1,22 +1,39 @@
 
+use crate::building::{Building, RatedLoad};
+use crate::conversation::vocabulary::{CallDirection, CarLoad};
+use crate::elevator_installation::dispatch_strategy::DispatchStrategy;
+use crate::elevator_installation::pending_calls::PendingCalls;
+use crate::elevator_installation::travel_scheduler::{TravelDirection, TravelScheduler};
+
+/// Where the carriage is and is headed, the calls it has yet to answer, and what it carries.
 #[derive(Debug)]
 pub struct CarriageData{
     current_floor: u8,
     dest_floor: Option<u8>,
-    next_dests_waiting_list: Vec<u8>,
+    pending_calls: PendingCalls,
+    scheduler: TravelScheduler,
     mx_floors: u16,
+    home_floor: u8,
+    rated_load: RatedLoad,
+    load: CarLoad,
     emergency_op_requested: bool
 }
 
 impl CarriageData {
     pub fn new(
-        mx_floors: u16, 
+        building: &Building, 
+        dispatch_strategy: Box<dyn DispatchStrategy>
     ) -> Self {
+            let mx_floors = building.floor_count();
             CarriageData {
                 current_floor: 0,
                 dest_floor: None,
-                next_dests_waiting_list: Vec::new(),
+                pending_calls: PendingCalls::new(),
+                scheduler: TravelScheduler::new(dispatch_strategy, (mx_floors - 1u16) as u8),
                 mx_floors: mx_floors - 1u16, // floors are zero-indexed, 0 to (mx_floors - 1),
+                home_floor: building.home_floor(),
+                rated_load: building.rated_load(),
+                load: CarLoad::default(),
                 emergency_op_requested: false
             }
     }
@@ -24,6 +41,11 @@ impl CarriageData {
         self.current_floor
     }
 
+    // Where the carriage is parked, when it is taken out of service.
+    pub fn home_floor(&self) -> u8 {
+        self.home_floor
+    }
+
     pub fn already_at_floor(&self, floor_index: u8) -> bool {
         self.current_floor == floor_index
     }
@@ -31,33 +53,98 @@ impl CarriageData {
     pub fn set_next_destination(&mut self, dest_floor: u8) -> u8 {
         if dest_floor as u16 <= self.mx_floors {
             self.dest_floor = Some(dest_floor);
+            self.scheduler.on_departure(self.current_floor, dest_floor);
         }
         self.current_floor
     }
 
-    pub fn enqueue_next_destination(&mut self, floor_id: u8) -> () {
-        self.next_dests_waiting_list.push(floor_id);
+    // A car call: the floor has been pressed inside the carriage.
+    pub fn enqueue_next_destination(&mut self, floor_id: u8) {
+        if floor_id as u16 <= self.mx_floors {
+            self.pending_calls.add_car_call(floor_id);
+        }
     }
 
-    pub fn on_arrival(&mut self) -> u8 {
-        self.current_floor = self.dest_floor.take().unwrap();
-        self.current_floor
+    pub fn enqueue_hall_call(&mut self, floor_id: u8, direction: CallDirection) {
+        if floor_id as u16 <= self.mx_floors {
+            self.pending_calls.add_hall_call(floor_id, direction);
+        }
+    }
+
+    pub fn travel_direction(&self) -> TravelDirection {
+        self.scheduler.direction()
+    }
+
+    pub fn dispatch_strategy_name(&self) -> &'static str {
+        self.scheduler.strategy_name()
+    }
+
+    // The carriage is where it was headed; without a destination, it has not gone anywhere.
+    pub fn on_arrival(&mut self) -> Option<u8> {
+        let arrived_at = self.dest_floor.take()?;
+        self.current_floor = arrived_at;
+        Some(arrived_at)
+    }
+
+    pub fn serves_floor(&self, floor_id: u8) -> bool {
+        floor_id as u16 <= self.mx_floors
+    }
+
+    // Is the floor between where the carriage has departed from, and where it is going to?
+    pub fn is_on_the_way(&self, floor_id: u8) -> bool {
+        match self.dest_floor {
+            Some(dest) if dest > self.current_floor => floor_id > self.current_floor && floor_id < dest,
+            Some(dest) if dest < self.current_floor => floor_id < self.current_floor && floor_id > dest,
+            _ => false
+        }
     }
 
-    pub fn any_destination_in_queue(&self) -> bool  {
-        !self.next_dests_waiting_list.is_empty()
+    // The hall call at `floor_id` is answered on the way, only if it is for the direction of travel.
+    pub fn is_on_the_way_for(&self, floor_id: u8, direction: CallDirection) -> bool {
+        self.is_on_the_way(floor_id) && matches!(
+            (direction, self.travel_direction()),
+            (CallDirection::Up, TravelDirection::Up) | (CallDirection::Down, TravelDirection::Down)
+        )
+    }
+
+    // The carriage stops at `floor_id` first; the destination it was travelling to, becomes
+    // a pending one.
+    pub fn divert_to(&mut self, floor_id: u8) {
+        if let Some(earlier_dest) = self.dest_floor.replace(floor_id) {
+            self.pending_calls.add_car_call(earlier_dest);
+        }
+        self.scheduler.on_departure(self.current_floor, floor_id);
     }
 
+    // Which of the pending calls comes next, is decided by the dispatch strategy. The calls at the
+    // current floor, for the direction the carriage leaves in, are answered; a full carriage
+    // answers no hall calls at all.
     pub fn dequeue_next_destination(&mut self) -> Option<u8> {
-        let head = 
-            if !self.next_dests_waiting_list.is_empty() {
-                    Some(self.next_dests_waiting_list.remove(0))
-            }
-            else { None };
-        head    
+        if self.is_full() {
+            self.scheduler.next_stop_bypassing_hall_calls(self.current_floor, &mut self.pending_calls)
+        } else {
+            self.scheduler.next_stop(self.current_floor, &mut self.pending_calls)
+        }
+    }
+
+    // As read by the load-weighing device, whenever passengers board or alight.
+    pub fn weigh(&mut self, load: CarLoad) {
+        self.load = load;
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.rated_load.is_full(&self.load)
     }
 
-    pub fn prepare_for_emergency(&mut self) -> () {
+    pub fn is_overloaded(&self) -> bool {
+        self.rated_load.is_overloaded(&self.load)
+    }
+
+    pub fn take_answered_hall_calls(&mut self) -> Vec<(u8, CallDirection)> {
+        self.scheduler.take_answered_hall_calls()
+    }
+
+    pub fn prepare_for_emergency(&mut self) {
         self.emergency_op_requested = true;
     }
 
@@ -68,46 +155,193 @@ impl CarriageData {
 
 }
 
+#[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::elevator_installation::dispatch_strategy::{FifoStrategy, LookStrategy};
 
     #[test]
-    fn when_initialized_then_no_dest () -> () {
+    fn when_initialized_then_no_dest () {
 
-        let carriage = CarriageData::new(8);
+        let carriage = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
 
         assert_eq!(carriage.current_floor, 0);
         assert_eq!(carriage.dest_floor, None);
     }
 
     #[test]
-    fn when_multiple_dest_specified_then_next_dest_should_be_in_order_of_specification() {
+    fn when_no_dest_then_arrival_leaves_carriage_where_it_is() {
+        let mut carriage = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        carriage.set_next_destination(3);
+        carriage.on_arrival();
 
-        use rand::seq::SliceRandom;
+        assert_eq!(carriage.on_arrival(), None);
+        assert_eq!(carriage.where_is(), 3);
+    }
+
+    #[test]
+    fn when_multiple_dest_specified_then_next_dest_should_be_in_order_of_travel() {
+
+        use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 
         // Create a vector of u8
         let mut data: Vec<u8> = (0..8).collect();
 
-        // Shuffle the contents randomly
-        let mut rng = rand::thread_rng();
+        // Shuffle the contents, the same way every run
+        let mut rng = StdRng::seed_from_u64(8);
         data.shuffle(&mut rng);
 
-        let carriage = &mut CarriageData::new(8);
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
 
         for i in data.iter() {
             carriage.enqueue_next_destination(*i);
         }
 
-        for i in (0..8).into_iter() {
-            assert_eq!(data[i as usize], carriage.dequeue_next_destination().unwrap_or(8));
+        // Carriage is at the ground floor, so it must sweep upwards, one floor at a time,
+        // irrespective of the order in which the floors were asked for.
+        for i in 1..8 {
+            let next = carriage.dequeue_next_destination().unwrap_or(8);
+            assert_eq!(next, i);
+            carriage.set_next_destination(next);
+            carriage.on_arrival();
         }
 
+        assert_eq!(carriage.dequeue_next_destination(), None);
+
+    }
+
+    #[test]
+    fn when_carriage_is_going_up_then_floors_below_are_served_on_the_way_back() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.set_next_destination(4);
+        carriage.on_arrival();
+
+        for i in [1, 6, 2, 5] {
+            carriage.enqueue_next_destination(i);
+        }
+
+        let mut stops = Vec::new();
+        while let Some(next) = carriage.dequeue_next_destination() {
+            stops.push(next);
+            carriage.set_next_destination(next);
+            carriage.on_arrival();
+        }
+
+        assert_eq!(stops, vec![5, 6, 2, 1]);
+    }
+
+    #[test]
+    fn when_fifo_strategy_is_chosen_then_next_dest_should_be_in_order_of_specification() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(FifoStrategy));
+
+        for i in [7, 1, 6] {
+            carriage.enqueue_next_destination(i);
+        }
+
+        for i in [7, 1, 6] {
+            let next = carriage.dequeue_next_destination().unwrap_or(8);
+            assert_eq!(next, i);
+            carriage.set_next_destination(next);
+            carriage.on_arrival();
+        }
+    }
+
+    #[test]
+    fn when_carriage_is_diverted_then_earlier_dest_is_served_next() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.set_next_destination(6);
+        carriage.enqueue_next_destination(3);
+
+        assert!(carriage.is_on_the_way(3));
+        assert!(!carriage.is_on_the_way(7));
+        assert!(!carriage.is_on_the_way(0));
+
+        carriage.divert_to(3);
+
+        assert_eq!(carriage.on_arrival(), Some(3));
+        assert_eq!(carriage.dequeue_next_destination(), Some(6));
+        carriage.set_next_destination(6);
+        carriage.on_arrival();
+        assert_eq!(carriage.dequeue_next_destination(), None);
+    }
+
+    #[test]
+    fn when_hall_call_is_against_the_direction_of_travel_then_it_is_not_on_the_way() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.set_next_destination(6);
+
+        assert!(carriage.is_on_the_way_for(3, CallDirection::Up));
+        assert!(!carriage.is_on_the_way_for(3, CallDirection::Down));
+    }
+
+    #[test]
+    fn when_hall_and_car_calls_are_pending_then_hall_calls_are_answered_in_their_direction() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.set_next_destination(2);
+        carriage.on_arrival();
+
+        carriage.enqueue_hall_call(4, CallDirection::Down);
+        carriage.enqueue_next_destination(6);
+        carriage.enqueue_hall_call(5, CallDirection::Up);
+
+        let mut stops = Vec::new();
+        while let Some(next) = carriage.dequeue_next_destination() {
+            stops.push(next);
+            carriage.set_next_destination(next);
+            carriage.on_arrival();
+        }
+
+        assert_eq!(stops, vec![5, 6, 4]);
+        assert_eq!(
+            carriage.take_answered_hall_calls(),
+            vec![(5, CallDirection::Up), (4, CallDirection::Down)]
+        );
+    }
+
+    #[test]
+    fn when_carriage_is_full_then_it_stops_only_for_its_passengers() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.enqueue_hall_call(3, CallDirection::Up);
+        carriage.enqueue_next_destination(5);
+        carriage.weigh(CarLoad { persons: 11, kg: 850 });
+
+        assert!(carriage.is_full() && !carriage.is_overloaded());
+        assert_eq!(carriage.dequeue_next_destination(), Some(5));
+        carriage.set_next_destination(5);
+        carriage.on_arrival();
+
+        // Passengers have got off; the hall call left behind is answered now.
+        carriage.weigh(CarLoad { persons: 2, kg: 140 });
+        assert_eq!(carriage.dequeue_next_destination(), Some(3));
+    }
+
+    #[test]
+    fn when_enqueued_floor_is_beyond_either_ends_then_it_is_ignored() {
+
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+
+        carriage.enqueue_next_destination(8);
+
+        assert!(carriage.pending_calls.is_empty());
     }
 
     #[test]
     fn when_dest_is_beyond_either_ends_then_dest_floor_should_remain_the_same() {
 
-        let carriage = &mut CarriageData::new(8);
+        let carriage = &mut CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
 
         carriage.set_next_destination(5);
 
@@ -120,4 +354,54 @@ mod tests {
         assert_eq!(carriage.dest_floor.unwrap(),   5);
     }
 
-}
\ No newline at end of file
+
+    #[derive(Clone, Debug)]
+    enum Asked {
+        SetDestination(u8),
+        Enqueue(u8),
+        Arrive
+    }
+
+    fn asked() -> impl Strategy<Value = Asked> {
+        prop_oneof![
+            any::<u8>().prop_map(Asked::SetDestination),
+            any::<u8>().prop_map(Asked::Enqueue),
+            Just(Asked::Arrive)
+        ]
+    }
+
+    proptest! {
+        // Whatever it is asked for, the carriage is only ever headed for a floor of the building.
+        #[test]
+        fn the_carriage_never_takes_a_destination_beyond_the_building(
+            floor_count in 2..=20u16,
+            sequence in prop::collection::vec(asked(), 1..40)
+        ) {
+            let carriage = &mut CarriageData::new(&Building::with_floor_count(floor_count).unwrap(), Box::new(LookStrategy));
+
+            for asked in sequence {
+                let dest_before = carriage.dest_floor;
+                match asked {
+                    Asked::SetDestination(floor) => {
+                        carriage.set_next_destination(floor);
+                        if floor as u16 >= floor_count {
+                            prop_assert_eq!(carriage.dest_floor, dest_before);
+                        }
+                    },
+                    Asked::Enqueue(floor) => carriage.enqueue_next_destination(floor),
+                    Asked::Arrive => {
+                        if let Some(next) = carriage.dequeue_next_destination() {
+                            prop_assert!((next as u16) < floor_count, "Next destination ({})", next);
+                            carriage.set_next_destination(next);
+                        }
+                        if carriage.dest_floor.is_some() {
+                            carriage.on_arrival();
+                        }
+                    }
+                }
+                prop_assert!(carriage.dest_floor.is_none_or(|dest| (dest as u16) < floor_count), "Destination {:?}", carriage.dest_floor);
+                prop_assert!((carriage.where_is() as u16) < floor_count);
+            }
+        }
+    }
+}
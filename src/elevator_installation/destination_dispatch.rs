@@ -0,0 +1,122 @@
+use ractor::concurrency::Duration;
+
+use crate::conversation::vocabulary::{CallDirection, CarIndex};
+use crate::elevator_installation::group_controller::{CarStatus, EtaTiming};
+
+/// A passenger who has entered `to_floor` at the kiosk on `from_floor`, and has been told which
+/// car to take. The car is given the destination as a car call, once it has picked the passenger up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DestinationCall {
+    pub from_floor: u8,
+    pub to_floor: u8,
+    pub car: CarIndex
+}
+
+impl DestinationCall {
+    pub fn direction(&self) -> CallDirection {
+        direction_of(self.from_floor, self.to_floor)
+    }
+}
+
+pub fn direction_of(from_floor: u8, to_floor: u8) -> CallDirection {
+    if to_floor > from_floor { CallDirection::Up } else { CallDirection::Down }
+}
+
+// The extra time a new passenger costs the passengers waiting for the same car, at the same floor.
+// A destination already asked for costs nothing. Otherwise, there is one more stop, which delays
+// everybody going farther; and the car may have to travel beyond the farthest destination so far.
+// This is what keeps passengers with similar destinations together.
+fn cost_of_joining(car: CarIndex, waiting: &[DestinationCall], from_floor: u8, to_floor: u8, timing: &EtaTiming) -> Duration {
+    let direction = direction_of(from_floor, to_floor);
+    let destinations: Vec<u8> = waiting
+        .iter()
+        .filter(|call| call.car == car && call.from_floor == from_floor && call.direction() == direction)
+        .map(|call| call.to_floor)
+        .collect();
+
+    if destinations.contains(&to_floor) {
+        return Duration::ZERO;
+    }
+
+    let floors_away = |floor: u8| floor.abs_diff(from_floor);
+    let passengers_delayed = destinations.iter().filter(|f| floors_away(**f) > floors_away(to_floor)).count();
+    let farthest_so_far = destinations.iter().map(|f| floors_away(*f)).max().unwrap_or(0);
+    let floors_beyond = floors_away(to_floor).saturating_sub(farthest_so_far);
+
+    timing.per_stop * (1 + passengers_delayed) as u32 + timing.per_floor * floors_beyond as u32
+}
+
+// The car that gets the passenger to the kiosk's floor the soonest, and disturbs the passengers
+// already waiting for it the least, if any car is in service.
+pub fn choose_car_for_destination(
+    cars: &[CarStatus],
+    waiting: &[DestinationCall],
+    from_floor: u8,
+    to_floor: u8,
+    timing: &EtaTiming
+) -> Option<CarIndex> {
+    let direction = direction_of(from_floor, to_floor);
+    cars.iter()
+        .enumerate()
+        .filter(|(_, car)| car.in_service)
+        .min_by_key(|(car_index, car)|
+            car.estimated_time_of_arrival(from_floor, direction, timing)
+                + cost_of_joining(*car_index, waiting, from_floor, to_floor, timing)
+        )
+        .map(|(car_index, _)| car_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing() -> EtaTiming {
+        EtaTiming { per_floor: Duration::from_millis(500), per_stop: Duration::from_secs(2) }
+    }
+
+    fn idle_car_at(at_floor: u8) -> CarStatus {
+        CarStatus { at_floor, in_service: true, ..CarStatus::default() }
+    }
+
+    fn assign(cars: &[CarStatus], waiting: &mut Vec<DestinationCall>, to_floor: u8) -> CarIndex {
+        let car = choose_car_for_destination(cars, waiting, 0, to_floor, &timing()).unwrap();
+        waiting.push(DestinationCall { from_floor: 0, to_floor, car });
+        car
+    }
+
+    #[test]
+    fn when_destinations_are_similar_then_passengers_share_a_car() {
+        let cars = [idle_car_at(0), idle_car_at(0)];
+        let waiting = &mut Vec::new();
+
+        assert_eq!(assign(&cars, waiting, 8), 0);
+        assert_eq!(assign(&cars, waiting, 7), 0);
+        assert_eq!(assign(&cars, waiting, 8), 0);
+    }
+
+    #[test]
+    fn when_destinations_are_far_apart_then_passengers_are_split_between_cars() {
+        let cars = [idle_car_at(0), idle_car_at(0)];
+        let waiting = &mut Vec::new();
+
+        assert_eq!(assign(&cars, waiting, 8), 0);
+        assert_eq!(assign(&cars, waiting, 2), 1);
+        assert_eq!(assign(&cars, waiting, 3), 1);
+    }
+
+    #[test]
+    fn when_a_car_is_far_away_then_the_nearer_car_is_chosen() {
+        let cars = [idle_car_at(7), idle_car_at(1)];
+
+        assert_eq!(choose_car_for_destination(&cars, &[], 0, 5, &timing()), Some(1));
+        assert_eq!(choose_car_for_destination(&cars, &[], 7, 2, &timing()), Some(0));
+    }
+
+    #[test]
+    fn when_a_car_is_out_of_service_then_it_is_never_chosen() {
+        let cars = [idle_car_at(0), CarStatus { in_service: false, ..idle_car_at(0) }];
+
+        assert_eq!(choose_car_for_destination(&cars, &[], 0, 5, &timing()), Some(0));
+        assert_eq!(choose_car_for_destination(&cars[1..], &[], 0, 5, &timing()), None);
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Debug;
+
+use crate::elevator_installation::pending_calls::PendingCalls;
+use crate::elevator_installation::travel_scheduler::TravelDirection;
+
+/// What a strategy gets to know about the carriage, when it is asked for the next destination.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DispatchContext {
+    pub current_floor: u8,
+    pub direction: TravelDirection,
+    pub top_floor: u8
+}
+
+/// Decides which floor the carriage should travel to next.
+///
+/// `pending_calls` never holds a call for the floor the carriage is standing at. A strategy may
+/// return a floor that has no call (SCAN, for example, sweeps to the end of the shaft).
+pub trait DispatchStrategy: Debug + Send {
+    fn name(&self) -> &'static str;
+
+    fn next_destination(&mut self, context: &DispatchContext, pending_calls: &PendingCalls) -> Option<u8>;
+}
+
+/// The names the strategies go by, e.g. on the command line.
+pub const DISPATCH_STRATEGY_NAMES: [&str; 4] = ["fifo", "nearest", "scan", "look"];
+
+/// The strategy going by the name (one of DISPATCH_STRATEGY_NAMES), if any.
+pub fn dispatch_strategy_named(name: &str) -> Option<Box<dyn DispatchStrategy>> {
+    match name {
+        "fifo"    => Some(Box::new(FifoStrategy)),
+        "nearest" => Some(Box::new(NearestFirstStrategy)),
+        "scan"    => Some(Box::new(ScanStrategy)),
+        "look"    => Some(Box::new(LookStrategy)),
+        _         => None
+    }
+}
+
+/// First come, first served: the earliest pending call is answered first.
+#[derive(Debug, Default)]
+pub struct FifoStrategy;
+
+/// The pending call closest to the carriage is answered first. Ties are broken in favour of
+/// the current direction of travel (upwards, if the carriage is idle).
+#[derive(Debug, Default)]
+pub struct NearestFirstStrategy;
+
+/// The carriage keeps its direction, and travels to the end of the shaft before reversing.
+/// On the way, it stops for car calls and for hall calls in its direction of travel.
+#[derive(Debug, Default)]
+pub struct ScanStrategy;
+
+/// The carriage keeps its direction, and reverses as soon as no call remains ahead of it.
+/// On the way, it stops for car calls and for hall calls in its direction of travel.
+#[derive(Debug, Default)]
+pub struct LookStrategy;
+
+impl DispatchStrategy for FifoStrategy {
+    fn name(&self) -> &'static str {
+        "fifo"
+    }
+
+    fn next_destination(&mut self, _context: &DispatchContext, pending_calls: &PendingCalls) -> Option<u8> {
+        pending_calls.floors().first().copied()
+    }
+}
+
+impl DispatchStrategy for NearestFirstStrategy {
+    fn name(&self) -> &'static str {
+        "nearest"
+    }
+
+    fn next_destination(&mut self, context: &DispatchContext, pending_calls: &PendingCalls) -> Option<u8> {
+        let prefer_down = context.direction == TravelDirection::Down;
+        pending_calls
+            .floors()
+            .iter()
+            .copied()
+            .min_by_key(|f| {
+                let is_below = *f < context.current_floor;
+                (f.abs_diff(context.current_floor), is_below != prefer_down)
+            })
+    }
+}
+
+impl DispatchStrategy for ScanStrategy {
+    fn name(&self) -> &'static str {
+        "scan"
+    }
+
+    fn next_destination(&mut self, context: &DispatchContext, pending_calls: &PendingCalls) -> Option<u8> {
+        let current_floor = context.current_floor;
+        let stop_ahead = |direction| stops_ahead(current_floor, pending_calls, direction).next();
+
+        match context.direction {
+            TravelDirection::Up => stop_ahead(TravelDirection::Up).or_else(|| {
+                if current_floor < context.top_floor { Some(context.top_floor) }
+                else { next_in_direction(current_floor, pending_calls, TravelDirection::Down) }
+            }),
+            TravelDirection::Down => stop_ahead(TravelDirection::Down).or_else(|| {
+                if current_floor > 0 { Some(0) }
+                else { next_in_direction(current_floor, pending_calls, TravelDirection::Up) }
+            }),
+            TravelDirection::Idle => nearest(current_floor, pending_calls)
+        }
+    }
+}
+
+impl DispatchStrategy for LookStrategy {
+    fn name(&self) -> &'static str {
+        "look"
+    }
+
+    fn next_destination(&mut self, context: &DispatchContext, pending_calls: &PendingCalls) -> Option<u8> {
+        let current_floor = context.current_floor;
+
+        match context.direction {
+            TravelDirection::Up =>
+                next_in_direction(current_floor, pending_calls, TravelDirection::Up)
+                .or_else(|| next_in_direction(current_floor, pending_calls, TravelDirection::Down)),
+            TravelDirection::Down =>
+                next_in_direction(current_floor, pending_calls, TravelDirection::Down)
+                .or_else(|| next_in_direction(current_floor, pending_calls, TravelDirection::Up)),
+            TravelDirection::Idle => nearest(current_floor, pending_calls)
+        }
+    }
+}
+
+// Floors ahead of the carriage (nearest first) that it should stop at, travelling in `direction`.
+fn stops_ahead<'a>(current_floor: u8, pending_calls: &'a PendingCalls, direction: TravelDirection)
+    -> impl Iterator<Item = u8> + 'a {
+    let mut ahead: Vec<u8> = pending_calls
+        .floors()
+        .iter()
+        .copied()
+        .filter(|f| match direction {
+            TravelDirection::Up   => *f > current_floor,
+            TravelDirection::Down => *f < current_floor,
+            TravelDirection::Idle => false
+        })
+        .filter(|f| pending_calls.wants_stop(*f, direction))
+        .collect();
+    ahead.sort_by_key(|f| f.abs_diff(current_floor));
+    ahead.into_iter()
+}
+
+// The nearest stop ahead. Failing that, the farthest floor ahead: the calls there are for the
+// opposite direction, so the carriage reverses at that floor.
+fn next_in_direction(current_floor: u8, pending_calls: &PendingCalls, direction: TravelDirection) -> Option<u8> {
+    stops_ahead(current_floor, pending_calls, direction).next().or_else(|| {
+        let ahead = pending_calls.floors().iter().copied();
+        match direction {
+            TravelDirection::Up   => ahead.filter(|f| *f > current_floor).max(),
+            TravelDirection::Down => ahead.filter(|f| *f < current_floor).min(),
+            TravelDirection::Idle => None
+        }
+    })
+}
+
+// An idle carriage goes to the nearest call, preferring to go up when two are equally far.
+fn nearest(current_floor: u8, pending_calls: &PendingCalls) -> Option<u8> {
+    pending_calls
+        .floors()
+        .iter()
+        .copied()
+        .min_by_key(|f| (f.abs_diff(current_floor), *f < current_floor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::vocabulary::CallDirection;
+    use crate::elevator_installation::travel_scheduler::TravelScheduler;
+
+    fn stop_order_for(
+        strategy: Box<dyn DispatchStrategy>,
+        from_floor: u8,
+        heading_to: u8,
+        calls: &mut PendingCalls
+    ) -> Vec<u8> {
+        let scheduler = &mut TravelScheduler::new(strategy, 7);
+        scheduler.on_departure(from_floor, heading_to);
+
+        let mut stops = Vec::new();
+        let mut at_floor = heading_to;
+        while let Some(next) = scheduler.next_stop(at_floor, calls) {
+            stops.push(next);
+            at_floor = next;
+        }
+        stops
+    }
+
+    fn stop_order(strategy: Box<dyn DispatchStrategy>, from_floor: u8, heading_to: u8, car_calls: &[u8]) -> Vec<u8> {
+        let calls = &mut PendingCalls::new();
+        for f in car_calls {
+            calls.add_car_call(*f);
+        }
+        stop_order_for(strategy, from_floor, heading_to, calls)
+    }
+
+    #[test]
+    fn when_fifo_then_floors_are_served_in_order_of_request() {
+        assert_eq!(stop_order(Box::new(FifoStrategy), 0, 3, &[7, 1, 6, 1]), vec![7, 1, 6]);
+    }
+
+    #[test]
+    fn when_nearest_first_then_closest_floor_is_served_first() {
+        assert_eq!(stop_order(Box::new(NearestFirstStrategy), 0, 3, &[7, 2, 5]), vec![2, 5, 7]);
+    }
+
+    #[test]
+    fn when_nearest_first_and_tied_then_direction_of_travel_wins() {
+        assert_eq!(stop_order(Box::new(NearestFirstStrategy), 6, 3, &[1, 5]), vec![1, 5]);
+        assert_eq!(stop_order(Box::new(NearestFirstStrategy), 0, 3, &[1, 5]), vec![5, 1]);
+    }
+
+    #[test]
+    fn when_scan_then_carriage_sweeps_to_the_end_of_the_shaft_before_reversing() {
+        assert_eq!(stop_order(Box::new(ScanStrategy), 0, 3, &[1, 5, 2]), vec![5, 7, 2, 1]);
+    }
+
+    #[test]
+    fn when_look_then_carriage_reverses_at_the_last_call() {
+        assert_eq!(stop_order(Box::new(LookStrategy), 0, 3, &[1, 5, 2]), vec![5, 2, 1]);
+    }
+
+    #[test]
+    fn when_look_then_hall_calls_are_answered_only_in_their_direction() {
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(4, CallDirection::Down);
+        calls.add_hall_call(5, CallDirection::Up);
+        calls.add_car_call(6);
+        calls.add_hall_call(7, CallDirection::Down);
+        calls.add_hall_call(1, CallDirection::Up);
+
+        // Going up from 2: 5 (up) and 6 (car) on the way, turn at 7 (down), then 4 (down) on the
+        // way down, and finally 1, where the carriage turns again to go up.
+        assert_eq!(stop_order_for(Box::new(LookStrategy), 0, 2, calls), vec![5, 6, 7, 4, 1]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn when_scan_then_hall_calls_against_the_direction_wait_for_the_return_sweep() {
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(4, CallDirection::Down);
+        calls.add_car_call(5);
+
+        assert_eq!(stop_order_for(Box::new(ScanStrategy), 0, 2, calls), vec![5, 7, 4]);
+    }
+
+    #[test]
+    fn when_named_then_the_strategy_goes_by_that_name() {
+        for name in DISPATCH_STRATEGY_NAMES {
+            assert_eq!(dispatch_strategy_named(name).map(|strategy| strategy.name()), Some(name));
+        }
+        assert!(dispatch_strategy_named("elevator-algorithm").is_none());
+    }
+}
@@ -0,0 +1,560 @@
+use async_trait::async_trait;
+use ractor::concurrency::{Duration, JoinHandle};
+use ractor::{Actor, ActorProcessingErr, ActorRef, MessagingErr};
+use rust_fsm::*;
+use std::fmt;
+use tracing::info;
+
+use crate::conversation::vocabulary::{DoorState, DoorVocabulary, ElevatorVocabulary};
+use crate::conversation::vocabulary::DoorState::*;
+use crate::elevator_installation::door_machinery::DoorFSMInputs::*;
+use crate::elevator_installation::door_machinery::DoorFSMOutputs::*;
+use crate::elevator_installation::fault_policy::FaultPolicy;
+use crate::simulation_clock::SimulationClock;
+
+// Re-opened by the photo-eye so many times at one stop, the door closes anyway: it nudges.
+pub const NUDGE_AFTER_REOPENINGS: u8 = 3;
+
+// A nudging door closes these many times slower than usual.
+pub const NUDGING_SLOWDOWN: u32 = 2;
+
+/// How long the door takes to open, is held open for the passengers to alight and board, and
+/// takes to close.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DoorTimes {
+    pub opening: Duration,
+    pub dwell: Duration,
+    pub closing: Duration
+}
+
+#[derive(Debug)]
+pub enum DoorFSMInputs {
+    CarriageStopped,
+    FullyOpen,
+    DwellIsOver,
+    Nudge,  // the dwell is over, but the door has been re-opened too often
+    Obstruct,
+    OpenButton,
+    CloseButton,
+    FullyClosed
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DoorFSMOutputs {
+    StartOpening,
+    Reopen,
+    StartDwell,
+    StartClosing,
+    StartNudging,
+    Lock
+}
+
+/// What can go wrong in a door: the elevator it tells of every change, may not be there. Each error
+/// is handled by its own policy (see FaultPolicy).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DoorError {
+    NotPoweredOn,          // no elevator has hooked it yet
+    ElevatorGone           // nobody is left to tell that the door is closed
+}
+
+impl DoorError {
+    pub fn policy(&self) -> FaultPolicy {
+        match self {
+            DoorError::NotPoweredOn => FaultPolicy::LogAndContinue,
+            // Whoever comes for the car next, must not find the door going on by itself.
+            DoorError::ElevatorGone => FaultPolicy::EnterFaultState
+        }
+    }
+}
+
+impl fmt::Display for DoorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DoorError::NotPoweredOn => write!(f, "no elevator has powered the door on"),
+            DoorError::ElevatorGone => write!(f, "the elevator is not there anymore")
+        }
+    }
+}
+
+impl std::error::Error for DoorError {}
+
+pub struct DoorStateMachine {}
+impl StateMachineImpl for DoorStateMachine {
+    type Input = DoorFSMInputs;
+    type State = DoorState;
+    type Output = DoorFSMOutputs;
+    const INITIAL_STATE: Self::State = Closed;
+
+    fn transition(state: &Self::State, input: &Self::Input) -> Option<Self::State> {
+        match (state, input) {
+            (Closed, CarriageStopped) => Some(Opening),
+            (Opening, FullyOpen) => Some(Open),
+            (Open, CarriageStopped) => Some(Open),
+            (Open, Obstruct) => Some(Open),
+            (Open, OpenButton) => Some(Open),
+            (Open, DwellIsOver) => Some(Closing),
+            (Open, CloseButton) => Some(Closing),
+            (Open, Nudge) => Some(Nudging),
+            (Closing, CarriageStopped) => Some(Opening),
+            (Closing, Obstruct) => Some(Opening),
+            (Closing, OpenButton) => Some(Opening),
+            (Closing, FullyClosed) => Some(Closed),
+            (Nudging, CarriageStopped) => Some(Opening),
+            (Nudging, OpenButton) => Some(Opening),
+            (Nudging, FullyClosed) => Some(Closed),
+
+            _ => None,
+        }
+    }
+
+    fn output(state: &Self::State, input: &Self::Input) -> Option<Self::Output> {
+        match (state, input) {
+            (Closed, CarriageStopped) => Some(StartOpening),
+            (Opening, FullyOpen) => Some(StartDwell),
+            (Open, CarriageStopped) => Some(StartDwell),
+            (Open, Obstruct) => Some(StartDwell),
+            (Open, OpenButton) => Some(StartDwell),
+            (Open, DwellIsOver) => Some(StartClosing),
+            (Open, CloseButton) => Some(StartClosing),
+            (Open, Nudge) => Some(StartNudging),
+            (Closing, CarriageStopped) => Some(Reopen),
+            (Closing, Obstruct) => Some(Reopen),
+            (Closing, OpenButton) => Some(Reopen),
+            (Closing, FullyClosed) => Some(Lock),
+            (Nudging, CarriageStopped) => Some(Reopen),
+            (Nudging, OpenButton) => Some(Reopen),
+            (Nudging, FullyClosed) => Some(Lock),
+
+            _ => None,
+        }
+    }
+}
+
+pub struct DoorData {
+    at_floor: u8,
+    door_state_machine: StateMachine<DoorStateMachine>,
+    times: DoorTimes,
+    clock: SimulationClock,
+    reopenings: u8,                 // by the photo-eye, since the door last opened at this floor
+    held: bool,                     // by the elevator, while the car is overloaded
+    motion_started_at: Duration,    // simulated time; with how long the motion takes, where the door is
+    motion_takes: Duration,
+    timer: Option<JoinHandle<Result<(), MessagingErr<DoorVocabulary>>>>,
+    timer_generation: u64,          // a timer that has gone off after being replaced, is not heeded
+    elevator_controller_actor: Option<ActorRef<ElevatorVocabulary>>,
+    fault: Option<DoorError>        // once out of order, the door does nothing more until powered off
+}
+
+impl DoorData {
+    pub fn new(times: DoorTimes, clock: SimulationClock) -> Self {
+        DoorData {
+            at_floor: 0,
+            door_state_machine: StateMachine::new(),
+            times,
+            clock,
+            reopenings: 0,
+            held: false,
+            motion_started_at: Duration::ZERO,
+            motion_takes: Duration::ZERO,
+            timer: None,
+            timer_generation: 0,
+            elevator_controller_actor: None,
+            fault: None
+        }
+    }
+
+    pub fn hook(&mut self, controller: ActorRef<ElevatorVocabulary>) -> &mut Self {
+        self.elevator_controller_actor = Some(controller);
+        self
+    }
+
+    pub fn current_state(&self) -> DoorState {
+        *self.door_state_machine.state()
+    }
+
+    pub fn run_machine(&mut self, input: &DoorFSMInputs) -> (DoorState, Option<DoorFSMOutputs>) {
+        if let Ok(maybe_output) = self.door_state_machine.consume(input) {
+            (self.current_state(), maybe_output)
+        } else {
+            (self.current_state(), None)
+        }
+    }
+
+    // What the door does next, once the timer set last has gone off.
+    fn on_time_up(&self) -> Option<DoorFSMInputs> {
+        match self.current_state() {
+            Opening => Some(FullyOpen),
+            Open if self.reopenings >= NUDGE_AFTER_REOPENINGS => Some(Nudge),
+            Open => Some(DwellIsOver),
+            Closing | Nudging => Some(FullyClosed),
+            Closed => None
+        }
+    }
+
+    // A door re-opened halfway closed, has only half the way to go back.
+    fn time_to_reopen(&self) -> Duration {
+        let moved_for = self.clock.elapsed().saturating_sub(self.motion_started_at).min(self.motion_takes);
+        if self.motion_takes.is_zero() {
+            self.times.opening
+        } else {
+            self.times.opening.mul_f64(moved_for.as_secs_f64() / self.motion_takes.as_secs_f64())
+        }
+    }
+
+    fn start_motion(&mut self, myself: &ActorRef<DoorVocabulary>, takes: Duration) {
+        self.motion_started_at = self.clock.elapsed();
+        self.motion_takes = takes;
+        self.set_timer(myself, takes);
+    }
+
+    fn set_timer(&mut self, myself: &ActorRef<DoorVocabulary>, period: Duration) {
+        self.cancel_timer();
+        let generation = self.timer_generation;
+        self.timer = Some(self.clock.send_after(period, myself.get_cell(), move || DoorVocabulary::TimeIsUp(generation)));
+    }
+
+    fn cancel_timer(&mut self) {
+        self.timer_generation += 1;
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+    }
+
+    fn tell_elevator(&self, message: ElevatorVocabulary) -> Result<(), DoorError> {
+        self.elevator_controller_actor
+            .as_ref()
+            .ok_or(DoorError::NotPoweredOn)?
+            .send_message(message)
+            .map_err(|_| DoorError::ElevatorGone)
+    }
+
+    pub fn fault(&self) -> Option<&DoorError> {
+        self.fault.as_ref()
+    }
+
+    // The error is handled as its policy says. A door out of order, stops where it is: no timer of
+    // it goes off anymore.
+    pub fn on_error(&mut self, error: DoorError) -> Result<(), DoorError> {
+        match error.policy() {
+            FaultPolicy::LogAndContinue => {
+                info!("Door: {}, carrying on", error);
+                Ok(())
+            },
+            FaultPolicy::EnterFaultState => {
+                info!("Door: {}, out of order until powered off", error);
+                self.cancel_timer();
+                self.fault = Some(error);
+                Ok(())
+            },
+            FaultPolicy::Escalate => {
+                info!("Door: {}, giving up", error);
+                Err(error)
+            }
+        }
+    }
+
+    // Runs the door's machine, and carries out what it says. The elevator is told of every
+    // change of state; of the door closed, only once it is locked.
+    fn operate(&mut self, myself: &ActorRef<DoorVocabulary>, input: DoorFSMInputs) -> Result<(), DoorError> {
+        let was = self.current_state();
+        let time_to_reopen = self.time_to_reopen();
+        let _mc_run_outcome = self.run_machine(&input);
+        info!("Door: event ({:?}) at floor ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+            input,
+            self.at_floor,
+            _mc_run_outcome.0,
+            _mc_run_outcome.1
+        );
+
+        match _mc_run_outcome.1 {
+            Some(StartOpening) => self.start_motion(myself, self.times.opening),
+            Some(Reopen) => self.start_motion(myself, time_to_reopen),
+            Some(StartDwell) if self.held => self.cancel_timer(),
+            Some(StartDwell) => self.set_timer(myself, self.times.dwell),
+            Some(StartClosing) => self.start_motion(myself, self.times.closing),
+            Some(StartNudging) => self.start_motion(myself, self.times.closing * NUDGING_SLOWDOWN),
+            Some(Lock) => {
+                self.cancel_timer();
+                self.reopenings = 0;
+            },
+            None => {}
+        }
+
+        match self.current_state() {
+            Closed if was != Closed => self.tell_elevator(ElevatorVocabulary::DoorClosed(self.at_floor)),
+            now if now != was => self.tell_elevator(ElevatorVocabulary::DoorStateChanged(self.at_floor, now)),
+            _ => Ok(())
+        }
+    }
+}
+
+/// The door of a car, and its operator: it opens when the carriage has stopped, is held open for
+/// the dwell, and closes. The photo-eye re-opens a closing door (until the door has had enough,
+/// and nudges); the buttons in the car hold it open longer, or close it at once. The elevator is
+/// told that the door is closed, only once it is closed and locked: not before then, may the
+/// carriage move.
+pub struct DoorActor;
+
+#[async_trait]
+impl Actor for DoorActor {
+    type Msg       = DoorVocabulary;
+    type State     = DoorData;
+    type Arguments = (DoorTimes, SimulationClock);
+
+    async fn pre_start(&self, _myself: ActorRef<Self::Msg>, args: Self::Arguments) ->
+        Result<Self::State, ActorProcessingErr> {
+            let (times, clock) = args;
+            Ok(DoorData::new(times, clock))
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        door: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+
+        // Out of order, the door only waits to be powered off.
+        if let Some(fault) = door.fault() {
+            match message {
+                DoorVocabulary::PowerOff => myself.stop(Some(String::from("Out of order"))),
+                _ => info!("Door: out of order ({}), ignoring what it is told", fault)
+            }
+            return Ok(());
+        }
+
+        if let Err(error) = on_message(&myself, message, door) {
+            door.on_error(error)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn on_message(
+    myself: &ActorRef<DoorVocabulary>,
+    message: DoorVocabulary,
+    door: &mut DoorData
+) -> Result<(), DoorError> {
+
+    match message {
+        DoorVocabulary::PowerOn(controller) => {
+            door.hook(controller);
+            info!("Door: is being powered on!");
+        },
+        DoorVocabulary::PowerOff => {
+            info!("Door: is being powered off");
+            door.cancel_timer();
+            myself.stop(Some(String::from("powered down")));
+        },
+        DoorVocabulary::Open(floor) => {
+            if door.current_state() == Closed {
+                door.at_floor = floor;
+            }
+            door.operate(myself, CarriageStopped)?;
+        },
+        DoorVocabulary::Obstructed => {
+            if door.current_state() == Closing {
+                door.reopenings += 1;
+            }
+            door.operate(myself, Obstruct)?;
+        },
+        DoorVocabulary::OpenButton => door.operate(myself, OpenButton)?,
+        // An overloaded car is not to be closed, by anybody.
+        DoorVocabulary::CloseButton if door.held => info!("Door: is held open, the close button is of no use"),
+        DoorVocabulary::CloseButton => door.operate(myself, CloseButton)?,
+        DoorVocabulary::Hold => {
+            door.held = true;
+            match door.current_state() {
+                Closing | Nudging => door.operate(myself, CarriageStopped)?,
+                Open => door.cancel_timer(),
+                _ => {}
+            }
+        },
+        DoorVocabulary::Release => {
+            door.held = false;
+            if door.current_state() == Open {
+                door.set_timer(myself, door.times.dwell);
+            }
+        },
+        DoorVocabulary::TimeIsUp(generation) if generation == door.timer_generation => {
+            door.timer = None;
+            if let Some(input) = door.on_time_up() {
+                door.operate(myself, input)?;
+            }
+        },
+        DoorVocabulary::TimeIsUp(_) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ractor::{Actor, ActorProcessingErr, ActorRef, ActorStatus};
+    use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+    use super::*;
+
+    // Stands in for the elevator: whatever the door tells it, is passed on to the test.
+    struct ElevatorProbe;
+
+    #[async_trait]
+    impl Actor for ElevatorProbe {
+        type Msg = ElevatorVocabulary;
+        type State = UnboundedSender<ElevatorVocabulary>;
+        type Arguments = UnboundedSender<ElevatorVocabulary>;
+
+        async fn pre_start(&self, _myself: ActorRef<Self::Msg>, tx: Self::Arguments) -> Result<Self::State, ActorProcessingErr> {
+            Ok(tx)
+        }
+
+        async fn handle(&self, _myself: ActorRef<Self::Msg>, message: Self::Msg, tx: &mut Self::State) -> Result<(), ActorProcessingErr> {
+            let _ = tx.send(message);
+            Ok(())
+        }
+    }
+
+    const TIMES: DoorTimes = DoorTimes {
+        opening: Duration::from_secs(1),
+        dwell: Duration::from_secs(3),
+        closing: Duration::from_secs(2)
+    };
+
+    async fn door_at_work(name: &str) -> (ActorRef<DoorVocabulary>, UnboundedReceiver<ElevatorVocabulary>, SimulationClock) {
+        let clock = SimulationClock::default();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (probe, _) = Actor::spawn(Some(format!("{}-probe", name)), ElevatorProbe, tx).await.unwrap();
+        let (door, _) = Actor::spawn(Some(String::from(name)), DoorActor, (TIMES, clock.clone())).await.unwrap();
+        door.send_message(DoorVocabulary::PowerOn(probe)).unwrap();
+        (door, rx, clock)
+    }
+
+    // What the door tells the elevator, and when (simulated time), until it is closed.
+    async fn until_closed(rx: &mut UnboundedReceiver<ElevatorVocabulary>, clock: &SimulationClock) -> Vec<(u64, ElevatorVocabulary)> {
+        let mut told = Vec::new();
+        while let Some(message) = rx.recv().await {
+            let closed = matches!(message, ElevatorVocabulary::DoorClosed(_));
+            told.push((clock.elapsed().as_millis() as u64, message));
+            if closed {
+                break;
+            }
+        }
+        told
+    }
+
+    #[test]
+    fn when_closing_door_is_obstructed_then_it_opens_again_unless_nudging() {
+        let mut door: StateMachine<DoorStateMachine> = StateMachine::new();
+        assert_eq!(door.consume(&CarriageStopped).unwrap(), Some(StartOpening));
+        let _ = door.consume(&FullyOpen);
+        assert_eq!(door.consume(&DwellIsOver).unwrap(), Some(StartClosing));
+        assert_eq!(door.consume(&Obstruct).unwrap(), Some(Reopen));
+        assert_eq!(door.state(), &Opening);
+
+        let _ = door.consume(&FullyOpen);
+        assert_eq!(door.consume(&Nudge).unwrap(), Some(StartNudging));
+        assert!(door.consume(&Obstruct).is_err());
+        assert!(door.consume(&DwellIsOver).is_err());
+        assert_eq!(door.consume(&FullyClosed).unwrap(), Some(Lock));
+        assert_eq!(door.state(), &Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_carriage_stops_then_door_opens_dwells_and_closes_in_its_own_time() {
+        let (door, mut rx, clock) = door_at_work("Test_door-1").await;
+
+        door.send_message(DoorVocabulary::Open(2)).unwrap();
+
+        assert_eq!(until_closed(&mut rx, &clock).await, vec![
+            (0, ElevatorVocabulary::DoorStateChanged(2, Opening)),
+            (1000, ElevatorVocabulary::DoorStateChanged(2, Open)),
+            (4000, ElevatorVocabulary::DoorStateChanged(2, Closing)),
+            (6000, ElevatorVocabulary::DoorClosed(2))
+        ]);
+        door.send_message(DoorVocabulary::PowerOff).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_buttons_are_pressed_then_the_dwell_is_longer_or_shorter() {
+        let (door, mut rx, clock) = door_at_work("Test_door-2").await;
+
+        door.send_message(DoorVocabulary::Open(0)).unwrap();
+        clock.sleep(Duration::from_secs(3)).await;
+        door.send_message(DoorVocabulary::OpenButton).unwrap();
+
+        let told = until_closed(&mut rx, &clock).await;
+        assert_eq!(told[2], (6000, ElevatorVocabulary::DoorStateChanged(0, Closing)));
+        assert_eq!(told[3], (8000, ElevatorVocabulary::DoorClosed(0)));
+
+        door.send_message(DoorVocabulary::Open(1)).unwrap();
+        clock.sleep(Duration::from_millis(1500)).await;
+        door.send_message(DoorVocabulary::CloseButton).unwrap();
+
+        let told = until_closed(&mut rx, &clock).await;
+        assert_eq!(told[2], (9500, ElevatorVocabulary::DoorStateChanged(1, Closing)));
+        assert_eq!(told[3], (11500, ElevatorVocabulary::DoorClosed(1)));
+        door.send_message(DoorVocabulary::PowerOff).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_photo_eye_keeps_reopening_the_door_then_it_nudges_closed() {
+        let (door, mut rx, clock) = door_at_work("Test_door-3").await;
+
+        door.send_message(DoorVocabulary::Open(3)).unwrap();
+        for _ in 0..NUDGE_AFTER_REOPENINGS {
+            while rx.recv().await != Some(ElevatorVocabulary::DoorStateChanged(3, Closing)) {}
+            // Halfway closed: it is half a second back to fully open.
+            clock.sleep(Duration::from_secs(1)).await;
+            door.send_message(DoorVocabulary::Obstructed).unwrap();
+        }
+
+        let told = until_closed(&mut rx, &clock).await;
+        let reopened_at = told[0].0;
+        assert_eq!(told, vec![
+            (reopened_at, ElevatorVocabulary::DoorStateChanged(3, Opening)),
+            (reopened_at + 500, ElevatorVocabulary::DoorStateChanged(3, Open)),
+            (reopened_at + 3500, ElevatorVocabulary::DoorStateChanged(3, Nudging)),
+            (reopened_at + 7500, ElevatorVocabulary::DoorClosed(3))
+        ]);
+        door.send_message(DoorVocabulary::PowerOff).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_car_is_overloaded_then_door_is_held_open_until_released() {
+        let (door, mut rx, clock) = door_at_work("Test_door-4").await;
+
+        door.send_message(DoorVocabulary::Open(1)).unwrap();
+        door.send_message(DoorVocabulary::Hold).unwrap();
+        clock.sleep(Duration::from_secs(60)).await;
+        door.send_message(DoorVocabulary::CloseButton).unwrap();
+        clock.sleep(Duration::from_secs(60)).await;
+        door.send_message(DoorVocabulary::Release).unwrap();
+
+        // Told while the test was away: only the order is known.
+        let told = until_closed(&mut rx, &clock).await;
+        assert_eq!(told[1].1, ElevatorVocabulary::DoorStateChanged(1, Open));
+        assert_eq!(told[2], (123_000, ElevatorVocabulary::DoorStateChanged(1, Closing)));
+        door.send_message(DoorVocabulary::PowerOff).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_elevator_is_gone_then_the_door_is_out_of_order_until_powered_off() {
+        let clock = SimulationClock::default();
+        let (door, door_handle) = Actor::spawn(Some(String::from("Test_door-5")), DoorActor, (TIMES, clock.clone())).await.unwrap();
+        let (tx, _) = mpsc::unbounded_channel();
+        let (probe, probe_handle) = Actor::spawn(Some(String::from("Test_door-5-probe")), ElevatorProbe, tx).await.unwrap();
+        door.send_message(DoorVocabulary::PowerOn(probe.clone())).unwrap();
+        probe.stop(None);
+        probe_handle.await.unwrap();
+
+        // Nobody hears that the door is opening: it stops there, and heeds nothing but the power.
+        door.send_message(DoorVocabulary::Open(2)).unwrap();
+        door.send_message(DoorVocabulary::CloseButton).unwrap();
+        clock.sleep(Duration::from_secs(60)).await;
+        assert_eq!(door.get_status(), ActorStatus::Running);
+
+        door.send_message(DoorVocabulary::PowerOff).unwrap();
+        door_handle.await.unwrap();
+    }
+}
@@ -1,23 +1,27 @@
 use crate::elevator_installation::carriage_machinery::CarriageData;
-use crate::conversation::vocabulary::ElevatorVocabulary;
-use crate::conversation::vocabulary::PulleyVocabulary;
+use crate::conversation::vocabulary::{CallDirection, ElevatorVocabulary};
+use crate::conversation::vocabulary::{DoorVocabulary, PulleyVocabulary};
 use crate::elevator_installation::elevator_operations::ElevatorFSMInputs::*;
 use crate::elevator_installation::elevator_operations::ElevatorFSMOutputs::*;
 use crate::elevator_installation::elevator_operations::ElevatorFSMStates::*;
+use crate::elevator_installation::fault_policy::FaultPolicy;
 
 use ractor::ActorRef;
 use rust_fsm::*;
+use std::fmt;
 use tokio::sync::mpsc::UnboundedSender;
+use tracing::info;
 
-const MX_FLOORS: u32 = 7;
-
+/// What the carriage is to do next, once it has stopped.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NextDestTodo {
-    EmergencyAtGroundFloorAlready,
-    EmergencyGotoGroundFloorNow,
+    EmergencyAtHomeFloorAlready,
+    EmergencyGotoHomeFloorNow,
     AllFineGotoNextPassenger(u8)
 }
 
+/// The states of an elevator: from powered off, through ready for service, moving and standing
+/// with its door open, to shutting down and unavailable for service.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ElevatorFSMStates {
     Moving,
@@ -26,35 +30,97 @@ pub enum ElevatorFSMStates {
     ShuttingDown,
     NonOperational,
     DoorIsOpen,
-    PoweredOn,
+    Overloaded, // the door is kept open, and the car does not move, until the load is reduced
     PoweredOff
 }
 
+impl ElevatorFSMStates {
+    /// Every state, whether or not it can be reached.
+    pub const ALL: [ElevatorFSMStates; 8] = [
+        PoweredOff, ReadyForService, Moving, DoorIsOpen, Overloaded,
+        UnavailableForService, ShuttingDown, NonOperational
+    ];
+}
+
+/// What moves an elevator from one state to another: what it is told, and what it is told about
+/// its door and its load.
 #[derive(Debug)]
 pub enum ElevatorFSMInputs {
     SwitchOn,
     SwitchOff,
     MoveTo(u8),
+    Divert(u8),
     DoorClosed,
     Stop,
+    Overload,
+    LoadReduced,
 }
 
+/// What an elevator does on a transition: the controller acts on it (see ElevatorController).
 #[derive(Debug, PartialEq)]
 pub enum ElevatorFSMOutputs {
-    SettleAtGroundFloor,
+    SettleAtHomeFloor,
     NextDest(u8),
     Enqueue(u8),
+    ChangeDest(u8),
     Reached,
     CheckNextDest,
     PrepareForEmergencyStop,
     ExitPassengers,
+    KeepDoorOpen,
+    ResumeClosingDoor,
+}
+
+/// What can go wrong in an elevator: whoever it tells, or is told by, may have gone, and it may be
+/// told what it cannot make sense of. Each error is handled by its own policy (see FaultPolicy).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElevatorError {
+    OperatorGone,          // nobody hears the reports anymore
+    MailboxClosed,         // the elevator is stopping already
+    FloorOutOfRange(u8),   // called to a floor the building does not have
+    PulleyGone,            // the carriage cannot be moved
+    DoorGone,              // the door cannot be opened, nor closed
+    UnexpectedStop { at_floor: u8, heading_to: Option<u8> } // the pulley has stopped where the car was not headed
+}
+
+impl ElevatorError {
+    pub fn policy(&self) -> FaultPolicy {
+        match self {
+            ElevatorError::OperatorGone | ElevatorError::MailboxClosed | ElevatorError::FloorOutOfRange(_) => FaultPolicy::LogAndContinue,
+            ElevatorError::PulleyGone | ElevatorError::DoorGone => FaultPolicy::EnterFaultState,
+            // Where the carriage is, cannot be trusted anymore.
+            ElevatorError::UnexpectedStop { .. } => FaultPolicy::Escalate
+        }
+    }
 }
 
+impl fmt::Display for ElevatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElevatorError::OperatorGone => write!(f, "the operator is not there anymore"),
+            ElevatorError::MailboxClosed => write!(f, "the elevator is not taking messages anymore"),
+            ElevatorError::FloorOutOfRange(floor) => write!(f, "floor ({}) is not in the building", floor),
+            ElevatorError::PulleyGone => write!(f, "the pulley is not there anymore"),
+            ElevatorError::DoorGone => write!(f, "the door is not there anymore"),
+            ElevatorError::UnexpectedStop { at_floor, heading_to } =>
+                write!(f, "the carriage has stopped at floor ({}), while heading to {:?}", at_floor, heading_to)
+        }
+    }
+}
+
+impl std::error::Error for ElevatorError {}
+
+/// Drives one elevator: feeds its state machine, acts on the outputs on the carriage data, tells
+/// the pulley where to go and the door when to open, and reports to the operator.
 pub struct ElevatorController<T: StateMachineImpl> {
     pub carriage_data: CarriageData,
     pub op_informant_channel: Option<UnboundedSender<ElevatorVocabulary>>,
     carriage_state_machine: StateMachine<T>,
     pub pulley_actor: ActorRef<PulleyVocabulary>,
+    door_actor: Option<ActorRef<DoorVocabulary>>, // none, if whoever operates the car opens and closes the door
+    door_is_locked: bool,
+    held_destination: Option<u8>, // asked for while the door was not locked yet
+    fault: Option<ElevatorError> // once out of order, the car does nothing more until powered off
 }
 
 impl ElevatorController<ElevatorStateMachine> {
@@ -62,62 +128,174 @@ impl ElevatorController<ElevatorStateMachine> {
         carriage_data: CarriageData,
         op_informant_channel: Option<UnboundedSender<ElevatorVocabulary>>,
         pulley_actor: ActorRef<PulleyVocabulary>,
+        door_actor: Option<ActorRef<DoorVocabulary>>,
     ) -> Self {
         Self {
-            carriage_data: carriage_data,
+            carriage_data,
             carriage_state_machine: StateMachine::new(),
             op_informant_channel,
-            pulley_actor: pulley_actor,
+            pulley_actor,
+            door_actor,
+            door_is_locked: true,
+            held_destination: None,
+            fault: None
         }
     }
 
     pub fn current_state(&mut self) -> ElevatorFSMStates {
-        self.carriage_state_machine.state().clone()
+        *self.carriage_state_machine.state()
     }
 
     pub fn already_at_floor(&self, floor_index: u8) -> bool {
         self.carriage_data.already_at_floor(floor_index)
     }
 
+    pub fn home_floor(&self) -> u8 {
+        self.carriage_data.home_floor()
+    }
+
     pub fn set_next_destination(&mut self, dest_floor: u8) -> u8 {
         self.carriage_data.set_next_destination(dest_floor)
     }
 
-    pub fn add_to_destinations_queue(&mut self, floor_id: u8) -> () {
+    pub fn add_to_destinations_queue(&mut self, floor_id: u8) {
         self.carriage_data.enqueue_next_destination(floor_id)
     }
 
-    pub fn on_arrival(&mut self) -> u8 {
-        self.carriage_data.on_arrival()
+    pub fn add_to_hall_calls(&mut self, floor_id: u8, direction: CallDirection) {
+        self.carriage_data.enqueue_hall_call(floor_id, direction)
+    }
+
+    // The pulley has stopped the carriage at `at_floor`: that is where it must have been headed.
+    pub fn on_arrival(&mut self, at_floor: u8) -> Result<u8, ElevatorError> {
+        match self.carriage_data.on_arrival() {
+            Some(dest_floor) if dest_floor == at_floor => Ok(at_floor),
+            heading_to => Err(ElevatorError::UnexpectedStop { at_floor, heading_to })
+        }
+    }
+
+    pub fn serves_floor(&self, floor_id: u8) -> bool {
+        self.carriage_data.serves_floor(floor_id)
+    }
+
+    pub fn is_on_the_way(&self, floor_id: u8) -> bool {
+        self.carriage_data.is_on_the_way(floor_id)
+    }
+
+    pub fn is_on_the_way_for(&self, floor_id: u8, direction: CallDirection) -> bool {
+        self.carriage_data.is_on_the_way_for(floor_id, direction)
+    }
+
+    pub fn divert_to(&mut self, floor_id: u8) {
+        self.carriage_data.divert_to(floor_id)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.carriage_data.is_full()
     }
 
     pub fn 
     on_checking_next_dest(&mut self) -> Option<NextDestTodo> {
 
         // If an emergency-stop (PowerOff) has been received already, then the carriage must
-        //      1) Halt if it is already at groundfloor (floor 0)
-        //      2) Move to the groundfloor (floor 0) if it is at any other floor
+        //      1) Halt if it is already at its home floor (the ground floor, usually)
+        //      2) Move to the home floor if it is at any other floor
         //         ignoring all the next destinations requested for, by passengers
         //         at various floors. Safety is paramount!
         // Otherwise, Move to the next destination floor
 
         if self.carriage_data.is_emergency_op_requested() {
-            if self.carriage_data.already_at_floor(0) {
-                Some(NextDestTodo::EmergencyAtGroundFloorAlready) // TODO: Use enum here
+            if self.carriage_data.already_at_floor(self.home_floor()) {
+                Some(NextDestTodo::EmergencyAtHomeFloorAlready) // TODO: Use enum here
             } else {
-                self.set_next_destination(0);
-                Some(NextDestTodo::EmergencyGotoGroundFloorNow)
+                self.set_next_destination(self.home_floor());
+                Some(NextDestTodo::EmergencyGotoHomeFloorNow)
             }
-        } else if self.carriage_data.any_destination_in_queue() {
-            let next_destination = self.carriage_data.dequeue_next_destination();
-            self.set_next_destination(next_destination.unwrap());
-            Some(NextDestTodo::AllFineGotoNextPassenger(next_destination.unwrap()))
+        } else if let Some(next_destination) = self.carriage_data.dequeue_next_destination() {
+            self.set_next_destination(next_destination);
+            Some(NextDestTodo::AllFineGotoNextPassenger(next_destination))
         } else {
             None
         }
     }
 
-    pub fn on_emergency(&mut self) -> () {
+    pub fn operate_door(&self, message: DoorVocabulary) -> Result<(), ElevatorError> {
+        match self.door_actor.as_ref() {
+            Some(door) => door.send_message(message).map_err(|_| ElevatorError::DoorGone),
+            None => Ok(())
+        }
+    }
+
+    pub fn open_door(&mut self, at_floor: u8) -> Result<(), ElevatorError> {
+        self.door_is_locked = false;
+        self.operate_door(DoorVocabulary::Open(at_floor))
+    }
+
+    pub fn on_door_locked(&mut self) -> Result<(), ElevatorError> {
+        self.door_is_locked = true;
+        match self.held_destination.take() {
+            Some(dest_floor) => self.move_carriage_to(dest_floor),
+            None => Ok(())
+        }
+    }
+
+    // The pulley is set going only once the door is closed and locked; the door tells when it is.
+    // Until then, the move is held back: the car is on its way already, as far as it is concerned.
+    pub fn move_carriage_to(&mut self, dest_floor: u8) -> Result<(), ElevatorError> {
+        if self.door_is_locked {
+            self.move_pulley(PulleyVocabulary::MoveToFloor(dest_floor))
+        } else {
+            info!("Door is not locked yet, the carriage leaves floor ({}) for floor ({}) once it is",
+                self.carriage_data.where_is(),
+                dest_floor
+            );
+            self.held_destination = Some(dest_floor);
+            Ok(())
+        }
+    }
+
+    pub fn move_pulley(&self, message: PulleyVocabulary) -> Result<(), ElevatorError> {
+        self.pulley_actor.send_message(message).map_err(|_| ElevatorError::PulleyGone)
+    }
+
+    // Once nobody hears the reports, the car carries on regardless.
+    pub fn tell_operator(&mut self, report: ElevatorVocabulary) {
+        if let Some(Err(_)) = self.op_informant_channel.as_ref().map(|channel| channel.send(report)) {
+            let _ = self.on_error(ElevatorError::OperatorGone);
+        }
+    }
+
+    pub fn fault(&self) -> Option<&ElevatorError> {
+        self.fault.as_ref()
+    }
+
+    // The error is handled as its policy says; only an error to escalate, is given back.
+    pub fn on_error(&mut self, error: ElevatorError) -> Result<(), ElevatorError> {
+        match error.policy() {
+            FaultPolicy::LogAndContinue => {
+                info!("Elevator: {}, carrying on", error);
+                if error == ElevatorError::OperatorGone {
+                    self.op_informant_channel = None;
+                }
+                Ok(())
+            },
+            FaultPolicy::EnterFaultState => {
+                info!("Elevator: {}, out of order until powered off", error);
+                self.fault = Some(error);
+                // Whatever is left of the car, is switched off; the group hears that it is out of service.
+                let _ = self.move_pulley(PulleyVocabulary::PowerOff);
+                let _ = self.operate_door(DoorVocabulary::PowerOff);
+                self.tell_operator(ElevatorVocabulary::ElevatorOutOfService);
+                Ok(())
+            },
+            FaultPolicy::Escalate => {
+                info!("Elevator: {}, giving up", error);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn on_emergency(&mut self) {
         self.carriage_data.prepare_for_emergency();
     }
 
@@ -133,6 +311,7 @@ impl ElevatorController<ElevatorStateMachine> {
     }
 }
 
+/// The transitions and outputs of an elevator (see ElevatorFSMStates).
 pub struct ElevatorStateMachine {}
 impl StateMachineImpl for ElevatorStateMachine {
     type Input = ElevatorFSMInputs;
@@ -147,12 +326,19 @@ impl StateMachineImpl for ElevatorStateMachine {
             (ReadyForService, MoveTo(_)) => Some(Moving),
             (UnavailableForService, MoveTo(_)) => Some(ShuttingDown),
             (Moving, MoveTo(_)) => Some(Moving),
+            (Moving, Divert(_)) => Some(Moving),
             (Moving, Stop) => Some(DoorIsOpen),
             (Moving, SwitchOff) => Some(Moving),
-            (ShuttingDown, MoveTo(0)) => Some(ShuttingDown),
+            (ShuttingDown, MoveTo(_)) => Some(ShuttingDown),
             (DoorIsOpen, MoveTo(_)) => Some(DoorIsOpen),
             (DoorIsOpen, SwitchOff) => Some(DoorIsOpen),
             (DoorIsOpen, DoorClosed) => Some(ReadyForService),
+            (DoorIsOpen, Overload) => Some(Overloaded),
+            (Overloaded, Overload) => Some(Overloaded),
+            (Overloaded, DoorClosed) => Some(Overloaded),
+            (Overloaded, MoveTo(_)) => Some(Overloaded),
+            (Overloaded, SwitchOff) => Some(Overloaded),
+            (Overloaded, LoadReduced) => Some(DoorIsOpen),
             (ShuttingDown, Stop) => Some(NonOperational),
             (NonOperational, DoorClosed) => Some(PoweredOff),
 
@@ -162,15 +348,21 @@ impl StateMachineImpl for ElevatorStateMachine {
 
     fn output(state: &Self::State, input: &Self::Input) -> Option<Self::Output> {
         match (state, input) {
-            (PoweredOff, SwitchOn) => Some(SettleAtGroundFloor),
-            (ReadyForService, SwitchOff) => Some(SettleAtGroundFloor),
+            (PoweredOff, SwitchOn) => Some(SettleAtHomeFloor),
+            (ReadyForService, SwitchOff) => Some(SettleAtHomeFloor),
             (ReadyForService, MoveTo(floor)) => Some(NextDest(*floor)),
-            (UnavailableForService, MoveTo(_)) => Some(SettleAtGroundFloor),
+            (UnavailableForService, MoveTo(_)) => Some(SettleAtHomeFloor),
             (Moving, MoveTo(floor)) => Some(Enqueue(*floor)),
+            (Moving, Divert(floor)) => Some(ChangeDest(*floor)),
             (Moving, SwitchOff) => Some(PrepareForEmergencyStop),
             (DoorIsOpen, MoveTo(floor)) => Some(Enqueue(*floor)),
             (DoorIsOpen, SwitchOff) => Some(PrepareForEmergencyStop),
             (DoorIsOpen, DoorClosed) => Some(CheckNextDest),
+            (DoorIsOpen, Overload) => Some(KeepDoorOpen),
+            (Overloaded, DoorClosed) => Some(KeepDoorOpen),
+            (Overloaded, MoveTo(floor)) => Some(Enqueue(*floor)),
+            (Overloaded, SwitchOff) => Some(PrepareForEmergencyStop),
+            (Overloaded, LoadReduced) => Some(ResumeClosingDoor),
             (Moving, Stop) => Some(Reached),
             (ShuttingDown, Stop) => Some(ExitPassengers),
 
@@ -179,12 +371,18 @@ impl StateMachineImpl for ElevatorStateMachine {
     }
 }
 
+#[cfg(test)]
 mod tests {
     
     use ractor::{Actor, ActorStatus};
     use tokio::sync::mpsc;
 
-    use crate::elevator_installation::pulley_machinery::PulleyActor;
+    use crate::elevator_installation::pulley_machinery::{PulleyActor, PULLEY_STEP_INTERVAL};
+    use crate::elevator_installation::motion_profile::BRISK_MOTION;
+    use crate::elevator_installation::shaft::FloorLevels;
+    use crate::simulation_clock::SimulationClock;
+    use crate::elevator_installation::dispatch_strategy::LookStrategy;
+    use crate::building::Building;
 
     use super::*;
 
@@ -198,18 +396,18 @@ mod tests {
     #[tokio::test]
     async fn carriage_is_at_ground_floor_when_started() {
         let (tx, mut rx) = mpsc::unbounded_channel();
-        let carriage_data = CarriageData::new(8);
-        let floor_setting = vec![(0.0, 5.0), (5.0, 10.0), (10.0, 15.0), (15.0, 20.0)];
+        let carriage_data = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
         let (pulley_ref, pulley_handle) = Actor::spawn(
             Some(String::from("Test_pulley_actor")),
             PulleyActor,
-            floor_setting,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
         )
         .await
         .expect("Failed to create Pulley actor");
 
         let mut carriage =
-            ElevatorController::new(carriage_data, Some(tx.clone()), pulley_ref.clone());
+            ElevatorController::new(carriage_data, Some(tx.clone()), pulley_ref.clone(), None);
         let maybe_output = carriage.run_machine(&ElevatorFSMInputs::SwitchOn);
 
         assert_eq!(
@@ -220,13 +418,12 @@ mod tests {
 
         assert_eq!(
             maybe_output.1.unwrap(),
-            SettleAtGroundFloor,
-            "FSM Output = SettleAtGroundFloor"
+            SettleAtHomeFloor,
+            "FSM Output = SettleAtHomeFloor"
         );
 
-        assert_eq!(
+        assert!(
             carriage.already_at_floor(0),
-            true,
             "Carriage current floor = 0"
         );
 
@@ -249,19 +446,46 @@ mod tests {
         drop(tx);
     }
 
+    #[tokio::test]
+    async fn when_door_is_not_locked_yet_then_the_carriage_leaves_once_it_is() {
+        let carriage_data = CarriageData::new(&Building::with_floor_count(4).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+        let (pulley_ref, pulley_handle) = Actor::spawn(
+            Some(String::from("Test_pulley_actor-held")),
+            PulleyActor,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
+        )
+        .await
+        .expect("Failed to create Pulley actor");
+
+        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref.clone(), None);
+        carriage.open_door(0).unwrap();
+        carriage.move_carriage_to(2).unwrap();
+        assert_eq!(carriage.held_destination, Some(2));
+
+        carriage.on_door_locked().unwrap();
+        assert_eq!(carriage.held_destination, None);
+
+        carriage.move_carriage_to(3).unwrap();
+        assert_eq!(carriage.held_destination, None);
+
+        pulley_ref.send_message(PulleyVocabulary::PowerOff).unwrap();
+        pulley_handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn when_passenger_dest_is_notified_to_stationery_carriage_then_it_begins_to_move() {
-        let carriage_data = CarriageData::new(8);
-        let floor_setting = vec![(0.0, 5.0), (5.0, 10.0), (10.0, 15.0), (15.0, 20.0)];
+        let carriage_data = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
         let (pulley_ref, _) = Actor::spawn(
             Some(String::from("Test_pulley_actor-1")),
             PulleyActor,
-            floor_setting,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
         )
         .await
         .expect("Failed to create Pulley actor-1");
 
-        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref);
+        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref, None);
         let _ = carriage.run_machine(&ElevatorFSMInputs::SwitchOn);
         let _ = carriage.run_machine(&MoveTo(2));
         assert_eq!(carriage.current_state(), ElevatorFSMStates::Moving);
@@ -269,16 +493,16 @@ mod tests {
 
     #[tokio::test]
     async fn carriage_keeps_moving_when_new_passenger_destination_arrives() {
-        let carriage_data = CarriageData::new(8);
-        let floor_setting = vec![(0.0, 5.0), (5.0, 10.0), (10.0, 15.0), (15.0, 20.0)];
+        let carriage_data = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
         let (pulley_ref, _) = Actor::spawn(
             Some(String::from("Test_pulley_actor-2")),
             PulleyActor,
-            floor_setting,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
         )
         .await
         .expect("Failed to create Pulley actor-2");
-        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref);
+        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref, None);
         let _ = carriage.run_machine(&ElevatorFSMInputs::SwitchOn);
         let _ = carriage.run_machine(&ElevatorFSMInputs::MoveTo(2));
 
@@ -288,4 +512,117 @@ mod tests {
         assert_eq!(maybe_output.1, Some(ElevatorFSMOutputs::Enqueue(5)));
 
     }
+
+    #[tokio::test]
+    async fn when_carriage_stops_where_it_was_not_headed_then_the_error_is_escalated() {
+        let carriage_data = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+        let (pulley_ref, _) = Actor::spawn(
+            Some(String::from("Test_pulley_actor-7")),
+            PulleyActor,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
+        )
+        .await
+        .expect("Failed to create Pulley actor-7");
+        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref.clone(), None);
+        carriage.set_next_destination(2);
+
+        let error = carriage.on_arrival(1).unwrap_err();
+        assert_eq!(error, ElevatorError::UnexpectedStop { at_floor: 1, heading_to: Some(2) });
+        assert_eq!(carriage.on_error(error.clone()), Err(error));
+        assert_eq!(carriage.on_arrival(1), Err(ElevatorError::UnexpectedStop { at_floor: 1, heading_to: None }));
+
+        // Turned down calls, and a pulley gone, are not escalated.
+        assert_eq!(carriage.on_error(ElevatorError::FloorOutOfRange(9)), Ok(()));
+        assert_eq!(carriage.fault(), None);
+        assert_eq!(carriage.on_error(ElevatorError::PulleyGone), Ok(()));
+        assert_eq!(carriage.fault(), Some(&ElevatorError::PulleyGone));
+
+        pulley_ref.stop(None);
+    }
+
+    #[tokio::test]
+    async fn when_passengers_call_while_carriage_is_busy_then_floors_are_served_in_direction_of_travel() {
+        let carriage_data = CarriageData::new(&Building::with_floor_count(8).unwrap(), Box::new(LookStrategy));
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+        let (pulley_ref, _) = Actor::spawn(
+            Some(String::from("Test_pulley_actor-3")),
+            PulleyActor,
+            (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()),
+        )
+        .await
+        .expect("Failed to create Pulley actor-3");
+        let mut carriage = ElevatorController::new(carriage_data, None, pulley_ref, None);
+        let _ = carriage.run_machine(&ElevatorFSMInputs::SwitchOn);
+
+        if let (_, Some(NextDest(f))) = carriage.run_machine(&ElevatorFSMInputs::MoveTo(3)) {
+            carriage.set_next_destination(f);
+        }
+
+        // Requests arrive while moving up to floor 3, and while the door is open at floor 3.
+        for f in [1, 6] {
+            if let (_, Some(Enqueue(f))) = carriage.run_machine(&ElevatorFSMInputs::MoveTo(f)) {
+                carriage.add_to_destinations_queue(f);
+            }
+        }
+        let _ = carriage.run_machine(&ElevatorFSMInputs::Stop);
+        assert_eq!(carriage.on_arrival(3), Ok(3));
+        for f in [0, 5] {
+            if let (_, Some(Enqueue(f))) = carriage.run_machine(&ElevatorFSMInputs::MoveTo(f)) {
+                carriage.add_to_destinations_queue(f);
+            }
+        }
+
+        let mut stops = Vec::new();
+        while let (_, Some(CheckNextDest)) = carriage.run_machine(&ElevatorFSMInputs::DoorClosed) {
+            match carriage.on_checking_next_dest() {
+                Some(NextDestTodo::AllFineGotoNextPassenger(f)) => {
+                    let _ = carriage.run_machine(&ElevatorFSMInputs::MoveTo(f));
+                    let _ = carriage.run_machine(&ElevatorFSMInputs::Stop);
+                    assert_eq!(carriage.on_arrival(f), Ok(f));
+                    stops.push(f);
+                },
+                _ => break
+            }
+        }
+
+        assert_eq!(stops, vec![5, 6, 1, 0]);
+        assert_eq!(carriage.current_state(), ElevatorFSMStates::ReadyForService);
+    }
+
+    #[test]
+    fn when_overloaded_then_door_stays_open_until_load_is_reduced() {
+        let mut elevator_fsm: StateMachine<ElevatorStateMachine> = StateMachine::new();
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::SwitchOn);
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::MoveTo(3));
+
+        // A moving car cannot be overloaded: nobody gets on between floors.
+        assert!(elevator_fsm.consume(&ElevatorFSMInputs::Overload).is_err());
+
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::Stop);
+        assert_eq!(elevator_fsm.consume(&ElevatorFSMInputs::Overload).ok(), Some(Some(KeepDoorOpen)));
+        assert_eq!(elevator_fsm.consume(&ElevatorFSMInputs::DoorClosed).ok(), Some(Some(KeepDoorOpen)));
+        assert_eq!(elevator_fsm.consume(&ElevatorFSMInputs::MoveTo(5)).ok(), Some(Some(Enqueue(5))));
+        assert_eq!(elevator_fsm.state(), &ElevatorFSMStates::Overloaded);
+
+        assert_eq!(elevator_fsm.consume(&ElevatorFSMInputs::LoadReduced).ok(), Some(Some(ResumeClosingDoor)));
+        assert_eq!(elevator_fsm.consume(&ElevatorFSMInputs::DoorClosed).ok(), Some(Some(CheckNextDest)));
+        assert_eq!(elevator_fsm.state(), &ElevatorFSMStates::ReadyForService);
+    }
+
+    #[test]
+    fn carriage_can_change_destination_only_while_moving() {
+        let mut elevator_fsm: StateMachine<ElevatorStateMachine> = StateMachine::new();
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::SwitchOn);
+
+        assert!(elevator_fsm.consume(&ElevatorFSMInputs::Divert(2)).is_err());
+
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::MoveTo(5));
+        let maybe_output = elevator_fsm.consume(&ElevatorFSMInputs::Divert(2));
+        assert_eq!(maybe_output.ok(), Some(Some(ElevatorFSMOutputs::ChangeDest(2))));
+        assert_eq!(elevator_fsm.state(), &ElevatorFSMStates::Moving);
+
+        let _ = elevator_fsm.consume(&ElevatorFSMInputs::Stop);
+        assert!(elevator_fsm.consume(&ElevatorFSMInputs::Divert(3)).is_err());
+    }
 }
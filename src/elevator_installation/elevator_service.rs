@@ -2,27 +2,36 @@ use async_trait::async_trait;
 use ractor::{Actor, ActorProcessingErr, ActorRef};
 use tokio::sync::mpsc::UnboundedSender;
 use tracing::info;
+use crate::building::Building;
 use crate::elevator_installation::carriage_machinery::CarriageData;
-use crate::conversation::vocabulary::{ElevatorVocabulary, PulleyVocabulary};
+use crate::elevator_installation::dispatch_strategy::DispatchStrategy;
+use crate::conversation::vocabulary::{CallDirection, DoorVocabulary, ElevatorVocabulary, PulleyVocabulary};
 use crate::conversation::vocabulary::ElevatorVocabulary::*;
-use crate::elevator_installation::elevator_operations::{ElevatorController, ElevatorFSMInputs, ElevatorFSMOutputs, ElevatorFSMStates, ElevatorStateMachine, NextDestTodo};
+use crate::elevator_installation::elevator_operations::{ElevatorController, ElevatorError, ElevatorFSMInputs, ElevatorFSMOutputs, ElevatorFSMStates, ElevatorStateMachine, NextDestTodo};
 
 
 
-#[cfg(feature = "cluster")]
-impl ractor::Message for ElevatorVocabulary {}
-
+/// An elevator, as an actor: it hears the calls, its pulley and its door, and moves its carriage
+/// through an ElevatorController. Whatever goes wrong, is handled as its ElevatorError says: an
+/// error to escalate, fails the actor, for its supervisor to take the car out of service.
 pub struct PassengerLiftActor;
 #[async_trait]
 impl Actor for PassengerLiftActor {
     type Msg = ElevatorVocabulary;
     type State = ElevatorController<ElevatorStateMachine>;
-    type Arguments = (u16,Option<UnboundedSender<ElevatorVocabulary>>,ActorRef<PulleyVocabulary>);
-
-    async fn pre_start(&self, myself: ActorRef<Self::Msg>, args: Self::Arguments) -> 
+    type Arguments = (
+        Building,
+        Option<UnboundedSender<ElevatorVocabulary>>,
+        ActorRef<PulleyVocabulary>,
+        Box<dyn DispatchStrategy>,
+        Option<ActorRef<DoorVocabulary>> // none, if whoever operates the car opens and closes the door
+    );
+
+    async fn pre_start(&self, _myself: ActorRef<Self::Msg>, args: Self::Arguments) ->
         Result<Self::State, ActorProcessingErr> {
-            let carriage_data = CarriageData::new(args.0);
-            Ok(ElevatorController::new(carriage_data,args.1,args.2))
+            let carriage_data = CarriageData::new(&args.0, args.3);
+            info!("Elevator: dispatching with ({}) strategy", carriage_data.dispatch_strategy_name());
+            Ok(ElevatorController::new(carriage_data,args.1,args.2,args.4))
     }
 
     async fn handle(
@@ -32,307 +41,422 @@ impl Actor for PassengerLiftActor {
         elevator_control: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
 
-        match message {
-            ElevatorVocabulary::PowerOn => {
-                let _mc_run_outcome = elevator_control
-                        .run_machine(&ElevatorFSMInputs::SwitchOn);
-                info!("Event (PowerOn), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))", 
-                        elevator_control.carriage_data.where_is(),
-                        _mc_run_outcome.0, 
-                        _mc_run_outcome.1
-                );    
-                
-                match _mc_run_outcome {
-                    (_, Some(ElevatorFSMOutputs::SettleAtGroundFloor)) => {
-                        elevator_control
-                        .pulley_actor
-                        .send_message(PulleyVocabulary::PowerOn(myself.clone()))
-                        .unwrap();
-                        
-                        elevator_control.set_next_destination(0);    
-                        
-                        elevator_control
-                        .op_informant_channel.as_ref().map(|channel| {
-                            channel.send(MoveToGroundFloor).unwrap();
-                        });
-                    },
-                    (_, None)   => { 
-                        info!("State {:?}, Transition outcome {}", 
-                            _mc_run_outcome.0, 
-                            "is NOP"
-                        )},
-                    (_, _) => {}
-                }
+        // Out of order, the car only waits to be powered off.
+        if let Some(fault) = elevator_control.fault() {
+            match message {
+                ElevatorVocabulary::PowerOff => myself.stop(Some(String::from("Out of order"))),
+                _ => info!("Elevator: out of order ({}), ignoring {:?}", fault, message)
+            }
+            return Ok(());
+        }
 
-            },
-            ElevatorVocabulary::PowerOff => {
-                let _mc_run_outcome = 
-                        elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
-                info!("Event (PowerOff), was on floor:: ({}), Transition(State: ({:?}), Outcome: ({:?}))", 
-                        elevator_control.carriage_data.where_is(),
-                        _mc_run_outcome.0, 
-                        _mc_run_outcome.1
-                );
-                match _mc_run_outcome {
-                    (_, Some(ElevatorFSMOutputs::PrepareForEmergencyStop)) => {
-                        elevator_control.on_emergency();
-                    },
-                    (_, Some(ElevatorFSMOutputs::SettleAtGroundFloor)) => {
-                        elevator_control
-                        .op_informant_channel.as_ref().map(|channel| {
-                            channel.send(MovingTo(0)).unwrap_or_else(|e| {
-                                info!("Receiver stopped, {:?}",e.0);
-                            });
-                        });
+        if let Err(error) = on_message(&myself, message, elevator_control) {
+            elevator_control.on_error(error)?;
+        }
 
-                        elevator_control.set_next_destination(0);
+        Ok(())
+    }
+}
 
-                        myself.send_message(ElevatorVocabulary::MoveToFloor(0)).unwrap();
-                    },
-                    (_, None)   => { 
-                        info!("State {:?}, Transition outcome {}", 
-                            _mc_run_outcome.0, 
-                            "is NOP"
-                        )},
-                    (_, _) => {}
-                }
-            },
-            ElevatorVocabulary::MoveToFloor(dest_floor) => {
-                let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::MoveTo(dest_floor));
-                info!("Event (MoveToFloor({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))", 
-                    dest_floor,
+fn on_message(
+    myself: &ActorRef<ElevatorVocabulary>,
+    message: ElevatorVocabulary,
+    elevator_control: &mut ElevatorController<ElevatorStateMachine>
+) -> Result<(), ElevatorError> {
+
+    match message {
+        ElevatorVocabulary::PowerOn => {
+            let _mc_run_outcome = elevator_control
+                    .run_machine(&ElevatorFSMInputs::SwitchOn);
+            info!("Event (PowerOn), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
                     elevator_control.carriage_data.where_is(),
-                    _mc_run_outcome.0, 
+                    _mc_run_outcome.0,
                     _mc_run_outcome.1
-                )
-                ;
-                match _mc_run_outcome {
-                    (ElevatorFSMStates::ShuttingDown, Some(ElevatorFSMOutputs::SettleAtGroundFloor)) => {
+            );
 
-                        if elevator_control.already_at_floor(0) {
-                            elevator_control.
-                            op_informant_channel.as_ref().map(|channel|{
-                                channel.send(Stop(0)).unwrap();
-                            });
+            match _mc_run_outcome {
+                (_, Some(ElevatorFSMOutputs::SettleAtHomeFloor)) => {
+                    elevator_control.move_pulley(PulleyVocabulary::PowerOn(myself.clone()))?;
+                    elevator_control.operate_door(DoorVocabulary::PowerOn(myself.clone()))?;
 
-                            myself.send_message(Stop(0)).unwrap();
-                        }
-                        else {
-                            elevator_control.set_next_destination(0);
-                            elevator_control
-                            .op_informant_channel.as_ref().map(|channel| {
-                                channel.send(MovingTo(0)).unwrap();
-                            });
-                            elevator_control
-                            .pulley_actor
-                            .send_message(PulleyVocabulary::MoveToFloor(0))
-                            .unwrap();
-                        }
-                        
-                    },
-                    (_, Some(ElevatorFSMOutputs::NextDest(dest_floor))) => {
-                        elevator_control.set_next_destination(dest_floor);
-                        elevator_control
-                        .op_informant_channel.as_ref().map(|channel| {
-                            channel.send(MovingTo(dest_floor)).unwrap();
-                        });
-                        elevator_control
-                        .pulley_actor
-                        .send_message(PulleyVocabulary::MoveToFloor(dest_floor))
-                        .unwrap();
-                    },
-                    (_, Some(ElevatorFSMOutputs::Enqueue(dest_floor))) => {
-                        elevator_control.add_to_destinations_queue(dest_floor);
-                    },
-                    (_, None) => {},
-                    (_, _)    => {} 
-                };
-            },
-
-            ElevatorVocabulary::CurrentCarriagePosn((x,y)) => {
-                info!("Event (CurrentCarriagePosn({},{})), Current State ({:?})",
-                            x,
-                            y,
-                            elevator_control.current_state()
-                        );
-                elevator_control
-                .op_informant_channel.as_ref().map(|channel| {
-                    channel.send(ElevatorVocabulary::CurrentCarriagePosn((x,y)))
-                    .unwrap();
-                });
-            },
-
-            ElevatorVocabulary::Stop(at_floor) => {
-                
-                let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::Stop);
-                
-                info!("Event (Stop({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))", 
-                        at_floor,
-                        elevator_control.carriage_data.where_is(),
-                        _mc_run_outcome.0, 
-                        _mc_run_outcome.1
-                    )
-                    ;
-                match _mc_run_outcome {
-                    (ElevatorFSMStates::NonOperational, Some(ElevatorFSMOutputs::ExitPassengers)) => {
-                        elevator_control.on_arrival();
-                        elevator_control
-                        .op_informant_channel
-                        .as_ref()
-                        .map(|channel| {
-                            channel.send(ElevatorVocabulary::OpenTheDoor(at_floor)).unwrap();
-                        });
-                        elevator_control.pulley_actor.send_message(PulleyVocabulary::PowerOff).unwrap();
+                    // The carriage is installed at the lowest floor; it is parked at its home floor.
+                    let home_floor = elevator_control.home_floor();
+                    elevator_control.tell_operator(MoveToHomeFloor(home_floor));
 
-                    },
-                    (_, Some(ElevatorFSMOutputs::Reached)) => {
-                        elevator_control.on_arrival();
-                        elevator_control
-                        .op_informant_channel
-                        .as_ref()
-                        .map(|channel| {
-                            channel.send(ElevatorVocabulary::OpenTheDoor(at_floor)).unwrap();
-                        });
-                    },
-                    (_, None)   => { 
-                        info!("State {:?}, Transition outcome {}", _mc_run_outcome.0, "is NOP")
-                    },
+                    if elevator_control.already_at_floor(home_floor) {
+                        elevator_control.set_next_destination(home_floor);
+                    }
+                    else {
+                        tell_myself(myself, MoveToFloor(home_floor))?;
+                    }
+                },
+                (_, None)   => {
+                    info!("State {:?}, Transition outcome {}",
+                        _mc_run_outcome.0,
+                        "is NOP"
+                    )},
+                (_, _) => {}
+            }
+
+        },
+        ElevatorVocabulary::PowerOff => {
+            let _mc_run_outcome =
+                    elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
+            info!("Event (PowerOff), was on floor:: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+                    elevator_control.carriage_data.where_is(),
+                    _mc_run_outcome.0,
+                    _mc_run_outcome.1
+            );
+            match _mc_run_outcome {
+                (_, Some(ElevatorFSMOutputs::PrepareForEmergencyStop)) => {
+                    elevator_control.on_emergency();
+                },
+                (_, Some(ElevatorFSMOutputs::SettleAtHomeFloor)) => {
+                    elevator_control.tell_operator(MovingTo(elevator_control.home_floor()));
+
+                    elevator_control.set_next_destination(elevator_control.home_floor());
+
+                    tell_myself(myself, ElevatorVocabulary::MoveToFloor(elevator_control.home_floor()))?;
+                },
+                (_, None)   => {
+                    info!("State {:?}, Transition outcome {}",
+                        _mc_run_outcome.0,
+                        "is NOP"
+                    )},
+                (_, _) => {}
+            }
+        },
+        ElevatorVocabulary::MoveToFloor(dest_floor) | ElevatorVocabulary::CarCall { floor: dest_floor } => {
+            on_passenger_call(myself, elevator_control, dest_floor, None)?;
+        },
+
+        ElevatorVocabulary::HallCall { floor, direction } => {
+            on_passenger_call(myself, elevator_control, floor, Some(direction))?;
+        },
+
+        ElevatorVocabulary::DivertedTo(dest_floor) => {
+            let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::Divert(dest_floor));
+            info!("Event (DivertedTo({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+                dest_floor,
+                elevator_control.carriage_data.where_is(),
+                _mc_run_outcome.0,
+                _mc_run_outcome.1
+            )
+            ;
+            if let (_, Some(ElevatorFSMOutputs::ChangeDest(dest_floor))) = _mc_run_outcome {
+                elevator_control.divert_to(dest_floor);
+                elevator_control.tell_operator(MovingTo(dest_floor));
+            }
+        },
 
-                    (_, _) => {}
-                }
+        ElevatorVocabulary::CurrentCarriagePosn(level) => {
+            info!("Event (CurrentCarriagePosn({})), Current State ({:?})",
+                        level,
+                        elevator_control.current_state()
+                    );
+            elevator_control.tell_operator(ElevatorVocabulary::CurrentCarriagePosn(level));
+        },
+
+        ElevatorVocabulary::Stop(at_floor) => {
 
-            },
+            let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::Stop);
 
-            ElevatorVocabulary::DoorClosed(at_floor) => {
-                let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::DoorClosed);
-                info!("Event (DoorClosed({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))", 
+            info!("Event (Stop({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
                     at_floor,
                     elevator_control.carriage_data.where_is(),
-                    _mc_run_outcome.0, 
+                    _mc_run_outcome.0,
                     _mc_run_outcome.1
                 )
                 ;
-                match _mc_run_outcome {
-                    (ElevatorFSMStates::PoweredOff,_) => {
-                        info!("Elevator is being powered off. Will be out of service!");
-                        elevator_control
-                        .op_informant_channel
-                        .as_ref()
-                        .map(|channel| {
-                            channel.send(ElevatorVocabulary::ElevatorOutOfService).unwrap();
-                        });
-                        myself.stop(Some("Power off".to_owned()));
+            match _mc_run_outcome {
+                (ElevatorFSMStates::NonOperational, Some(ElevatorFSMOutputs::ExitPassengers)) => {
+                    elevator_control.on_arrival(at_floor)?;
+                    elevator_control.tell_operator(ElevatorVocabulary::OpenTheDoor(at_floor));
+                    elevator_control.open_door(at_floor)?;
+                    elevator_control.move_pulley(PulleyVocabulary::PowerOff)?;
+
+                },
+                (_, Some(ElevatorFSMOutputs::Reached)) => {
+                    elevator_control.on_arrival(at_floor)?;
+                    elevator_control.tell_operator(ElevatorVocabulary::OpenTheDoor(at_floor));
+                    elevator_control.open_door(at_floor)?;
+                },
+                (_, None)   => {
+                    info!("State {:?}, Transition outcome {}", _mc_run_outcome.0, "is NOP")
+                },
+
+                (_, _) => {}
+            }
+
+        },
+
+        ElevatorVocabulary::LoadWeighed(load) => {
+            elevator_control.carriage_data.weigh(load);
+            let input = if elevator_control.carriage_data.is_overloaded() { ElevatorFSMInputs::Overload } else { ElevatorFSMInputs::LoadReduced };
+            let _mc_run_outcome = elevator_control.run_machine(&input);
+            info!("Event (LoadWeighed({:?})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+                load,
+                elevator_control.carriage_data.where_is(),
+                _mc_run_outcome.0,
+                _mc_run_outcome.1
+            )
+            ;
+            let at_floor = elevator_control.carriage_data.where_is();
+            let reports = match _mc_run_outcome {
+                (_, Some(ElevatorFSMOutputs::KeepDoorOpen)) => {
+                    elevator_control.operate_door(DoorVocabulary::Hold)?;
+                    vec![LoadWeighed(load), Overloaded(at_floor)]
+                },
+                (_, Some(ElevatorFSMOutputs::ResumeClosingDoor)) => {
+                    elevator_control.operate_door(DoorVocabulary::Release)?;
+                    vec![LoadWeighed(load), OverloadCleared(at_floor)]
+                },
+                (_, _) => vec![LoadWeighed(load)]
+            };
+            for report in reports {
+                elevator_control.tell_operator(report);
+            }
+        },
+
+        ElevatorVocabulary::DoorClosed(at_floor) => {
+            let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::DoorClosed);
+            info!("Event (DoorClosed({})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+                at_floor,
+                elevator_control.carriage_data.where_is(),
+                _mc_run_outcome.0,
+                _mc_run_outcome.1
+            )
+            ;
+            elevator_control.on_door_locked()?;
+            elevator_control.tell_operator(ElevatorVocabulary::DoorClosed(at_floor));
+
+            match _mc_run_outcome {
+                // The door cannot close, while the car is overloaded.
+                (_, Some(ElevatorFSMOutputs::KeepDoorOpen)) => {
+                    elevator_control.open_door(at_floor)?;
+                    elevator_control.operate_door(DoorVocabulary::Hold)?;
+                    elevator_control.tell_operator(ElevatorVocabulary::Overloaded(at_floor));
+                },
+                (ElevatorFSMStates::PoweredOff,_) => {
+                    info!("Elevator is being powered off. Will be out of service!");
+                    elevator_control.operate_door(DoorVocabulary::PowerOff)?;
+                    elevator_control.tell_operator(ElevatorVocabulary::ElevatorOutOfService);
+                    myself.stop(Some("Power off".to_owned()));
+                }
+                (_, Some(ElevatorFSMOutputs::CheckNextDest)) => {
+                    let next_dest_todo = elevator_control.on_checking_next_dest();
+
+                    for (floor, direction) in elevator_control.carriage_data.take_answered_hall_calls() {
+                        elevator_control.tell_operator(ElevatorVocabulary::HallCallAnswered { floor, direction });
                     }
-                    (_, Some(ElevatorFSMOutputs::CheckNextDest)) => {
-                        match elevator_control.on_checking_next_dest() {
-                            Some(NextDestTodo::EmergencyAtGroundFloorAlready) => {
-                                // The operator has instructed for an emergency shutdown. We have to 
-                                // prepare for the shutdown. 
-                                // If it is already at ground floor, 
-                                //     then we have to stop the operation.
-                                info!("Emergency stop request, detected! Stoppin at ground floor.");
-                                let _ = elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
-                                elevator_control
-                                .op_informant_channel
-                                .as_ref()
-                                .map(|channel| {
-                                    channel.send(ElevatorVocabulary::Stop(0)).unwrap();
-                                });
-
-                                elevator_control.set_next_destination(0);
-
-                                myself.send_message(Stop(0)).unwrap();
-                                
-                            },
-                            Some(NextDestTodo::EmergencyGotoGroundFloorNow) => {
-                                // If it is not at the ground floor already, 
-                                //     then we have to send the carriage to the gruond floor (floor = 0)
-                                info!("Emergency stop request, detected! Moving to ground floor.");
-                                let _ = elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
-                                elevator_control.set_next_destination(0);
-                                elevator_control
-                                .op_informant_channel
-                                .as_ref()
-                                .map(|channel| {
-                                    channel.send(ElevatorVocabulary::MoveToGroundFloor).unwrap();
-                                });
-                                myself.send_message(MoveToFloor(0)).unwrap();
-                                
-                            },
-                            Some(NextDestTodo::AllFineGotoNextPassenger(dest_floor)) => {
-                                // While door is being closed, requests from passengers in other floors may 
-                                // arrive (and be enqueued). Moreover, zero or more such requests may already
-                                // in the queue. The earliest such request, if exists, must be handled. 
-                                // So, the FSM is engaged and the state is changed. In addition to this, 
-                                // the pulley is instructed to begin operation (up or down).
-                                
-                                info!("Next passenger request, at floor {}", dest_floor);
-                                let _ = elevator_control
-                                        .run_machine(&ElevatorFSMInputs::MoveTo(dest_floor));
-                                elevator_control.set_next_destination(dest_floor);
-
-                                elevator_control
-                                .pulley_actor
-                                .send_message(PulleyVocabulary::MoveToFloor(dest_floor))
-                                .unwrap();
-                                
-                                elevator_control
-                                .op_informant_channel
-                                .as_ref()
-                                .map(|channel| {
-                                    channel.send(ElevatorVocabulary::MovingTo(dest_floor)).unwrap();
-                                });
-                                
-                            },
-                            None => {
-                                info!("Staying at current floor {}", 
-                                            elevator_control.carriage_data.where_is());
-                                elevator_control
-                                .op_informant_channel
-                                .as_ref()
-                                .map(|channel| {
-                                    channel.send(ElevatorVocabulary::Stay(at_floor)).unwrap();
-                                });
-                            }
+
+                    match next_dest_todo {
+                        Some(NextDestTodo::EmergencyAtHomeFloorAlready) => {
+                            // The operator has instructed for an emergency shutdown. We have to
+                            // prepare for the shutdown.
+                            // If it is already at its home floor,
+                            //     then we have to stop the operation.
+                            // Unavailable for service, the elevator is shut down only by being
+                            // sent to its home floor: being there already, it stops right away.
+                            info!("Emergency stop request, detected! Stopping at home floor.");
+                            let _ = elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
+                            elevator_control.set_next_destination(elevator_control.home_floor());
+
+                            tell_myself(myself, MoveToFloor(elevator_control.home_floor()))?;
+
+                        },
+                        Some(NextDestTodo::EmergencyGotoHomeFloorNow) => {
+                            // If it is not at its home floor already,
+                            //     then we have to send the carriage to the home floor
+                            info!("Emergency stop request, detected! Moving to home floor.");
+                            let _ = elevator_control.run_machine(&ElevatorFSMInputs::SwitchOff);
+                            elevator_control.set_next_destination(elevator_control.home_floor());
+                            elevator_control.tell_operator(ElevatorVocabulary::MoveToHomeFloor(elevator_control.home_floor()));
+                            tell_myself(myself, MoveToFloor(elevator_control.home_floor()))?;
+
+                        },
+                        Some(NextDestTodo::AllFineGotoNextPassenger(dest_floor)) => {
+                            // While door is being closed, requests from passengers in other floors may
+                            // arrive (and be enqueued). Moreover, zero or more such requests may already
+                            // in the queue. The earliest such request, if exists, must be handled.
+                            // So, the FSM is engaged and the state is changed. In addition to this,
+                            // the pulley is instructed to begin operation (up or down).
+
+                            info!("Next passenger request, at floor {}", dest_floor);
+                            let _ = elevator_control
+                                    .run_machine(&ElevatorFSMInputs::MoveTo(dest_floor));
+                            elevator_control.set_next_destination(dest_floor);
+
+                            elevator_control.move_carriage_to(dest_floor)?;
+
+                            elevator_control.tell_operator(ElevatorVocabulary::MovingTo(dest_floor));
+
+                        },
+                        None => {
+                            info!("Staying at current floor {}",
+                                        elevator_control.carriage_data.where_is());
+                            elevator_control.tell_operator(ElevatorVocabulary::Stay(at_floor));
                         }
-                    },
-                    (_, _) => {},   
-                }
+                    }
+                },
+                (_, _) => {},
+            }
 
-            },
+        },
 
-            _ => info!("Unknown message received by Elevator_Service"),
-        };
-       
-        Ok(())
+        // The photo-eye and the buttons in the car, work the door directly.
+        ElevatorVocabulary::DoorObstructed => elevator_control.operate_door(DoorVocabulary::Obstructed)?,
+        ElevatorVocabulary::DoorOpenButton => elevator_control.operate_door(DoorVocabulary::OpenButton)?,
+        ElevatorVocabulary::DoorCloseButton => elevator_control.operate_door(DoorVocabulary::CloseButton)?,
+
+        ElevatorVocabulary::DoorStateChanged(at_floor, door_state) => {
+            elevator_control.tell_operator(ElevatorVocabulary::DoorStateChanged(at_floor, door_state));
+        },
+
+        _ => info!("Unknown message received by Elevator_Service"),
+    };
+
+    Ok(())
+}
+
+// The elevator's own mailbox is closed only while it is stopping.
+fn tell_myself(myself: &ActorRef<ElevatorVocabulary>, message: ElevatorVocabulary) -> Result<(), ElevatorError> {
+    myself.send_message(message).map_err(|_| ElevatorError::MailboxClosed)
+}
+
+// A passenger has asked for `dest_floor`: from inside the carriage, or from the landing (in which
+// case, `hall_call` says which way the passenger wants to go).
+fn on_passenger_call(
+    myself: &ActorRef<ElevatorVocabulary>,
+    elevator_control: &mut ElevatorController<ElevatorStateMachine>,
+    dest_floor: u8,
+    hall_call: Option<CallDirection>
+) -> Result<(), ElevatorError> {
+    // A floor the building does not have, is never reached; the call is turned down at once.
+    if !elevator_control.serves_floor(dest_floor) {
+        return Err(ElevatorError::FloorOutOfRange(dest_floor));
     }
+
+    let _mc_run_outcome = elevator_control.run_machine(&ElevatorFSMInputs::MoveTo(dest_floor));
+    info!("Event (Call({}, {:?})), was on floor: ({}), Transition(State: ({:?}), Outcome: ({:?}))",
+        dest_floor,
+        hall_call,
+        elevator_control.carriage_data.where_is(),
+        _mc_run_outcome.0,
+        _mc_run_outcome.1
+    )
+    ;
+    match _mc_run_outcome {
+        (ElevatorFSMStates::ShuttingDown, Some(ElevatorFSMOutputs::SettleAtHomeFloor)) => {
+
+            if elevator_control.already_at_floor(elevator_control.home_floor()) {
+                elevator_control.tell_operator(Stop(elevator_control.home_floor()));
+
+                tell_myself(myself, Stop(elevator_control.home_floor()))?;
+            }
+            else {
+                elevator_control.set_next_destination(elevator_control.home_floor());
+                elevator_control.tell_operator(MovingTo(elevator_control.home_floor()));
+                elevator_control.move_carriage_to(elevator_control.home_floor())?;
+            }
+
+        },
+        (_, Some(ElevatorFSMOutputs::NextDest(dest_floor))) => {
+            if let Some(direction) = hall_call {
+                elevator_control.add_to_hall_calls(dest_floor, direction);
+            }
+            elevator_control.set_next_destination(dest_floor);
+
+            if elevator_control.already_at_floor(dest_floor) {
+                // The carriage is standing right here; the door only needs to be opened.
+                tell_myself(myself, Stop(dest_floor))?;
+            }
+            else {
+                elevator_control.tell_operator(MovingTo(dest_floor));
+                elevator_control.move_carriage_to(dest_floor)?;
+            }
+        },
+        (ElevatorFSMStates::Moving, Some(ElevatorFSMOutputs::Enqueue(dest_floor))) => {
+            let on_the_way = match hall_call {
+                Some(direction) => {
+                    elevator_control.add_to_hall_calls(dest_floor, direction);
+                    // A full car passes the landing by.
+                    elevator_control.is_on_the_way_for(dest_floor, direction) && !elevator_control.is_full()
+                },
+                None => {
+                    elevator_control.add_to_destinations_queue(dest_floor);
+                    elevator_control.is_on_the_way(dest_floor)
+                }
+            };
+
+            // The carriage may still be able to stop at this floor, on its way. Only the
+            // pulley knows if it can brake in time; if it can, it says so by DivertedTo.
+            if on_the_way {
+                elevator_control.move_pulley(PulleyVocabulary::DivertToFloor(dest_floor))?;
+            }
+        },
+        (_, Some(ElevatorFSMOutputs::Enqueue(dest_floor))) => {
+            match hall_call {
+                Some(direction) => elevator_control.add_to_hall_calls(dest_floor, direction),
+                None => elevator_control.add_to_destinations_queue(dest_floor)
+            }
+        },
+        (_, None) => {},
+        (_, _)    => {}
+    };
+    Ok(())
 }
 
+#[cfg(test)]
 mod test {
 
-    use ractor::{Actor, ActorStatus};
-    use tokio::{sync::mpsc, time::timeout};
+    use ractor::{Actor, ActorRef, ActorStatus};
+    use tokio::{sync::mpsc, task::JoinHandle, time::timeout};
     use tracing::info;
 
     use crate::{
-        conversation::vocabulary::ElevatorVocabulary, 
-        elevator_installation::pulley_machinery::PulleyActor, elevator_installation::elevator_service::PassengerLiftActor
+        building::Building,
+        conversation::vocabulary::{CallDirection, DoorVocabulary, ElevatorVocabulary, Millimetres, PulleyVocabulary},
+        elevator_installation::door_machinery::{DoorActor, DoorTimes}, 
+        elevator_installation::pulley_machinery::{PulleyActor, PULLEY_STEP_INTERVAL}, elevator_installation::motion_profile::BRISK_MOTION, elevator_installation::shaft::FloorLevels, simulation_clock::SimulationClock, elevator_installation::elevator_service::PassengerLiftActor,
+        elevator_installation::dispatch_strategy::LookStrategy
     };
     use tokio::time::Duration;
     use assertx::assert_contains_exactly;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+    use crate::elevator_installation::generators::{calls_over_time, floor_levels};
+    use crate::elevator_installation::carriage_machinery::CarriageData;
+    use crate::elevator_installation::elevator_operations::{ElevatorController, ElevatorFSMStates};
+
+    // The carriage is to have gone steadily toward `to_level`, and stopped right there (the positions
+    // between floors depend on its motion profile). The other messages are left, as they came.
+    fn carriage_went_to(messages: &[ElevatorVocabulary], to_level: Millimetres) -> Vec<ElevatorVocabulary> {
+        let positions: Vec<Millimetres> = messages
+            .iter()
+            .filter_map(|msg| match msg { ElevatorVocabulary::CurrentCarriagePosn(level) => Some(*level), _ => None })
+            .collect();
+
+        assert_eq!(positions.last(), Some(&to_level), "{:?}", positions);
+        assert!(positions.windows(2).all(|pair| to_level.abs_diff(pair[1]) <= to_level.abs_diff(pair[0])), "{:?}", positions);
+
+        messages
+            .iter()
+            .filter(|msg| !matches!(msg, ElevatorVocabulary::CurrentCarriagePosn(_)))
+            .cloned()
+            .collect()
+    }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn when_elevator_is_powered_on_then_the_carriage_must_move_to_floor_zero() -> () {
+    async fn when_elevator_is_powered_on_then_the_carriage_must_move_to_floor_zero() {
 
         let (tx, mut rx) = mpsc::unbounded_channel();
-        let floor_setting = vec! [
-            (0.0,5.0), (0.0,10.0), (0.0,15.0), (0.0,20.0) // x never changes between floors
-        ];
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
     
         let (pulley_ref, pulley_handle) = Actor::spawn(
                                 Some(String::from("Test_pulley_actor-10")), 
                                 PulleyActor, 
-                                floor_setting
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
                             )
                             .await
                             .expect("Failed to create Pulley actor")
@@ -342,7 +466,7 @@ mod test {
             Actor::spawn(
                 Some(String::from("Elevator-Actor-10")),
                 PassengerLiftActor,
-                (8,Some(tx.clone()),pulley_ref.clone())
+                (Building::with_floor_count(8).unwrap(),Some(tx.clone()),pulley_ref.clone(),Box::new(LookStrategy),None)
             ).await
             .expect("Failed to start actor"); 
 
@@ -356,7 +480,7 @@ mod test {
             tokio::time::timeout(Duration::from_secs(1), rx.recv())
             .await
             .expect("timeout before messages are received from elevator") {
-                assert_eq!(msg_received,ElevatorVocabulary::MoveToGroundFloor);
+                assert_eq!(msg_received,ElevatorVocabulary::MoveToHomeFloor(0));
         }
         elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
 
@@ -382,6 +506,12 @@ mod test {
         }
 
         elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::DoorClosed(0));
+        }
 
         if let Some(msg_received) = 
             tokio::time::timeout(Duration::from_secs(1), rx.recv())
@@ -406,15 +536,13 @@ mod test {
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn when_passenger_calls_then_the_pulley_must_displace_the_carriage_as_needed() {
         let (tx,mut rx) = mpsc::unbounded_channel();
-        let floor_setting = vec! [
-            (0.0,0.0), (0.0,5.0), (0.0,10.0), (0.0,15.0) // x never changes between floors
-        ];
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
     
 
         let (pulley_ref, pulley_handle) = Actor::spawn(
                                 Some(String::from("Test_pulley_actor-20")), 
                                 PulleyActor, 
-                                floor_setting.clone()
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
                             )
                             .await
                             .expect("Failed to create Pulley actor")
@@ -424,7 +552,7 @@ mod test {
             Actor::spawn(
                 Some(String::from("Elevator-Actor-20")),
                 PassengerLiftActor,
-                (8,Some(tx.clone()),pulley_ref.clone())
+                (Building::with_floor_count(8).unwrap(),Some(tx.clone()),pulley_ref.clone(),Box::new(LookStrategy),None)
             ).await
             .expect("Failed to start actor");
 
@@ -434,7 +562,7 @@ mod test {
                 tokio::time::timeout(Duration::from_secs(1), rx.recv())
                 .await
                 .expect("timeout before messages are received from elevator") {
-            assert_eq!(msg_received,ElevatorVocabulary::MoveToGroundFloor);
+            assert_eq!(msg_received,ElevatorVocabulary::MoveToHomeFloor(0));
         }
 
         elev_ref.send_message(ElevatorVocabulary::MoveToFloor(3)).unwrap();
@@ -453,17 +581,17 @@ mod test {
                     actual_messages.push(msg_received.unwrap());        
         }
 
-        let mut expected_messages: Vec<ElevatorVocabulary> =  
-                    (1..=15)   // Hard-coded, because the floor_setting are pre-determinted.
-                    .into_iter()
-                    .map(|next_y| ElevatorVocabulary::CurrentCarriagePosn((0.0,next_y as f64)))
-                    .collect()
-                    ;
-        expected_messages.push(ElevatorVocabulary::OpenTheDoor(3));        
+        let mut expected_messages: Vec<ElevatorVocabulary> = vec![ElevatorVocabulary::OpenTheDoor(3)];
         
-        assert_contains_exactly!(actual_messages, expected_messages);
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(3)), expected_messages);
 
         elev_ref.send_message(ElevatorVocabulary::DoorClosed(3)).unwrap(); 
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::DoorClosed(3));
+        }
         if let Some(msg_received) = 
             tokio::time::timeout(Duration::from_secs(1), rx.recv())
             .await
@@ -483,13 +611,6 @@ mod test {
         actual_messages.clear();
 
         expected_messages.push(ElevatorVocabulary::MovingTo(0));
-    
-        expected_messages.extend(
-                (0..15).rev()   // Hard-coded, because the floor_setting are pre-determinted.
-                .into_iter()
-                .map(|next_y| ElevatorVocabulary::CurrentCarriagePosn((0.0,next_y as f64)))
-                .collect::<Vec<ElevatorVocabulary>>()
-        );
         expected_messages.push(ElevatorVocabulary::OpenTheDoor(0));
 
         while let Ok(msg_received) = 
@@ -497,9 +618,15 @@ mod test {
                     actual_messages.push(msg_received.unwrap());
         }
 
-        assert_contains_exactly!(actual_messages, expected_messages);
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(0)), expected_messages);
 
         elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::DoorClosed(0));
+        }
 
         if let Some(msg_received) = 
             tokio::time::timeout(Duration::from_secs(1), rx.recv())
@@ -524,18 +651,134 @@ mod test {
 
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_powered_off_with_the_door_open_at_home_then_the_elevator_shuts_down_there() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+
+        let (pulley_ref, pulley_handle) = Actor::spawn(
+                                Some(String::from("Test_pulley_actor-11")),
+                                PulleyActor,
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+                            )
+                            .await
+                            .expect("Failed to create Pulley actor");
+
+        let (elev_ref, elev_handle) =
+            Actor::spawn(
+                Some(String::from("Elevator-Actor-11")),
+                PassengerLiftActor,
+                (Building::with_floor_count(4).unwrap(), Some(tx.clone()), pulley_ref.clone(), Box::new(LookStrategy), None)
+            ).await
+            .expect("Failed to start actor");
+
+        // A passenger calls the car where it stands; the operator switches it off, while its door is open.
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 0 }).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // The passengers get out once more, and the car is out of service as soon as its door closes.
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+
+        let mut actual_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+                actual_messages.push(msg_received);
+        }
+        assert_eq!(actual_messages, vec![
+            ElevatorVocabulary::MoveToHomeFloor(0),
+            ElevatorVocabulary::OpenTheDoor(0),
+            ElevatorVocabulary::DoorClosed(0),
+            ElevatorVocabulary::Stop(0),
+            ElevatorVocabulary::OpenTheDoor(0),
+            ElevatorVocabulary::DoorClosed(0),
+            ElevatorVocabulary::ElevatorOutOfService
+        ]);
+
+        tokio::time::timeout(Duration::from_secs(1), elev_handle)
+            .await
+            .expect("The elevator is still on")
+            .unwrap();
+        pulley_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_home_is_above_the_lowest_floor_then_the_car_goes_there_and_shuts_down_there() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+        let building = Building::from_json(r#"{ "floor_count": 4, "home": "2" }"#).unwrap();
+
+        let (pulley_ref, pulley_handle) = Actor::spawn(
+                                Some(String::from("Test_pulley_actor-12")),
+                                PulleyActor,
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+                            )
+                            .await
+                            .expect("Failed to create Pulley actor");
+
+        let (elev_ref, elev_handle) =
+            Actor::spawn(
+                Some(String::from("Elevator-Actor-12")),
+                PassengerLiftActor,
+                (building, Some(tx.clone()), pulley_ref.clone(), Box::new(LookStrategy), None)
+            ).await
+            .expect("Failed to start actor");
+
+        // Installed at the lowest floor, the car is parked at its home floor; floor 0 is not on its way.
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(2)).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Switched off there, it shuts down where it stands.
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(2)).unwrap();
+
+        let mut actual_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+                if !matches!(msg_received, ElevatorVocabulary::CurrentCarriagePosn(_)) {
+                    actual_messages.push(msg_received);
+                }
+        }
+        assert_eq!(actual_messages, vec![
+            ElevatorVocabulary::MoveToHomeFloor(2),
+            ElevatorVocabulary::MovingTo(2),
+            ElevatorVocabulary::OpenTheDoor(2),
+            ElevatorVocabulary::DoorClosed(2),
+            ElevatorVocabulary::Stay(2),
+            ElevatorVocabulary::MovingTo(2),
+            ElevatorVocabulary::Stop(2),
+            ElevatorVocabulary::OpenTheDoor(2),
+            ElevatorVocabulary::DoorClosed(2),
+            ElevatorVocabulary::ElevatorOutOfService
+        ]);
+
+        tokio::time::timeout(Duration::from_secs(1), elev_handle)
+            .await
+            .expect("The elevator is still on")
+            .unwrap();
+        pulley_handle.await.unwrap();
+
+        drop(tx);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn when_elev_is_moving_then_an_emergency_op_must_be_handled_after_reaching_dest() {
         let (tx,mut rx) = mpsc::unbounded_channel();
-        let floor_setting = vec! [
-            (0.0,0.0), (0.0,5.0), (0.0,10.0), (0.0,15.0) // x never changes between floors
-        ];
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
     
 
         let (pulley_ref, pulley_handle) = Actor::spawn(
                                 Some(String::from("Test_pulley_actor-21")), 
                                 PulleyActor, 
-                                floor_setting.clone()
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
                             )
                             .await
                             .expect("Failed to create Pulley actor")
@@ -545,7 +788,7 @@ mod test {
             Actor::spawn(
                 Some(String::from("Elevator-Actor-21")),
                 PassengerLiftActor,
-                (8,Some(tx.clone()),pulley_ref.clone())
+                (Building::with_floor_count(8).unwrap(),Some(tx.clone()),pulley_ref.clone(),Box::new(LookStrategy),None)
             ).await
             .expect("Failed to start actor");
 
@@ -555,7 +798,7 @@ mod test {
                 tokio::time::timeout(Duration::from_secs(1), rx.recv())
                 .await
                 .expect("timeout before messages are received from elevator") {
-            assert_eq!(msg_received,ElevatorVocabulary::MoveToGroundFloor);
+            assert_eq!(msg_received,ElevatorVocabulary::MoveToHomeFloor(0));
         }
 
         elev_ref.send_message(ElevatorVocabulary::MoveToFloor(3)).unwrap();
@@ -586,35 +829,24 @@ mod test {
                     actual_messages.push(msg_received.unwrap());        
         }
 
-        let mut expected_messages: Vec<ElevatorVocabulary> =  
-                    (1..=15)   // Hard-coded, because the floor_setting are pre-determinted.
-                    .into_iter()
-                    .map(|next_y| ElevatorVocabulary::CurrentCarriagePosn((0.0,next_y as f64)))
-                    .collect()
-                    ;
-        expected_messages.push(ElevatorVocabulary::OpenTheDoor(3));        
+        let mut expected_messages: Vec<ElevatorVocabulary> = vec![ElevatorVocabulary::OpenTheDoor(3)];
         
-        assert_contains_exactly!(expected_messages, actual_messages);
+        assert_contains_exactly!(expected_messages, carriage_went_to(&actual_messages, floor_levels.level(3)));
 
         actual_messages.clear();      // Forget earlier messages;
         expected_messages.clear();    // Forget earlier messages;
 
         // Because an emergency PowerOff had been sent already, the elevator must automatically move to
-        // ground floor (floor = 0), after the door is closed at the floor just reached (the door was 
+        // its home floor (floor 0, here), after the door is closed at the floor just reached (the door was 
         // opened). Therefore, messages from the elevator should be:
+        // 0. DoorClosed(3), for information
         // 1. MoveToFloor(0)
         // 2. Several CurrentCarriagePosn(x,y)
         // 3. OpenTheDoor(0)
 
-        expected_messages.push(ElevatorVocabulary::MoveToGroundFloor);
+        expected_messages.push(ElevatorVocabulary::DoorClosed(3));
+        expected_messages.push(ElevatorVocabulary::MoveToHomeFloor(0));
         expected_messages.push(ElevatorVocabulary::MovingTo(0));
-        let mut expected_position_messages: Vec<ElevatorVocabulary> = 
-            (0..15).rev() // Hard-coded, because the floor_setting are pre-determinted, and decreasing
-            .into_iter()
-            .map(|next_y| ElevatorVocabulary::CurrentCarriagePosn((0.0,next_y as f64)))
-            .collect()
-            ;
-        expected_messages.append(&mut expected_position_messages);
         expected_messages.push(ElevatorVocabulary::OpenTheDoor(0));  
 
         elev_ref.send_message(ElevatorVocabulary::DoorClosed(3)).unwrap(); 
@@ -623,11 +855,17 @@ mod test {
                     actual_messages.push(msg_received.unwrap());        
         }
     
-        assert_contains_exactly!(actual_messages,expected_messages);
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(0)),expected_messages);
 
         info!("asserted here");
 
         elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::DoorClosed(0));
+        }
         if let Some(msg_received) = 
             tokio::time::timeout(Duration::from_secs(1), rx.recv())
             .await
@@ -651,4 +889,408 @@ mod test {
         drop(tx);
 
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_passenger_calls_from_a_floor_on_the_way_then_the_carriage_stops_there_first() {
+        let (tx,mut rx) = mpsc::unbounded_channel();
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+
+        let (pulley_ref, pulley_handle) = Actor::spawn(
+                                Some(String::from("Test_pulley_actor-22")), 
+                                PulleyActor, 
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+                            )
+                            .await
+                            .expect("Failed to create Pulley actor")
+                            ;
+
+        let (elev_ref, elev_handle) = 
+            Actor::spawn(
+                Some(String::from("Elevator-Actor-22")),
+                PassengerLiftActor,
+                (Building::with_floor_count(8).unwrap(),Some(tx.clone()),pulley_ref.clone(),Box::new(LookStrategy),None)
+            ).await
+            .expect("Failed to start actor");
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        if let Some(msg_received) = 
+                tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from elevator") {
+            assert_eq!(msg_received,ElevatorVocabulary::MoveToHomeFloor(0));
+        }
+
+        elev_ref.send_message(ElevatorVocabulary::MoveToFloor(3)).unwrap();
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::MovingTo(3));
+        }
+
+        // A passenger at floor 1 calls, while the carriage has only just left the ground floor.
+        elev_ref.send_message(ElevatorVocabulary::MoveToFloor(1)).unwrap();
+
+        let mut actual_messages: Vec<ElevatorVocabulary> = Vec::new();
+        while let Ok(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(2), rx.recv()).await{                         
+                    actual_messages.push(msg_received.unwrap());        
+        }
+
+        let mut expected_messages: Vec<ElevatorVocabulary> = vec![ElevatorVocabulary::MovingTo(1)];
+        expected_messages.push(ElevatorVocabulary::OpenTheDoor(1));        
+
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(1)), expected_messages);
+
+        // Once the door closes, the carriage resumes its journey to floor 3.
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(1)).unwrap(); 
+
+        actual_messages.clear();
+        while let Ok(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(2), rx.recv()).await{                         
+                    actual_messages.push(msg_received.unwrap());        
+        }
+
+        expected_messages = vec![ElevatorVocabulary::DoorClosed(1), ElevatorVocabulary::MovingTo(3)];
+        expected_messages.push(ElevatorVocabulary::OpenTheDoor(3));        
+
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(3)), expected_messages);
+
+        elev_ref.stop(None);
+        pulley_ref.stop(None);
+        elev_handle.await.unwrap();
+        pulley_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_hall_call_is_answered_then_operator_is_told_which_direction() {
+        let (tx,mut rx) = mpsc::unbounded_channel();
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+
+        let (pulley_ref, pulley_handle) = Actor::spawn(
+                                Some(String::from("Test_pulley_actor-23")), 
+                                PulleyActor, 
+                                (floor_levels.clone(), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+                            )
+                            .await
+                            .expect("Failed to create Pulley actor")
+                            ;
+
+        let (elev_ref, elev_handle) = 
+            Actor::spawn(
+                Some(String::from("Elevator-Actor-23")),
+                PassengerLiftActor,
+                (Building::with_floor_count(4).unwrap(),Some(tx.clone()),pulley_ref.clone(),Box::new(LookStrategy),None)
+            ).await
+            .expect("Failed to start actor");
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        if let Some(msg_received) = 
+                tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from elevator") {
+            assert_eq!(msg_received,ElevatorVocabulary::MoveToHomeFloor(0));
+        }
+
+        // The carriage is standing at the ground floor; the door only needs to open.
+        elev_ref.send_message(ElevatorVocabulary::HallCall { floor: 0, direction: CallDirection::Up }).unwrap();
+        if let Some(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from elevator") {
+                assert_eq!(msg_received,ElevatorVocabulary::OpenTheDoor(0));
+        }
+
+        // A passenger boards, and presses floor 2 inside the carriage.
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 2 }).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+
+        let mut actual_messages: Vec<ElevatorVocabulary> = Vec::new();
+        while let Ok(msg_received) = 
+            tokio::time::timeout(Duration::from_secs(2), rx.recv()).await{                         
+                    actual_messages.push(msg_received.unwrap());        
+        }
+
+        let mut expected_messages = vec![
+            ElevatorVocabulary::DoorClosed(0),
+            ElevatorVocabulary::HallCallAnswered { floor: 0, direction: CallDirection::Up },
+            ElevatorVocabulary::MovingTo(2)
+        ];
+        expected_messages.push(ElevatorVocabulary::OpenTheDoor(2));        
+
+        assert_contains_exactly!(carriage_went_to(&actual_messages, floor_levels.level(2)), expected_messages);
+
+        elev_ref.stop(None);
+        pulley_ref.stop(None);
+        elev_handle.await.unwrap();
+        pulley_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    // Nothing heard from the car for this long (of simulated time), it has nothing left to do.
+    const IDLE: Duration = Duration::from_secs(30);
+
+    // Still busy after this long, the car is going round in circles.
+    const BUSY_AT_MOST: Duration = Duration::from_secs(600);
+
+    // Told to go to these floors (each call so many milliseconds after the one before it), the car
+    // opens its door at every one of them, after it has been called there. Whoever operates it,
+    // closes the door as soon as it has opened.
+    async fn every_call_is_answered(floor_levels: FloorLevels, calls: Vec<(u64, ElevatorVocabulary)>) -> Result<(), String> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let building = Building::with_floor_count(floor_levels.floor_count() as u16).unwrap();
+        let (pulley_ref, pulley_handle) =
+            Actor::spawn(None, PulleyActor, (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default()))
+            .await
+            .expect("Failed to create Pulley actor");
+        let (elev_ref, elev_handle) =
+            Actor::spawn(None, PassengerLiftActor, (building, Some(tx), pulley_ref.clone(), Box::new(LookStrategy), None))
+            .await
+            .expect("Failed to start actor");
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+
+        let mut calls = calls.into_iter();
+        let mut next_call = calls.next().map(|(after, call)| (tokio::time::Instant::now() + Duration::from_millis(after), call));
+        let mut unanswered = BTreeSet::new();
+        let give_up_at = tokio::time::Instant::now() + BUSY_AT_MOST;
+        let mut gave_up = false;
+        loop {
+            let call_at = next_call.as_ref().map(|(at, _)| *at).unwrap_or_else(tokio::time::Instant::now);
+            tokio::select! {
+                _ = tokio::time::sleep_until(call_at), if next_call.is_some() => {
+                    let (_, call) = next_call.take().unwrap();
+                    let floor = match call {
+                        ElevatorVocabulary::CarCall { floor } | ElevatorVocabulary::HallCall { floor, .. } | ElevatorVocabulary::MoveToFloor(floor) => floor,
+                        _ => unreachable!()
+                    };
+                    unanswered.insert(floor);
+                    elev_ref.send_message(call).unwrap();
+                    next_call = calls.next().map(|(after, call)| (tokio::time::Instant::now() + Duration::from_millis(after), call));
+                },
+                report = tokio::time::timeout(IDLE, rx.recv()) => match report {
+                    Ok(Some(ElevatorVocabulary::OpenTheDoor(floor))) => {
+                        unanswered.remove(&floor);
+                        elev_ref.send_message(ElevatorVocabulary::DoorClosed(floor)).unwrap();
+                    },
+                    Ok(Some(_)) => {},
+                    _ => break
+                },
+                _ = tokio::time::sleep_until(give_up_at) => {
+                    gave_up = true;
+                    break;
+                }
+            }
+        }
+
+        elev_ref.stop(None);
+        pulley_ref.stop(None);
+        elev_handle.await.unwrap();
+        pulley_handle.await.unwrap();
+
+        if gave_up { Err(format!("Still busy after ({:?})", BUSY_AT_MOST)) }
+        else if unanswered.is_empty() { Ok(()) }
+        else { Err(format!("Never opened the door at floors {:?}", unanswered)) }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn when_passengers_call_then_the_car_opens_its_door_at_every_floor_called(
+            (floor_levels, calls) in floor_levels(6).prop_flat_map(|levels| {
+                let floor_count = levels.floor_count();
+                (Just(levels), calls_over_time(floor_count, 8))
+            })
+        ) {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().start_paused(true).build().unwrap();
+            let answered = runtime.block_on(every_call_is_answered(floor_levels, calls));
+            prop_assert_eq!(answered, Ok(()));
+        }
+    }
+
+    // A pulley and an elevator (and the door given, if any), spawned under the names of the test.
+    async fn car_for_test(
+        test: u8,
+        door: Option<ActorRef<DoorVocabulary>>,
+        tx: mpsc::UnboundedSender<ElevatorVocabulary>
+    ) -> ((ActorRef<PulleyVocabulary>, JoinHandle<()>), (ActorRef<ElevatorVocabulary>, JoinHandle<()>)) {
+        let floor_levels = FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height);
+        let pulley = Actor::spawn(
+                Some(format!("Test_pulley_actor-{}", test)),
+                PulleyActor,
+                (floor_levels, BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+            )
+            .await
+            .expect("Failed to create Pulley actor");
+        let elevator = Actor::spawn(
+                Some(format!("Elevator-Actor-{}", test)),
+                PassengerLiftActor,
+                (Building::with_floor_count(4).unwrap(), Some(tx), pulley.0.clone(), Box::new(LookStrategy), door)
+            )
+            .await
+            .expect("Failed to start actor");
+        (pulley, elevator)
+    }
+
+    // What the elevator tells, until it has nothing more to tell for a while; where the carriage
+    // is on the way, is left out.
+    async fn heard(rx: &mut mpsc::UnboundedReceiver<ElevatorVocabulary>) -> Vec<ElevatorVocabulary> {
+        let mut messages = Vec::new();
+        while let Ok(Some(msg_received)) = timeout(Duration::from_secs(5), rx.recv()).await {
+            if !matches!(msg_received, ElevatorVocabulary::CurrentCarriagePosn(_)) {
+                messages.push(msg_received);
+            }
+        }
+        messages
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_operator_stops_listening_then_the_car_carries_on() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((_, pulley_handle), (elev_ref, elev_handle)) = car_for_test(24, None, tx).await;
+
+        // The operator goes, once the car has set off.
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 2 }).unwrap();
+        while rx.recv().await != Some(ElevatorVocabulary::MovingTo(2)) {}
+        drop(rx);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        // Nobody hears it, but the car has gone up, and goes back home to be powered off.
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(2)).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        elev_ref.send_message(ElevatorVocabulary::DoorClosed(0)).unwrap();
+
+        timeout(Duration::from_secs(5), elev_handle).await.expect("The elevator is still on").unwrap();
+        timeout(Duration::from_secs(5), pulley_handle).await.expect("The pulley is still on").unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_called_to_a_floor_beyond_the_building_then_the_call_is_turned_down() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((pulley_ref, pulley_handle), (elev_ref, elev_handle)) = car_for_test(25, None, tx).await;
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 9 }).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::HallCall { floor: 4, direction: CallDirection::Down }).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MoveToHomeFloor(0)]);
+
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 2 }).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MovingTo(2), ElevatorVocabulary::OpenTheDoor(2)]);
+
+        elev_ref.stop(None);
+        pulley_ref.stop(None);
+        elev_handle.await.unwrap();
+        pulley_handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_pulley_has_gone_then_the_car_is_out_of_order_until_powered_off() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((pulley_ref, pulley_handle), (elev_ref, elev_handle)) = car_for_test(26, None, tx).await;
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MoveToHomeFloor(0)]);
+
+        pulley_ref.stop(None);
+        pulley_handle.await.unwrap();
+
+        // The car is on its way, as far as it knows, until the pulley does not answer.
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 2 }).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MovingTo(2), ElevatorVocabulary::ElevatorOutOfService]);
+
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 1 }).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![]);
+        assert_eq!(elev_ref.get_status(), ActorStatus::Running);
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        timeout(Duration::from_secs(5), elev_handle).await.expect("The elevator is still on").unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_door_has_gone_then_the_car_is_out_of_order_until_powered_off() {
+        let door_times = DoorTimes { opening: Duration::from_secs(1), dwell: Duration::from_secs(2), closing: Duration::from_secs(1) };
+        let (door_ref, door_handle) = Actor::spawn(Some(String::from("Test_door-27")), DoorActor, (door_times, SimulationClock::default()))
+            .await
+            .expect("Failed to start door");
+        door_ref.stop(None);
+        door_handle.await.unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((_, pulley_handle), (elev_ref, elev_handle)) = car_for_test(27, Some(door_ref), tx).await;
+
+        // The pulley is switched off along with the car.
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::ElevatorOutOfService]);
+        timeout(Duration::from_secs(5), pulley_handle).await.expect("The pulley is still on").unwrap();
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        timeout(Duration::from_secs(5), elev_handle).await.expect("The elevator is still on").unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_door_goes_while_the_car_runs_then_the_car_is_out_of_order_until_powered_off() {
+        let door_times = DoorTimes { opening: Duration::from_secs(1), dwell: Duration::from_secs(2), closing: Duration::from_secs(1) };
+        let (door_ref, door_handle) = Actor::spawn(Some(String::from("Test_door-29")), DoorActor, (door_times, SimulationClock::default()))
+            .await
+            .expect("Failed to start door");
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((_, pulley_handle), (elev_ref, elev_handle)) = car_for_test(29, Some(door_ref.clone()), tx).await;
+
+        // The door goes, while the carriage is on its way.
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 2 }).unwrap();
+        while rx.recv().await != Some(ElevatorVocabulary::MovingTo(2)) {}
+        door_ref.stop(None);
+        door_handle.await.unwrap();
+
+        // Arrived, the car cannot open its door after all; the pulley is switched off along with the car.
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::OpenTheDoor(2), ElevatorVocabulary::ElevatorOutOfService]);
+        timeout(Duration::from_secs(5), pulley_handle).await.expect("The pulley is still on").unwrap();
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOff).unwrap();
+        timeout(Duration::from_secs(5), elev_handle).await.expect("The elevator is still on").unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_elevator_cannot_tell_itself_anymore_then_it_carries_on() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((pulley_ref, pulley_handle), (elev_ref, elev_handle)) = car_for_test(30, None, tx.clone()).await;
+
+        // Its mailbox is closed, as it is while the elevator stops; what it would have heard, is
+        // handled as the actor handles it.
+        elev_ref.stop(None);
+        elev_handle.await.unwrap();
+        let carriage_data = CarriageData::new(&Building::with_floor_count(4).unwrap(), Box::new(LookStrategy));
+        let mut elevator_control = ElevatorController::new(carriage_data, Some(tx), pulley_ref.clone(), None);
+
+        // Switched off, it would send itself home; it cannot, but it has told the operator so.
+        PassengerLiftActor.handle(elev_ref.clone(), ElevatorVocabulary::PowerOn, &mut elevator_control).await.unwrap();
+        PassengerLiftActor.handle(elev_ref.clone(), ElevatorVocabulary::PowerOff, &mut elevator_control).await.unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MoveToHomeFloor(0), ElevatorVocabulary::MovingTo(0)]);
+        assert_eq!(elevator_control.current_state(), ElevatorFSMStates::UnavailableForService);
+
+        pulley_ref.stop(None);
+        pulley_handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_carriage_stops_where_it_was_not_headed_then_the_elevator_fails() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let ((pulley_ref, pulley_handle), (elev_ref, elev_handle)) = car_for_test(28, None, tx).await;
+
+        elev_ref.send_message(ElevatorVocabulary::PowerOn).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::CarCall { floor: 3 }).unwrap();
+        elev_ref.send_message(ElevatorVocabulary::Stop(1)).unwrap();
+
+        timeout(Duration::from_secs(5), elev_handle).await.expect("The elevator is still on").unwrap();
+        assert_eq!(heard(&mut rx).await, vec![ElevatorVocabulary::MoveToHomeFloor(0), ElevatorVocabulary::MovingTo(3)]);
+
+        pulley_ref.stop(None);
+        pulley_handle.await.unwrap();
+    }
 }
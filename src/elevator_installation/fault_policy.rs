@@ -0,0 +1,11 @@
+/// What becomes of a part of the installation (an elevator, a pulley, the group), when something
+/// goes wrong in it. Every error of a part says which policy it is handled by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaultPolicy {
+    // The fault is logged, and the part carries on: nobody is put at risk by it.
+    LogAndContinue,
+    // The part stops, failing; its supervisor (the group controller) takes the car out of service.
+    Escalate,
+    // The part stays, but does nothing more: the car is out of order, until it is powered off.
+    EnterFaultState
+}
@@ -0,0 +1,45 @@
+// Generators for the property-based tests: floor tables, and what an elevator may be told. When a
+// property fails, proptest shrinks the case to the shortest sequence (of the lowest floors) that
+// still fails it.
+
+use proptest::prelude::*;
+
+use crate::conversation::vocabulary::{CallDirection, ElevatorVocabulary, Millimetres};
+use crate::elevator_installation::shaft::{FloorLevels, PIT_DEPTH};
+
+// Floors from a low storey to a tall lobby, in millimetres.
+const LOWEST_STOREY: Millimetres = 2_500;
+const TALLEST_STOREY: Millimetres = 8_000;
+
+/// The landings of a shaft of 2 to `max_floors` floors, every storey of a height of its own.
+pub fn floor_levels(max_floors: usize) -> impl Strategy<Value = FloorLevels> {
+    prop::collection::vec(LOWEST_STOREY..=TALLEST_STOREY, 1..max_floors).prop_map(|storeys| {
+        let levels = std::iter::once(PIT_DEPTH)
+            .chain(storeys.iter().scan(PIT_DEPTH, |level, storey| {
+                *level += storey;
+                Some(*level)
+            }))
+            .collect();
+        FloorLevels::with_levels(levels).unwrap()
+    })
+}
+
+pub fn call_direction() -> impl Strategy<Value = CallDirection> {
+    prop_oneof![Just(CallDirection::Up), Just(CallDirection::Down)]
+}
+
+/// A passenger asking for a floor (below `floor_count`): from inside the carriage, or on a
+/// landing, or as the operator sending the car there.
+pub fn call(floor_count: u8) -> impl Strategy<Value = ElevatorVocabulary> {
+    let floor = 0..floor_count;
+    prop_oneof![
+        floor.clone().prop_map(|floor| ElevatorVocabulary::CarCall { floor }),
+        (floor.clone(), call_direction()).prop_map(|(floor, direction)| ElevatorVocabulary::HallCall { floor, direction }),
+        floor.prop_map(ElevatorVocabulary::MoveToFloor)
+    ]
+}
+
+/// Calls, each so many milliseconds (of simulated time) after the one before it.
+pub fn calls_over_time(floor_count: u8, max_calls: usize) -> impl Strategy<Value = Vec<(u64, ElevatorVocabulary)>> {
+    prop::collection::vec((0..5_000u64, call(floor_count)), 1..=max_calls)
+}
@@ -0,0 +1,1150 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use async_trait::async_trait;
+use ractor::concurrency::Duration;
+use ractor::{Actor, ActorId, ActorProcessingErr, ActorRef, SupervisionEvent};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::info;
+
+use crate::building::{Building, RatedLoad};
+use crate::conversation::vocabulary::{CallDirection, CarIndex, DoorVocabulary, ElevatorVocabulary, GroupVocabulary, Millimetres, Observer, PulleyVocabulary};
+use crate::elevator_installation::destination_dispatch::{choose_car_for_destination, DestinationCall};
+use crate::elevator_installation::dispatch_strategy::DispatchStrategy;
+use crate::elevator_installation::door_machinery::{DoorActor, DoorTimes};
+use crate::elevator_installation::elevator_service::PassengerLiftActor;
+use crate::elevator_installation::fault_policy::FaultPolicy;
+use crate::elevator_installation::motion_profile::MotionProfile;
+use crate::elevator_installation::pulley_machinery::{PulleyActor, PULLEY_STEP_INTERVAL};
+use crate::elevator_installation::shaft::FloorLevels;
+use crate::elevator_installation::travel_scheduler::TravelDirection;
+use crate::simulation_clock::SimulationClock;
+
+// At every stop, the door is held open for these many seconds (unless told otherwise).
+pub const TIME_SPENT_AT_EVERY_STOP: Duration = Duration::from_secs(2);
+
+// How long the door takes to open, and to close (unless told otherwise).
+pub const DOOR_OPENING_TIME: Duration = Duration::from_secs(1);
+pub const DOOR_CLOSING_TIME: Duration = Duration::from_secs(1);
+
+// Every car is an elevator, with a pulley and a door of its own.
+const ACTORS_PER_CAR: usize = 3;
+
+/// The letter a car goes by, on the screen and in the log: A for the first one.
+pub fn car_letter(car: CarIndex) -> char {
+    (b'A' + car as u8) as char
+}
+
+/// How fast the machinery of every car works: the pulley moves the carriage as its motion profile
+/// allows, telling where it is at every step, and the door opens, is held open for a while, and
+/// closes at every stop (see DoorActor).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OperatingTimes {
+    pub pulley_step_interval: Duration,
+    pub door_opening: Duration,
+    pub door_dwell: Duration,
+    pub door_closing: Duration,
+    #[serde(default)]
+    pub motion: MotionProfile
+}
+
+impl Default for OperatingTimes {
+    fn default() -> Self {
+        OperatingTimes {
+            pulley_step_interval: PULLEY_STEP_INTERVAL,
+            door_opening: DOOR_OPENING_TIME,
+            door_dwell: TIME_SPENT_AT_EVERY_STOP,
+            door_closing: DOOR_CLOSING_TIME,
+            motion: MotionProfile::default()
+        }
+    }
+}
+
+/// How long it takes a car to travel one floor, and to stop at one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EtaTiming {
+    pub per_floor: Duration,
+    pub per_stop: Duration
+}
+
+impl OperatingTimes {
+    pub fn door_times(&self) -> DoorTimes {
+        DoorTimes { opening: self.door_opening, dwell: self.door_dwell, closing: self.door_closing }
+    }
+}
+
+impl EtaTiming {
+    // Floors are equally far apart, and passed at the rated speed; a stop also costs the time
+    // spent slowing down and speeding up again, and a whole cycle of the door.
+    pub fn new(operating_times: &OperatingTimes) -> Self {
+        EtaTiming {
+            per_floor: operating_times.motion.time_per_floor(),
+            per_stop: operating_times.door_opening
+                + operating_times.door_dwell
+                + operating_times.door_closing
+                + operating_times.motion.time_lost_per_stop()
+        }
+    }
+}
+
+/// What the group controller knows about a car: only what the car has told its operator, and the
+/// calls that the group has passed on to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CarStatus {
+    pub at_floor: u8,
+    pub heading_to: Option<u8>,
+    pub stops: BTreeSet<u8>,
+    pub in_service: bool,
+    pub is_full: bool
+}
+
+impl CarStatus {
+    // Where the car goes next. A car standing with its door open has no destination yet, but the
+    // stops it has queued up, take it on to the nearest of them.
+    pub fn next_stop(&self) -> Option<u8> {
+        self.heading_to.or_else(|| self.stops.iter().copied().min_by_key(|stop| stop.abs_diff(self.at_floor)))
+    }
+
+    pub fn heading(&self) -> TravelDirection {
+        match self.next_stop() {
+            Some(dest) if dest > self.at_floor => TravelDirection::Up,
+            Some(dest) if dest < self.at_floor => TravelDirection::Down,
+            _ => TravelDirection::Idle
+        }
+    }
+
+    // A moving car is past `at_floor`. A call ahead of it, in its direction of travel, is reached on
+    // the way: only the stops before it, hold the car up. Otherwise, the car completes its run
+    // (turning at the farthest of its stops) before coming back for the call, and every stop it
+    // has, holds it up.
+    pub fn estimated_time_of_arrival(&self, floor: u8, direction: CallDirection, timing: &EtaTiming) -> Duration {
+        let at_floor = self.at_floor;
+        let farthest_stop = |heading_to: u8| match self.heading() {
+            TravelDirection::Up   => self.stops.iter().copied().fold(heading_to.max(floor), u8::max),
+            TravelDirection::Down => self.stops.iter().copied().fold(heading_to.min(floor), u8::min),
+            TravelDirection::Idle => heading_to
+        };
+
+        let (floors_to_travel, stops_on_the_way) = match (self.heading(), direction) {
+            (TravelDirection::Idle, _) => (at_floor.abs_diff(floor), self.stops.len()),
+            (TravelDirection::Up, CallDirection::Up) if floor > at_floor => (
+                floor - at_floor,
+                self.stops.iter().filter(|stop| **stop > at_floor && **stop < floor).count()
+            ),
+            (TravelDirection::Down, CallDirection::Down) if floor < at_floor => (
+                at_floor - floor,
+                self.stops.iter().filter(|stop| **stop < at_floor && **stop > floor).count()
+            ),
+            (_, _) => {
+                let turn_at = farthest_stop(self.next_stop().unwrap_or(at_floor));
+                (at_floor.abs_diff(turn_at) + turn_at.abs_diff(floor), self.stops.len())
+            }
+        };
+
+        timing.per_floor * floors_to_travel as u32 + timing.per_stop * stops_on_the_way as u32
+    }
+}
+
+// The car in service that would arrive the soonest, if any car is in service; a full car, only if
+// every car in service is full. Ties go to the car earlier in the group.
+pub fn choose_car(cars: &[CarStatus], floor: u8, direction: CallDirection, timing: &EtaTiming) -> Option<CarIndex> {
+    cars.iter()
+        .enumerate()
+        .filter(|(_, car)| car.in_service)
+        .min_by_key(|(_, car)| (car.is_full, car.estimated_time_of_arrival(floor, direction, timing)))
+        .map(|(car_index, _)| car_index)
+}
+
+/// What can go wrong in a group: a car may have stopped, or one of its parts may have failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GroupError {
+    NoSuchCar(CarIndex),    // the group has no car by that index; whatever is meant for it, is dropped
+    CarGone(CarIndex),      // the car has stopped; whatever is meant for it, is dropped
+    CarFailed(CarIndex),    // a part of the car has failed; the car is out of order
+    PartFailed(String)      // an actor of the group, that is no part of any car, has failed
+}
+
+impl GroupError {
+    pub fn policy(&self) -> FaultPolicy {
+        match self {
+            GroupError::NoSuchCar(_) | GroupError::CarGone(_) => FaultPolicy::LogAndContinue,
+            // The other cars carry on.
+            GroupError::CarFailed(_) => FaultPolicy::EnterFaultState,
+            GroupError::PartFailed(_) => FaultPolicy::Escalate
+        }
+    }
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupError::NoSuchCar(car) => write!(f, "there is no car ({}) in the group", car),
+            GroupError::CarGone(car) => write!(f, "car ({}) is not there anymore", car_letter(*car)),
+            GroupError::CarFailed(car) => write!(f, "car ({}) has failed", car_letter(*car)),
+            GroupError::PartFailed(who) => write!(f, "({}) has failed", who)
+        }
+    }
+}
+
+impl std::error::Error for GroupError {}
+
+#[derive(Debug)]
+pub struct GroupData {
+    cars: Vec<(ActorRef<ElevatorVocabulary>, ActorRef<PulleyVocabulary>, ActorRef<DoorVocabulary>)>,
+    car_status: Vec<CarStatus>,
+    assigned_hall_calls: HashMap<(u8, CallDirection), CarIndex>,
+    destination_calls: Vec<DestinationCall>,
+    floor_levels: FloorLevels,
+    timing: EtaTiming,
+    rated_load: RatedLoad,
+    op_informant_channel: Option<UnboundedSender<(CarIndex, ElevatorVocabulary)>>,
+    observers: Vec<Observer>,
+    actors_terminated: usize
+}
+
+impl GroupData {
+    // The floor that a moving carriage has passed last; it can only stop at floors beyond it.
+    fn floor_passed_last(&self, level: Millimetres, heading: TravelDirection) -> Option<u8> {
+        match heading {
+            TravelDirection::Up   => self.floor_levels.floor_at_or_below(level),
+            TravelDirection::Down => self.floor_levels.floor_at_or_above(level),
+            TravelDirection::Idle => None
+        }
+    }
+
+    // Returns false, if the operator need not be told: a hall call answered for a passenger who
+    // has used a kiosk, has no lamp lit on the landing.
+    fn on_report_from_car(&mut self, car: CarIndex, report: &ElevatorVocabulary) -> bool {
+        let floor_passed_last = match report {
+            ElevatorVocabulary::CurrentCarriagePosn(level) => self.floor_passed_last(*level, self.car_status[car].heading()),
+            _ => None
+        };
+        let status = &mut self.car_status[car];
+
+        match report {
+            ElevatorVocabulary::MoveToHomeFloor(home) => {
+                status.in_service = true;
+                status.heading_to = Some(*home);
+            },
+            ElevatorVocabulary::MovingTo(f) => status.heading_to = Some(*f),
+            ElevatorVocabulary::CurrentCarriagePosn(_) => {
+                status.at_floor = floor_passed_last.unwrap_or(status.at_floor);
+            },
+            ElevatorVocabulary::OpenTheDoor(f) => {
+                status.at_floor = *f;
+                status.heading_to = None;
+                status.stops.remove(f);
+            },
+            ElevatorVocabulary::Stay(f) => {
+                status.at_floor = *f;
+                status.heading_to = None;
+            },
+            ElevatorVocabulary::LoadWeighed(load) => status.is_full = self.rated_load.is_full(load),
+            ElevatorVocabulary::HallCallAnswered { floor, direction } => {
+                if self.assigned_hall_calls.get(&(*floor, *direction)) != Some(&car) {
+                    return false;
+                }
+                self.assigned_hall_calls.remove(&(*floor, *direction));
+            },
+            // Told only once: the group may have noticed it first (see handle_supervisor_evt).
+            ElevatorVocabulary::ElevatorOutOfService => return std::mem::replace(&mut status.in_service, false),
+            _ => {}
+        }
+        true
+    }
+
+    // The car has opened its door at `floor`. The passengers who have been told to take this car,
+    // and are going the way it heads next, board it while the door is open; their destinations
+    // become its car calls before it decides where to go. A car with nowhere else to go, takes the
+    // passenger who has waited the longest, and those going the same way.
+    fn board_destination_passengers(&mut self, car: CarIndex, floor: u8) -> Vec<u8> {
+        let waiting_here = |call: &&DestinationCall| call.car == car && call.from_floor == floor;
+        let heading = match self.car_status[car].heading() {
+            TravelDirection::Up   => Some(CallDirection::Up),
+            TravelDirection::Down => Some(CallDirection::Down),
+            TravelDirection::Idle => None
+        };
+        let Some(direction) = heading
+            .filter(|direction| self.destination_calls.iter().filter(waiting_here).any(|call| call.direction() == *direction))
+            .or_else(|| self.destination_calls.iter().find(waiting_here).map(DestinationCall::direction)) else {
+            return Vec::new();
+        };
+        let (boarding, still_waiting) = self.destination_calls
+            .iter()
+            .partition(|call| waiting_here(call) && call.direction() == direction);
+        self.destination_calls = still_waiting;
+
+        boarding.iter().map(|call: &DestinationCall| call.to_floor).collect()
+    }
+
+    // The car an actor is a part of: its elevator, its pulley, or its door.
+    fn car_of(&self, who: ActorId) -> Option<CarIndex> {
+        self.cars.iter().position(|(elevator, pulley, door)| [elevator.get_id(), pulley.get_id(), door.get_id()].contains(&who))
+    }
+
+    fn tell_car(&mut self, car: CarIndex, message: ElevatorVocabulary) -> Result<(), GroupError> {
+        let Some((elevator, _, _)) = self.cars.get(car) else {
+            return self.on_error(GroupError::NoSuchCar(car));
+        };
+        match elevator.send_message(message) {
+            Ok(()) => Ok(()),
+            Err(_) => self.on_error(GroupError::CarGone(car))
+        }
+    }
+
+    // The error is handled as its policy says; only an error to escalate, is given back.
+    fn on_error(&mut self, error: GroupError) -> Result<(), GroupError> {
+        match (error.policy(), &error) {
+            (FaultPolicy::EnterFaultState, GroupError::CarFailed(car)) => {
+                info!("Group: {}, taking it out of service", error);
+                let car = *car;
+                if std::mem::replace(&mut self.car_status[car].in_service, false) {
+                    self.inform_operator(car, ElevatorVocabulary::ElevatorOutOfService);
+                }
+                // What is left of the car, is stopped; the group stops once every part has.
+                let (elevator, pulley, door) = &self.cars[car];
+                elevator.stop(Some(String::from("Car out of order")));
+                pulley.stop(Some(String::from("Car out of order")));
+                door.stop(Some(String::from("Car out of order")));
+                // Its calls are handed over to the cars still in service.
+                self.reassign_hall_calls_of(car)?;
+                self.reassign_destination_calls_of(car)
+            },
+            (FaultPolicy::Escalate, _) => {
+                info!("Group: {}, giving up", error);
+                Err(error)
+            },
+            (_, _) => {
+                info!("Group: {}, carrying on", error);
+                Ok(())
+            }
+        }
+    }
+
+    // The operator, and every observer still there, hear the report.
+    fn inform_operator(&mut self, car: CarIndex, report: ElevatorVocabulary) {
+        self.observers.retain(|observer| {
+            observer.tell(car, report.clone()) || {
+                info!("Group: ({:?}) stopped observing", observer);
+                false
+            }
+        });
+
+        if let Some(channel) = self.op_informant_channel.as_ref() {
+            channel.send((car, report)).unwrap_or_else(|e| {
+                info!("Receiver stopped, {:?}", e.0);
+            });
+        }
+    }
+
+    fn assign_hall_call(&mut self, floor: u8, direction: CallDirection) -> Result<(), GroupError> {
+        match choose_car(&self.car_status, floor, direction, &self.timing) {
+            Some(car) => {
+                info!("Group: hall call ({}, {:?}) is assigned to car ({}), ETA {:?}",
+                    floor,
+                    direction,
+                    car_letter(car),
+                    self.car_status[car].estimated_time_of_arrival(floor, direction, &self.timing)
+                );
+                self.assigned_hall_calls.insert((floor, direction), car);
+                self.car_status[car].stops.insert(floor);
+                self.tell_car(car, ElevatorVocabulary::HallCall { floor, direction })
+            },
+            None => {
+                info!("Group: hall call ({}, {:?}) is dropped, no car is in service", floor, direction);
+                Ok(())
+            }
+        }
+    }
+
+    fn assign_destination_call(&mut self, from_floor: u8, to_floor: u8) -> Result<(), GroupError> {
+        match choose_car_for_destination(&self.car_status, &self.destination_calls, from_floor, to_floor, &self.timing) {
+            Some(car) => {
+                info!("Group: destination ({}) from floor ({}) is assigned to car ({})", to_floor, from_floor, car_letter(car));
+                let destination_call = DestinationCall { from_floor, to_floor, car };
+                self.destination_calls.push(destination_call);
+                self.car_status[car].stops.insert(from_floor);
+                self.tell_car(car, ElevatorVocabulary::HallCall { floor: from_floor, direction: destination_call.direction() })?;
+                self.inform_operator(car, ElevatorVocabulary::DestinationAssigned { from_floor, to_floor });
+                Ok(())
+            },
+            None => {
+                info!("Group: destination ({}) from floor ({}) is dropped, no car is in service", to_floor, from_floor);
+                Ok(())
+            }
+        }
+    }
+
+    // A car that has gone out of service answers none of the hall calls assigned to it; they are
+    // assigned afresh, to the cars still in service.
+    fn reassign_hall_calls_of(&mut self, car: CarIndex) -> Result<(), GroupError> {
+        let hall_calls: Vec<(u8, CallDirection)> = self.assigned_hall_calls
+            .iter()
+            .filter(|(_, assigned_to)| **assigned_to == car)
+            .map(|(hall_call, _)| *hall_call)
+            .collect();
+
+        for (floor, direction) in hall_calls {
+            self.assigned_hall_calls.remove(&(floor, direction));
+            self.car_status[car].stops.remove(&floor);
+            self.assign_hall_call(floor, direction)?;
+        }
+        Ok(())
+    }
+
+    // Nor does it pick up the passengers still waiting for it; each is told to take another car.
+    fn reassign_destination_calls_of(&mut self, car: CarIndex) -> Result<(), GroupError> {
+        let (stranded, still_assigned): (Vec<DestinationCall>, Vec<DestinationCall>) =
+            std::mem::take(&mut self.destination_calls)
+                .into_iter()
+                .partition(|call| call.car == car);
+        self.destination_calls = still_assigned;
+
+        for call in stranded {
+            self.car_status[car].stops.remove(&call.from_floor);
+            self.assign_destination_call(call.from_floor, call.to_floor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Owns a group of cars, each an elevator with its own pulley, in a shaft of its own. A hall call
+/// is assigned to the car estimated to arrive there the soonest; car calls go to the car they are
+/// pressed in. A passenger who enters a destination at a kiosk, is told which car to take.
+/// Whatever a car tells its operator, is passed on with the car's index in the group.
+pub struct GroupControllerActor;
+
+#[async_trait]
+impl Actor for GroupControllerActor {
+    type Msg = GroupVocabulary;
+    type State = GroupData;
+    // One dispatch strategy per car: as many cars are spawned, as there are strategies.
+    type Arguments = (
+        Building,
+        OperatingTimes,
+        SimulationClock,
+        Vec<Box<dyn DispatchStrategy>>,
+        Option<UnboundedSender<(CarIndex, ElevatorVocabulary)>>
+    );
+
+    async fn pre_start(&self, myself: ActorRef<Self::Msg>, args: Self::Arguments) ->
+        Result<Self::State, ActorProcessingErr> {
+            let (building, operating_times, clock, dispatch_strategies, op_informant_channel) = args;
+            let floor_levels = FloorLevels::evenly_spaced(building.floor_count(), operating_times.motion.floor_height);
+            let group_name = myself.get_name().unwrap_or(String::from("Group"));
+
+            let mut cars = Vec::new();
+            for (car, dispatch_strategy) in dispatch_strategies.into_iter().enumerate() {
+                let (pulley_ref, _) = Actor::spawn_linked(
+                    Some(format!("{}-Pulley-{}", group_name, car_letter(car))),
+                    PulleyActor,
+                    (floor_levels.clone(), operating_times.motion, operating_times.pulley_step_interval, clock.clone()),
+                    myself.get_cell()
+                )
+                .await?;
+
+                let (door_ref, _) = Actor::spawn_linked(
+                    Some(format!("{}-Door-{}", group_name, car_letter(car))),
+                    DoorActor,
+                    (operating_times.door_times(), clock.clone()),
+                    myself.get_cell()
+                )
+                .await?;
+
+                // Whatever the car tells its operator, reaches the group first.
+                let (car_tx, mut car_rx) = mpsc::unbounded_channel();
+                let group = myself.clone();
+                tokio::spawn(async move {
+                    while let Some(report) = car_rx.recv().await {
+                        if group.send_message(GroupVocabulary::FromCar(car, report)).is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                let (elev_ref, _) = Actor::spawn_linked(
+                    Some(format!("{}-Elevator-{}", group_name, car_letter(car))),
+                    PassengerLiftActor,
+                    (building.clone(), Some(car_tx), pulley_ref.clone(), dispatch_strategy, Some(door_ref.clone())),
+                    myself.get_cell()
+                )
+                .await?;
+
+                cars.push((elev_ref, pulley_ref, door_ref));
+            }
+
+            info!("Group: ({}) cars are ready", cars.len());
+
+            Ok(GroupData {
+                car_status: vec![CarStatus::default(); cars.len()],
+                cars,
+                assigned_hall_calls: HashMap::new(),
+                destination_calls: Vec::new(),
+                timing: EtaTiming::new(&operating_times),
+                rated_load: building.rated_load(),
+                floor_levels,
+                op_informant_channel,
+                observers: Vec::new(),
+                actors_terminated: 0
+            })
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        group: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+
+        match message {
+            GroupVocabulary::PowerOn => {
+                // A call made before a car reports back, is still for the cars just powered on.
+                for car in 0..group.cars.len() {
+                    group.car_status[car].in_service = true;
+                    group.tell_car(car, ElevatorVocabulary::PowerOn)?;
+                }
+            },
+            GroupVocabulary::PowerOff => {
+                for car in 0..group.cars.len() {
+                    group.tell_car(car, ElevatorVocabulary::PowerOff)?;
+                }
+            },
+            GroupVocabulary::HallCall { floor, direction } => {
+                match group.assigned_hall_calls.get(&(floor, direction)) {
+                    Some(car) if group.car_status[*car].in_service => {
+                        info!("Group: hall call ({}, {:?}) is already assigned to car ({})", floor, direction, car_letter(*car));
+                    },
+                    _ => group.assign_hall_call(floor, direction)?
+                }
+            },
+            GroupVocabulary::ToCar(car, message) => {
+                if car >= group.cars.len() {
+                    group.on_error(GroupError::NoSuchCar(car))?;
+                    return Ok(());
+                }
+                match &message {
+                    ElevatorVocabulary::CarCall { floor } => { group.car_status[car].stops.insert(*floor); },
+                    ElevatorVocabulary::LoadWeighed(load) => group.car_status[car].is_full = group.rated_load.is_full(load),
+                    // The door closes on its own; nobody else may say that it has.
+                    ElevatorVocabulary::DoorClosed(_) => {
+                        info!("Group: car ({}) closes its own door", car_letter(car));
+                        return Ok(());
+                    },
+                    _ => {}
+                }
+                group.tell_car(car, message)?;
+            },
+            GroupVocabulary::DestinationCall { from_floor, to_floor } => {
+                if from_floor == to_floor || !group.floor_levels.contains(to_floor) {
+                    info!("Group: destination ({}) from floor ({}) is not served", to_floor, from_floor);
+                }
+                else {
+                    group.assign_destination_call(from_floor, to_floor)?;
+                }
+            },
+            GroupVocabulary::Subscribe(observer) => {
+                info!("Group: ({:?}) is observing", observer);
+                group.observers.push(observer);
+            },
+            GroupVocabulary::FromCar(car, report) => {
+                if group.on_report_from_car(car, &report) {
+                    group.inform_operator(car, report.clone());
+                }
+
+                if report == ElevatorVocabulary::ElevatorOutOfService {
+                    group.reassign_hall_calls_of(car)?;
+                    group.reassign_destination_calls_of(car)?;
+                }
+
+                if let ElevatorVocabulary::OpenTheDoor(floor) = report {
+                    for to_floor in group.board_destination_passengers(car, floor) {
+                        group.car_status[car].stops.insert(to_floor);
+                        group.tell_car(car, ElevatorVocabulary::CarCall { floor: to_floor })?;
+                        // The kiosk has pressed the floor on the passenger's behalf.
+                        group.inform_operator(car, ElevatorVocabulary::CarCall { floor: to_floor });
+                    }
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    // Every car stops on its own, once it has been powered off. The group stops only after all
+    // the elevators, pulleys and doors have stopped. A part of a car that fails, takes the car out
+    // of service; the other cars carry on.
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        group: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisionEvent::ActorTerminated(who, _, _) => {
+                group.actors_terminated += 1;
+                info!("Group: ({:?}) has stopped, ({}) of ({})", who.get_name(), group.actors_terminated, ACTORS_PER_CAR * group.cars.len());
+
+                // The car's own report may not have reached us yet; it never will, once we stop.
+                // Its calls are handed over to the cars still in service.
+                let stopped_car = group.cars.iter().position(|(elevator, _, _)| elevator.get_id() == who.get_id());
+                if let Some(car) = stopped_car.filter(|car| group.car_status[*car].in_service) {
+                    group.car_status[car].in_service = false;
+                    group.inform_operator(car, ElevatorVocabulary::ElevatorOutOfService);
+                    group.reassign_hall_calls_of(car)?;
+                    group.reassign_destination_calls_of(car)?;
+                }
+                if group.actors_terminated == ACTORS_PER_CAR * group.cars.len() {
+                    myself.stop(Some(String::from("All cars are out of service")));
+                }
+            },
+            SupervisionEvent::ActorPanicked(who, reason) => {
+                group.actors_terminated += 1;
+                info!("Group: ({:?}) has failed, {}", who.get_name(), reason);
+
+                let error = match group.car_of(who.get_id()) {
+                    Some(car) => GroupError::CarFailed(car),
+                    None => GroupError::PartFailed(who.get_name().unwrap_or_default())
+                };
+                group.on_error(error)?;
+                if group.actors_terminated == ACTORS_PER_CAR * group.cars.len() {
+                    myself.stop(Some(String::from("All cars are out of service")));
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::conversation::vocabulary::DoorState;
+    use crate::elevator_installation::dispatch_strategy::LookStrategy;
+    use crate::elevator_installation::motion_profile::BRISK_MOTION;
+    use crate::elevator_installation::shaft::PIT_DEPTH;
+    use ractor::ActorStatus;
+    use tokio::sync::mpsc;
+
+    fn timing() -> EtaTiming {
+        EtaTiming { per_floor: Duration::from_secs(1), per_stop: Duration::from_secs(2) }
+    }
+
+    fn car(at_floor: u8, heading_to: Option<u8>, stops: &[u8]) -> CarStatus {
+        CarStatus { at_floor, heading_to, stops: stops.iter().copied().collect(), in_service: true, is_full: false }
+    }
+
+    #[test]
+    fn when_call_is_ahead_in_direction_of_travel_then_only_stops_before_it_count() {
+        let going_up = car(2, Some(7), &[4, 7]);
+
+        assert_eq!(going_up.estimated_time_of_arrival(5, CallDirection::Up, &timing()), Duration::from_secs(3 + 2));
+        assert_eq!(going_up.estimated_time_of_arrival(3, CallDirection::Up, &timing()), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn when_call_is_behind_or_against_direction_of_travel_then_car_completes_its_run_first() {
+        let going_up = car(2, Some(5), &[5, 7]);
+
+        // Up to 7, and back down to 4 (or 1), stopping at 5 and 7 on the way.
+        assert_eq!(going_up.estimated_time_of_arrival(4, CallDirection::Down, &timing()), Duration::from_secs(5 + 3 + 4));
+        assert_eq!(going_up.estimated_time_of_arrival(1, CallDirection::Up, &timing()), Duration::from_secs(5 + 6 + 4));
+    }
+
+    #[test]
+    fn when_a_car_stands_with_its_door_open_and_stops_queued_then_it_is_costed_as_busy() {
+        let door_open = car(4, None, &[6, 7]);
+        let cars = [door_open.clone(), car(0, None, &[])];
+
+        // Up to 7, and back down to 3, stopping at 6 and 7 on the way.
+        assert_eq!(door_open.heading(), TravelDirection::Up);
+        assert_eq!(door_open.estimated_time_of_arrival(3, CallDirection::Down, &timing()), Duration::from_secs(3 + 4 + 4));
+        assert_eq!(choose_car(&cars, 3, CallDirection::Down, &timing()), Some(1));
+    }
+
+    #[test]
+    fn when_cars_are_compared_then_the_soonest_in_service_wins() {
+        let mut out_of_service = car(3, None, &[]);
+        out_of_service.in_service = false;
+        let cars = [out_of_service, car(0, Some(6), &[6]), car(7, None, &[])];
+
+        assert_eq!(choose_car(&cars, 3, CallDirection::Up, &timing()), Some(1));
+        assert_eq!(choose_car(&cars, 3, CallDirection::Down, &timing()), Some(2));
+        assert_eq!(choose_car(&cars[..1], 3, CallDirection::Down, &timing()), None);
+        assert_eq!(choose_car(&[], 3, CallDirection::Down, &timing()), None);
+    }
+
+    #[test]
+    fn when_the_nearest_car_is_full_then_another_car_answers() {
+        let mut full = car(2, None, &[]);
+        full.is_full = true;
+        let cars = [full.clone(), car(6, None, &[])];
+
+        assert_eq!(choose_car(&cars, 3, CallDirection::Up, &timing()), Some(1));
+        assert_eq!(choose_car(&[full], 3, CallDirection::Up, &timing()), Some(0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_one_car_is_busy_then_hall_call_is_assigned_to_the_idle_car() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-30")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+
+        let mut powered_on = Vec::new();
+        while powered_on.len() < 2 {
+            let msg_received = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+            powered_on.push(msg_received.unwrap());
+        }
+        powered_on.sort_by_key(|(car, _)| *car);
+        assert_eq!(
+            powered_on,
+            vec![(0, ElevatorVocabulary::MoveToHomeFloor(0)), (1, ElevatorVocabulary::MoveToHomeFloor(0))]
+        );
+
+        // Car A leaves for the top floor; a passenger at floor 1 then wants to go up.
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::CarCall { floor: 3 })).unwrap();
+        if let Some(msg_received) =
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from group") {
+                assert_eq!(msg_received, (0, ElevatorVocabulary::MovingTo(3)));
+        }
+
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        group_ref.send_message(GroupVocabulary::HallCall { floor: 1, direction: CallDirection::Up }).unwrap();
+
+        let mut car_b_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_secs(2), rx.recv()).await {
+                if msg_received.0 == 1 {
+                    car_b_messages.push(msg_received.1);
+                }
+        }
+
+        // Car B closes its door on its own, once the passenger has had time to board.
+        let expected_messages = vec![
+            ElevatorVocabulary::MovingTo(1),
+            ElevatorVocabulary::OpenTheDoor(1),
+            ElevatorVocabulary::DoorStateChanged(1, DoorState::Opening),
+            ElevatorVocabulary::DoorStateChanged(1, DoorState::Open),
+            ElevatorVocabulary::DoorStateChanged(1, DoorState::Closing),
+            ElevatorVocabulary::DoorClosed(1),
+            ElevatorVocabulary::HallCallAnswered { floor: 1, direction: CallDirection::Up },
+            ElevatorVocabulary::Stay(1)
+        ];
+        let (car_b_positions, car_b_messages): (Vec<_>, Vec<_>) = car_b_messages
+            .into_iter()
+            .partition(|msg| matches!(msg, ElevatorVocabulary::CurrentCarriagePosn(_)));
+
+        assertx::assert_contains_exactly!(car_b_messages, expected_messages);
+        assert_eq!(car_b_positions.last(), Some(&ElevatorVocabulary::CurrentCarriagePosn(PIT_DEPTH + 5_000)));
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_destination_is_entered_at_kiosk_then_car_is_assigned_and_given_the_floor_on_boarding() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-31")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+        }
+
+        group_ref.send_message(GroupVocabulary::DestinationCall { from_floor: 0, to_floor: 3 }).unwrap();
+
+        let mut actual_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+                actual_messages.push(msg_received);
+        }
+        assert_eq!(actual_messages, vec![
+            (0, ElevatorVocabulary::DestinationAssigned { from_floor: 0, to_floor: 3 }),
+            (0, ElevatorVocabulary::OpenTheDoor(0)),
+            (0, ElevatorVocabulary::CarCall { floor: 3 }),
+            (0, ElevatorVocabulary::DoorStateChanged(0, DoorState::Opening))
+        ]);
+
+        // Nobody but the door may say that it is closed.
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::DoorClosed(0))).unwrap();
+
+        // The passenger boards car A, while its door is open; the kiosk has already told the car
+        // where to go, and it leaves for there directly.
+        actual_messages.clear();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_secs(3), rx.recv()).await {
+                let leaving = msg_received == (0, ElevatorVocabulary::MovingTo(3));
+                actual_messages.push(msg_received);
+                if leaving {
+                    break;
+                }
+        }
+        assert_eq!(actual_messages, [
+            (0, ElevatorVocabulary::DoorStateChanged(0, DoorState::Open)),
+            (0, ElevatorVocabulary::DoorStateChanged(0, DoorState::Closing)),
+            (0, ElevatorVocabulary::DoorClosed(0)),
+            (0, ElevatorVocabulary::MovingTo(3))
+        ]);
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    // Stands in for a console: whatever it hears from the group, it passes on to the test.
+    struct ObserverProbe;
+
+    #[async_trait]
+    impl Actor for ObserverProbe {
+        type Msg = GroupVocabulary;
+        type State = mpsc::UnboundedSender<(CarIndex, ElevatorVocabulary)>;
+        type Arguments = mpsc::UnboundedSender<(CarIndex, ElevatorVocabulary)>;
+
+        async fn pre_start(&self, _myself: ActorRef<Self::Msg>, tx: Self::Arguments) -> Result<Self::State, ActorProcessingErr> {
+            Ok(tx)
+        }
+
+        async fn handle(&self, _myself: ActorRef<Self::Msg>, message: Self::Msg, tx: &mut Self::State) -> Result<(), ActorProcessingErr> {
+            if let GroupVocabulary::FromCar(car, report) = message {
+                let _ = tx.send((car, report));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_two_observers_subscribe_then_both_hear_the_cars_until_one_stops() {
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-32")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>], None)
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        let mut observers = Vec::new();
+        for name in ["Test_observer-32a", "Test_observer-32b"] {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let (probe_ref, probe_handle) = Actor::spawn(Some(String::from(name)), ObserverProbe, tx)
+                .await
+                .expect("Failed to start observer");
+            group_ref.send_message(GroupVocabulary::Subscribe(Observer::new(probe_ref.clone(), GroupVocabulary::FromCar))).unwrap();
+            observers.push((probe_ref, probe_handle, rx));
+        }
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for (_, _, rx) in observers.iter_mut() {
+            let heard = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+            assert_eq!(heard, Some((0, ElevatorVocabulary::MoveToHomeFloor(0))));
+        }
+
+        // The group carries on without the observer that has gone.
+        let (gone_ref, gone_handle, _) = observers.remove(0);
+        gone_ref.stop(None);
+        gone_handle.await.unwrap();
+
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::CarCall { floor: 2 })).unwrap();
+        let (_, _, rx) = &mut observers[0];
+        let mut heard = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+                heard.push(msg_received);
+        }
+        assert!(heard.contains(&(0, ElevatorVocabulary::MovingTo(2))), "{:?}", heard);
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_a_car_goes_out_of_service_before_answering_its_hall_call_then_another_car_answers_it() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-36")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+        }
+
+        // Both cars are idle at the ground floor: the call goes to car A, which is then powered off.
+        group_ref.send_message(GroupVocabulary::HallCall { floor: 2, direction: CallDirection::Up }).unwrap();
+        let msg_received = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from group");
+        assert_eq!(msg_received, Some((0, ElevatorVocabulary::MovingTo(2))));
+
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::PowerOff)).unwrap();
+
+        let mut car_b_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_secs(3), rx.recv()).await {
+                // Car A lets its passengers out, and goes out of service once its door has closed.
+                if let (1, msg_received) = msg_received {
+                    car_b_messages.push(msg_received);
+                }
+        }
+
+        assert_eq!(car_b_messages.first(), Some(&ElevatorVocabulary::MovingTo(2)));
+        assert!(car_b_messages.contains(&ElevatorVocabulary::OpenTheDoor(2)));
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_a_car_goes_out_of_service_before_picking_up_a_kiosk_passenger_then_another_car_is_assigned() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-37")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+        }
+
+        // Both cars are idle at the ground floor: the passenger is told to take car A, which then
+        // reports that it is out of service, before it has got there.
+        group_ref.send_message(GroupVocabulary::DestinationCall { from_floor: 2, to_floor: 3 }).unwrap();
+        let msg_received = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from group");
+        assert_eq!(msg_received, Some((0, ElevatorVocabulary::DestinationAssigned { from_floor: 2, to_floor: 3 })));
+
+        group_ref.send_message(GroupVocabulary::FromCar(0, ElevatorVocabulary::ElevatorOutOfService)).unwrap();
+
+        let mut car_a_messages = Vec::new();
+        let mut car_b_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_secs(3), rx.recv()).await {
+                match msg_received {
+                    (0, msg_received) => car_a_messages.push(msg_received),
+                    (1, msg_received) => car_b_messages.push(msg_received),
+                    _ => {}
+                }
+        }
+
+        assert!(!car_a_messages.contains(&ElevatorVocabulary::CarCall { floor: 3 }));
+        assert_eq!(car_b_messages.first(), Some(&ElevatorVocabulary::DestinationAssigned { from_floor: 2, to_floor: 3 }));
+        assert!(car_b_messages.contains(&ElevatorVocabulary::OpenTheDoor(2)));
+        assert!(car_b_messages.contains(&ElevatorVocabulary::CarCall { floor: 3 }));
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_a_hall_call_is_made_right_after_power_on_then_a_car_answers_it() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-38")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        // The cars have not reported back yet, when the call is made.
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        group_ref.send_message(GroupVocabulary::HallCall { floor: 2, direction: CallDirection::Up }).unwrap();
+
+        let mut messages = Vec::new();
+        while let Ok(Some((_, msg_received))) =
+            tokio::time::timeout(Duration::from_secs(3), rx.recv()).await {
+                messages.push(msg_received);
+        }
+
+        assert!(messages.contains(&ElevatorVocabulary::MovingTo(2)));
+        assert!(messages.contains(&ElevatorVocabulary::OpenTheDoor(2)));
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_a_car_fails_then_it_is_taken_out_of_service_and_the_others_carry_on() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-33")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+        }
+
+        // Car A is told that its carriage has stopped at floor 1, on its way to floor 3.
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::CarCall { floor: 3 })).unwrap();
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::Stop(1))).unwrap();
+
+        let mut heard = Vec::new();
+        while let Ok(Some(msg_received)) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await {
+            heard.push(msg_received);
+        }
+        // The group may hear of the failure before the car's last report.
+        assert_eq!(heard.len(), 2, "{:?}", heard);
+        assert!(heard.contains(&(0, ElevatorVocabulary::MovingTo(3))), "{:?}", heard);
+        assert!(heard.contains(&(0, ElevatorVocabulary::ElevatorOutOfService)), "{:?}", heard);
+
+        // Whatever is meant for car A, is dropped; car B answers the hall calls.
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::CarCall { floor: 2 })).unwrap();
+        group_ref.send_message(GroupVocabulary::HallCall { floor: 1, direction: CallDirection::Up }).unwrap();
+        let msg_received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timeout before messages are received from group");
+        assert_eq!(msg_received, Some((1, ElevatorVocabulary::MovingTo(1))));
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+
+    // A part of the group, that is no part of any car: it fails at the first message.
+    struct FailingPart;
+
+    #[async_trait]
+    impl Actor for FailingPart {
+        type Msg = GroupVocabulary;
+        type State = ();
+        type Arguments = ();
+
+        async fn pre_start(&self, _myself: ActorRef<Self::Msg>, _: Self::Arguments) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(&self, _myself: ActorRef<Self::Msg>, _message: Self::Msg, _: &mut Self::State) -> Result<(), ActorProcessingErr> {
+            Err(From::from("Failing, as told"))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_a_part_of_no_car_fails_then_the_group_fails() {
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-34")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>], None)
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        let (part_ref, _) = Actor::spawn_linked(Some(String::from("Test_failing_part-34")), FailingPart, (), group_ref.get_cell())
+            .await
+            .expect("Failed to start failing part");
+        part_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), group_handle)
+            .await
+            .expect("The group is still there")
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_the_elevator_of_a_car_stops_then_its_hall_calls_go_to_another_car() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (group_ref, group_handle) = Actor::spawn(
+                Some(String::from("Test_group-35")),
+                GroupControllerActor,
+                (Building::with_floor_count(4).unwrap(), OperatingTimes { motion: BRISK_MOTION, ..OperatingTimes::default() }, SimulationClock::default(), vec![Box::new(LookStrategy) as Box<dyn DispatchStrategy>, Box::new(LookStrategy)], Some(tx.clone()))
+            )
+            .await
+            .expect("Failed to start group controller");
+
+        group_ref.send_message(GroupVocabulary::PowerOn).unwrap();
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .expect("timeout before messages are received from group");
+        }
+
+        // Both cars are idle at the ground floor: the call goes to car A.
+        group_ref.send_message(GroupVocabulary::HallCall { floor: 2, direction: CallDirection::Up }).unwrap();
+        let msg_received = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout before messages are received from group");
+        assert_eq!(msg_received, Some((0, ElevatorVocabulary::MovingTo(2))));
+
+        // The elevator of car A stops; its pulley and its door are still there.
+        ractor::registry::where_is(String::from("Test_group-35-Elevator-A"))
+            .expect("Car A has no elevator")
+            .stop(None);
+
+        let mut car_a_messages = Vec::new();
+        let mut car_b_messages = Vec::new();
+        while let Ok(Some(msg_received)) =
+            tokio::time::timeout(Duration::from_secs(3), rx.recv()).await {
+                match msg_received {
+                    (0, msg_received) => car_a_messages.push(msg_received),
+                    (1, msg_received) => car_b_messages.push(msg_received),
+                    _ => {}
+                }
+        }
+
+        assert_eq!(car_a_messages, vec![ElevatorVocabulary::ElevatorOutOfService]);
+        assert_eq!(car_b_messages.first(), Some(&ElevatorVocabulary::MovingTo(2)));
+        assert!(car_b_messages.contains(&ElevatorVocabulary::OpenTheDoor(2)));
+
+        // Nothing reaches car A anymore, nor a car the group does not have; the group carries on.
+        group_ref.send_message(GroupVocabulary::ToCar(0, ElevatorVocabulary::CarCall { floor: 3 })).unwrap();
+        group_ref.send_message(GroupVocabulary::ToCar(5, ElevatorVocabulary::CarCall { floor: 3 })).unwrap();
+        assert!(tokio::time::timeout(Duration::from_secs(1), rx.recv()).await.is_err());
+        assert_eq!(group_ref.get_status(), ActorStatus::Running);
+
+        group_ref.stop(None);
+        group_handle.await.unwrap();
+
+        drop(tx);
+    }
+}
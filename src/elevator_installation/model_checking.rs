@@ -0,0 +1,347 @@
+// Exhaustive model checking of an elevator: its state machine, its controller and the way
+// PassengerLiftActor handles what it hears. The pulley, the door and the elevator's own mailbox are
+// probes; the checker plays the passengers (in the car and on the landings), the operator, the
+// pulley and the door, trying every order in which what they do may reach the elevator. Every combination of FSM state, carriage data
+// (its calls, destination and emergency flag) and surroundings is visited, up to so many inputs
+// from outside, and from each of them the elevator is left to carry on by itself until nothing
+// more happens. Then:
+//
+//   * it is not stuck: idle and ready for service, or out of service (after a PowerOff);
+//   * after a PowerOff, it is out of service at its home floor (not the lowest one, here);
+//   * the pulley is never set going while the door is open;
+//   * every floor called, from the car or the landing, while in service, has been served (its
+//     door opened there).
+//
+// A state is not cloned, but rebuilt by handling again every message that led to it: whatever the
+// elevator hears, is handled alike every time.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ractor::{Actor, ActorCell, ActorProcessingErr, ActorRef, Message};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::building::Building;
+use crate::conversation::vocabulary::{CallDirection, CarLoad, DoorVocabulary, ElevatorVocabulary, PulleyVocabulary};
+use crate::elevator_installation::carriage_machinery::CarriageData;
+use crate::elevator_installation::dispatch_strategy::LookStrategy;
+use crate::elevator_installation::elevator_operations::{ElevatorController, ElevatorFSMStates, ElevatorStateMachine};
+use crate::elevator_installation::elevator_service::PassengerLiftActor;
+
+const FLOORS: u8 = 3;
+const BUILDING: &str = r#"{ "floor_count": 3, "home": "1" }"#;
+
+// No more inputs than these, from outside, on the way to any state.
+const INPUTS_FROM_OUTSIDE: usize = 4;
+
+// Left to itself, the elevator is to settle in these many steps at most.
+const STEPS_TO_SETTLE: usize = 100;
+
+const OVERLOAD: CarLoad = CarLoad { persons: 20, kg: 1500 };
+const LIGHT_LOAD: CarLoad = CarLoad { persons: 1, kg: 80 };
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Event {
+    // From outside.
+    PowerOn,
+    PowerOff,
+    CarCall(u8),
+    HallCall(u8, CallDirection),
+    Overload,
+    // The elevator, its pulley, its door and its passengers carrying on.
+    FromMailbox,
+    PulleyArrives,
+    PulleyDiverts,
+    DoorCloses,
+    LoadReduced
+}
+
+impl Event {
+    fn is_from_outside(&self) -> bool {
+        matches!(self, Event::PowerOn | Event::PowerOff | Event::CarCall(_) | Event::HallCall(_, _) | Event::Overload)
+    }
+}
+
+// Passes on whatever it is told, for the checker to find.
+struct Probe<TMsg>(PhantomData<fn() -> TMsg>);
+
+#[async_trait]
+impl<TMsg: Message> Actor for Probe<TMsg> {
+    type Msg = TMsg;
+    type State = UnboundedSender<TMsg>;
+    type Arguments = UnboundedSender<TMsg>;
+
+    async fn pre_start(&self, _myself: ActorRef<Self::Msg>, tx: Self::Arguments) -> Result<Self::State, ActorProcessingErr> {
+        Ok(tx)
+    }
+
+    async fn handle(&self, _myself: ActorRef<Self::Msg>, message: Self::Msg, tx: &mut Self::State) -> Result<(), ActorProcessingErr> {
+        let _ = tx.send(message);
+        Ok(())
+    }
+}
+
+async fn probe<TMsg: Message>() -> (ActorRef<TMsg>, UnboundedReceiver<TMsg>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let (probe, _) = Actor::spawn(None, Probe(PhantomData), tx).await.expect("Failed to create a probe");
+    (probe, rx)
+}
+
+// What the elevator has set going around it, and what it owes its passengers.
+#[derive(Debug, Default)]
+struct Surroundings {
+    mailbox: VecDeque<ElevatorVocabulary>, // what the elevator has told itself
+    pulley_to: Option<u8>,
+    divert_asked: Option<u8>,
+    door_open_at: Option<u8>,
+    door_held: bool,
+    overloaded: bool,
+    floors_called: BTreeSet<u8>,
+    power_off_asked: bool,
+    out_of_service: bool
+}
+
+struct Model {
+    elevator: ElevatorController<ElevatorStateMachine>,
+    myself: ActorRef<ElevatorVocabulary>,
+    probes: [ActorCell; 3],
+    mailbox: UnboundedReceiver<ElevatorVocabulary>,
+    pulley: UnboundedReceiver<PulleyVocabulary>,
+    door: UnboundedReceiver<DoorVocabulary>,
+    reports: UnboundedReceiver<ElevatorVocabulary>,
+    around: Surroundings,
+    inputs_from_outside: usize
+}
+
+impl Model {
+    async fn new() -> Self {
+        let (myself, mailbox) = probe().await;
+        let (pulley_ref, pulley) = probe().await;
+        let (door_ref, door) = probe().await;
+        let (tx, reports) = mpsc::unbounded_channel();
+        let building = Building::from_json(BUILDING).expect("Failed to describe the building");
+        let carriage_data = CarriageData::new(&building, Box::new(LookStrategy));
+        Model {
+            probes: [myself.get_cell(), pulley_ref.get_cell(), door_ref.get_cell()],
+            elevator: ElevatorController::new(carriage_data, Some(tx), pulley_ref, Some(door_ref)),
+            myself,
+            mailbox,
+            pulley,
+            door,
+            reports,
+            around: Surroundings::default(),
+            inputs_from_outside: 0
+        }
+    }
+
+    async fn replayed(path: &[Event]) -> Result<Self, String> {
+        let mut model = Model::new().await;
+        for event in path {
+            model.apply(*event).await?;
+        }
+        Ok(model)
+    }
+
+    fn key(&mut self) -> String {
+        format!("{:?} {:?} {:?} {}", self.elevator.current_state(), self.elevator.carriage_data, self.around, self.inputs_from_outside)
+    }
+
+    fn enabled(&mut self) -> Vec<Event> {
+        if self.around.out_of_service {
+            return Vec::new();
+        }
+        let mut events = self.carrying_on();
+        if self.inputs_from_outside < INPUTS_FROM_OUTSIDE {
+            events.extend([Event::PowerOn, Event::PowerOff]);
+            events.extend((0..FLOORS).map(Event::CarCall));
+            events.extend((0..FLOORS - 1).map(|floor| Event::HallCall(floor, CallDirection::Up)));
+            events.extend((1..FLOORS).map(|floor| Event::HallCall(floor, CallDirection::Down)));
+            if self.around.door_open_at.is_some() && !self.around.overloaded {
+                events.push(Event::Overload);
+            }
+        }
+        events
+    }
+
+    // What happens with nobody pressing anything; the first of them, happens first when the
+    // elevator is left to itself.
+    fn carrying_on(&self) -> Vec<Event> {
+        let around = &self.around;
+        let mut events = Vec::new();
+        if !around.mailbox.is_empty() { events.push(Event::FromMailbox); }
+        if around.overloaded { events.push(Event::LoadReduced); }
+        if around.door_open_at.is_some() && !around.door_held { events.push(Event::DoorCloses); }
+        if around.pulley_to.is_some() { events.push(Event::PulleyArrives); }
+        if around.divert_asked.is_some() { events.push(Event::PulleyDiverts); }
+        events
+    }
+
+    async fn apply(&mut self, event: Event) -> Result<(), String> {
+        let state = self.elevator.current_state();
+        let message = match event {
+            Event::PowerOn => ElevatorVocabulary::PowerOn,
+            Event::PowerOff => {
+                self.around.power_off_asked |= state != ElevatorFSMStates::PoweredOff;
+                ElevatorVocabulary::PowerOff
+            },
+            Event::CarCall(floor) => {
+                // Pressed where the door stands open, the floor is served already.
+                let served_here = self.around.door_open_at == Some(floor);
+                if state != ElevatorFSMStates::PoweredOff && !self.around.power_off_asked && !served_here {
+                    self.around.floors_called.insert(floor);
+                }
+                ElevatorVocabulary::CarCall { floor }
+            },
+            Event::HallCall(floor, direction) => {
+                let served_here = self.around.door_open_at == Some(floor);
+                if state != ElevatorFSMStates::PoweredOff && !self.around.power_off_asked && !served_here {
+                    self.around.floors_called.insert(floor);
+                }
+                ElevatorVocabulary::HallCall { floor, direction }
+            },
+            Event::Overload => {
+                self.around.overloaded = true;
+                ElevatorVocabulary::LoadWeighed(OVERLOAD)
+            },
+            Event::LoadReduced => {
+                self.around.overloaded = false;
+                ElevatorVocabulary::LoadWeighed(LIGHT_LOAD)
+            },
+            Event::FromMailbox => self.around.mailbox.pop_front().unwrap(),
+            Event::PulleyArrives => {
+                self.around.divert_asked = None;
+                ElevatorVocabulary::Stop(self.around.pulley_to.take().unwrap())
+            },
+            Event::PulleyDiverts => {
+                let floor = self.around.divert_asked.take().unwrap();
+                self.around.pulley_to = Some(floor);
+                ElevatorVocabulary::DivertedTo(floor)
+            },
+            Event::DoorCloses => ElevatorVocabulary::DoorClosed(self.around.door_open_at.take().unwrap())
+        };
+        if event.is_from_outside() {
+            self.inputs_from_outside += 1;
+        }
+
+        PassengerLiftActor
+            .handle(self.myself.clone(), message, &mut self.elevator)
+            .await
+            .map_err(|e| format!("The elevator failed: {}", e))?;
+
+        // The probes pass on what they have been told, as soon as nothing else is left to do.
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        self.hear_what_was_told()
+    }
+
+    fn hear_what_was_told(&mut self) -> Result<(), String> {
+        while let Ok(message) = self.mailbox.try_recv() {
+            self.around.mailbox.push_back(message);
+        }
+        while let Ok(message) = self.door.try_recv() {
+            match message {
+                DoorVocabulary::Open(floor) => self.around.door_open_at = Some(floor),
+                DoorVocabulary::Hold => self.around.door_held = true,
+                DoorVocabulary::Release => self.around.door_held = false,
+                _ => {}
+            }
+        }
+        while let Ok(message) = self.pulley.try_recv() {
+            match message {
+                PulleyVocabulary::MoveToFloor(floor) => {
+                    if let Some(open_at) = self.around.door_open_at {
+                        return Err(format!("The pulley is told to go to floor ({}), with the door open at floor ({})", floor, open_at));
+                    }
+                    self.around.pulley_to = Some(floor);
+                },
+                PulleyVocabulary::DivertToFloor(floor) if self.around.pulley_to.is_some() => self.around.divert_asked = Some(floor),
+                _ => {}
+            }
+        }
+        while let Ok(report) = self.reports.try_recv() {
+            match report {
+                ElevatorVocabulary::OpenTheDoor(floor) => { self.around.floors_called.remove(&floor); },
+                ElevatorVocabulary::ElevatorOutOfService => {
+                    self.around.out_of_service = true;
+                    let at_floor = self.elevator.carriage_data.where_is();
+                    let home_floor = self.elevator.home_floor();
+                    if at_floor != home_floor {
+                        return Err(format!("Out of service at floor ({}), not at its home floor ({})", at_floor, home_floor));
+                    }
+                },
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Left to itself, the elevator must come to rest: idle, with every call served, or out of
+    // service after a PowerOff.
+    async fn settles(&mut self) -> Result<(), String> {
+        for _ in 0..STEPS_TO_SETTLE {
+            match self.carrying_on().first() {
+                Some(event) => self.apply(*event).await?,
+                None => return self.at_rest()
+            }
+        }
+        Err(format!("Not at rest after ({}) steps: {:?}", STEPS_TO_SETTLE, self.around))
+    }
+
+    fn at_rest(&mut self) -> Result<(), String> {
+        let state = self.elevator.current_state();
+        if self.around.out_of_service {
+            return Ok(());
+        }
+        if self.around.power_off_asked {
+            return Err(format!("Powered off, but stuck in state ({:?}) at floor ({})", state, self.elevator.carriage_data.where_is()));
+        }
+        match state {
+            // Never switched on: where it was installed.
+            ElevatorFSMStates::PoweredOff if self.elevator.carriage_data.where_is() == 0 => Ok(()),
+            ElevatorFSMStates::ReadyForService if self.around.floors_called.is_empty() => Ok(()),
+            ElevatorFSMStates::ReadyForService => Err(format!("Idle, but never served floors {:?}", self.around.floors_called)),
+            _ => Err(format!("Stuck in state ({:?}): {:?}", state, self.around))
+        }
+    }
+}
+
+// A state is rebuilt many times over; the probes of the ones done with, are not left behind.
+impl Drop for Model {
+    fn drop(&mut self) {
+        for probe in &self.probes {
+            probe.stop(None);
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_reachable_state_of_the_elevator_settles_safely() {
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut to_explore: VecDeque<Vec<Event>> = VecDeque::from([Vec::new()]);
+
+    let mut initial = Model::new().await;
+    visited.insert(initial.key());
+
+    while let Some(path) = to_explore.pop_front() {
+        let events = Model::replayed(&path).await.unwrap().enabled();
+        for event in events {
+            let mut next_path = path.clone();
+            next_path.push(event);
+
+            let mut model = match Model::replayed(&next_path).await {
+                Ok(model) => model,
+                Err(violation) => panic!("{}, after {:?}", violation, next_path)
+            };
+            if !visited.insert(model.key()) {
+                continue;
+            }
+            if let Err(violation) = model.settles().await {
+                panic!("{}, after {:?}", violation, next_path);
+            }
+            to_explore.push_back(next_path);
+        }
+    }
+
+    assert!(visited.len() > 100, "States visited: ({})", visited.len());
+}
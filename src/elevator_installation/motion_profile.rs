@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// Two motions are taken for one and the same, when they differ by less than this (in metres, m/s
+// and m/s²).
+const SAME_MOTION: f64 = 1e-6;
+
+/// How the drive of a car moves it, in metres and seconds: never faster than its rated speed,
+/// speeding up and slowing down no harder than it may, and changing how hard it does so no faster
+/// than the jerk limit (lest the passengers be thrown about).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MotionProfile {
+    pub rated_speed: f64,  // m/s
+    pub acceleration: f64, // m/s²
+    pub deceleration: f64, // m/s²
+    pub jerk: f64,         // m/s³
+    pub floor_height: f64  // m, from one floor to the next
+}
+
+impl Default for MotionProfile {
+    fn default() -> Self {
+        MotionProfile {
+            rated_speed: 1.6,
+            acceleration: 1.0,
+            deceleration: 1.0,
+            jerk: 1.5,
+            floor_height: 3.5
+        }
+    }
+}
+
+impl MotionProfile {
+    // Travelling past a floor, at the rated speed.
+    pub fn time_per_floor(&self) -> Duration {
+        Duration::from_secs_f64(self.floor_height / self.rated_speed)
+    }
+
+    // What a stop costs on top of the door dwell: the time lost speeding up and slowing down,
+    // against travelling at the rated speed all the way.
+    pub fn time_lost_per_stop(&self) -> Duration {
+        let far_away = 100.0 * self.floor_height;
+        SCurveTrip::new(0.0, far_away, self)
+            .duration()
+            .saturating_sub(Duration::from_secs_f64(far_away / self.rated_speed))
+    }
+}
+
+/// Where the carriage is, how fast it goes and how hard it speeds up: positive upwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MotionState {
+    pub position: f64,
+    pub velocity: f64,
+    pub acceleration: f64
+}
+
+impl MotionState {
+    fn is_same_as(&self, other: &MotionState) -> bool {
+        (self.position - other.position).abs() < SAME_MOTION
+            && (self.velocity - other.velocity).abs() < SAME_MOTION
+            && (self.acceleration - other.acceleration).abs() < SAME_MOTION
+    }
+}
+
+/// A trip from standing at one position to standing at another. The jerk is only ever switched
+/// between +j, 0 and -j: the acceleration ramps up, holds and ramps down, the carriage cruises,
+/// then the same again for braking (the S-curve). A trip too short to reach the rated speed, peaks
+/// at the highest speed that leaves it room to brake.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SCurveTrip {
+    from: f64,
+    to: f64,
+    segments: Vec<(f64 /* seconds */, f64 /* jerk */)>,
+    peak_speed: f64
+}
+
+impl SCurveTrip {
+    pub fn new(from: f64, to: f64, profile: &MotionProfile) -> Self {
+        let distance = (to - from).abs();
+        let speeding_up = |speed: f64| ramp(speed, profile.acceleration, profile.jerk);
+        let braking = |speed: f64| ramp(speed, profile.deceleration, profile.jerk);
+        // Both the speeding up and the braking cover, on average, half the speed reached.
+        let distance_to_reach = |speed: f64| (seconds(&speeding_up(speed)) + seconds(&braking(speed))) * speed / 2.0;
+
+        let peak_speed = if distance_to_reach(profile.rated_speed) <= distance {
+            profile.rated_speed
+        } else {
+            let (mut slow_enough, mut too_fast) = (0.0, profile.rated_speed);
+            for _ in 0..64 {
+                let speed = (slow_enough + too_fast) / 2.0;
+                if distance_to_reach(speed) <= distance { slow_enough = speed } else { too_fast = speed }
+            }
+            slow_enough
+        };
+        let cruising = if peak_speed > 0.0 { (distance - distance_to_reach(peak_speed)).max(0.0) / peak_speed } else { 0.0 };
+
+        let mut segments = speeding_up(peak_speed);
+        segments.push((cruising, 0.0));
+        segments.extend(braking(peak_speed).into_iter().map(|(duration, jerk)| (duration, -jerk)));
+
+        SCurveTrip { from, to, segments, peak_speed }
+    }
+
+    pub fn from(&self) -> f64 {
+        self.from
+    }
+
+    pub fn peak_speed(&self) -> f64 {
+        self.peak_speed
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(seconds(&self.segments))
+    }
+
+    /// The motion of the carriage, so long after the trip started; standing at the end, once it is over.
+    pub fn state_at(&self, elapsed: Duration) -> MotionState {
+        let direction = if self.to < self.from { -1.0 } else { 1.0 };
+        let mut left = elapsed.as_secs_f64();
+        let (mut x, mut v, mut a) = (0.0, 0.0, 0.0);
+
+        for (duration, jerk) in self.segments.iter() {
+            let t = left.min(*duration);
+            x += v * t + a * t * t / 2.0 + jerk * t * t * t / 6.0;
+            v += a * t + jerk * t * t / 2.0;
+            a += jerk * t;
+            left -= t;
+            if left <= 0.0 {
+                return MotionState { position: self.from + direction * x, velocity: direction * v, acceleration: direction * a };
+            }
+        }
+
+        MotionState { position: self.to, velocity: 0.0, acceleration: 0.0 }
+    }
+
+    // Whether the carriage, so long into this trip, may still follow the other one instead: both
+    // start at the same place, and have gone alike so far.
+    pub fn may_turn_into(&self, other: &SCurveTrip, elapsed: Duration) -> bool {
+        self.from == other.from && self.state_at(elapsed).is_same_as(&other.state_at(elapsed))
+    }
+}
+
+// Speeding up from standing to `speed`, no harder than `rate`: the segments of constant jerk, and
+// how long each lasts. Without room to hold the acceleration, it ramps up and straight down again.
+fn ramp(speed: f64, rate: f64, jerk: f64) -> Vec<(f64, f64)> {
+    if speed >= rate * rate / jerk {
+        vec![(rate / jerk, jerk), (speed / rate - rate / jerk, 0.0), (rate / jerk, -jerk)]
+    } else {
+        let half = (speed / jerk).sqrt();
+        vec![(half, jerk), (half, -jerk)]
+    }
+}
+
+fn seconds(segments: &[(f64, f64)]) -> f64 {
+    segments.iter().map(|(duration, _)| duration).sum()
+}
+
+// Almost a carriage moving at a constant speed (of one unit every 100 ms, with five units to a
+// floor), as the tests of the actors expect it.
+#[doc(hidden)]
+pub const BRISK_MOTION: MotionProfile = MotionProfile {
+    rated_speed: 10.0,
+    acceleration: 100.0,
+    deceleration: 100.0,
+    jerk: 10_000.0,
+    floor_height: 5.0
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    fn sampled(trip: &SCurveTrip) -> Vec<MotionState> {
+        (0..=(trip.duration().as_millis() / STEP.as_millis() + 1) as u32)
+            .map(|step| trip.state_at(STEP * step))
+            .collect()
+    }
+
+    #[test]
+    fn when_the_trip_is_long_then_it_cruises_at_the_rated_speed_and_takes_as_long_as_the_s_curve() {
+        let profile = MotionProfile::default();
+        // Speeding up to 1.6 m/s: 1/1.5 s ramping up, 0.6 s holding 1 m/s², 1/1.5 s ramping down.
+        let ramp_time = 1.6 / 1.0 + 1.0 / 1.5;
+        let ramp_distance = 1.6 * ramp_time / 2.0;
+        let trip = SCurveTrip::new(0.0, 10.0 * 3.5, &profile);
+
+        let expected = 2.0 * ramp_time + (35.0 - 2.0 * ramp_distance) / 1.6;
+        assert!((trip.duration().as_secs_f64() - expected).abs() < 1e-9, "{:?}", trip.duration());
+        assert_eq!(trip.peak_speed(), 1.6);
+
+        let samples = sampled(&trip);
+        assert!(samples.iter().all(|state| state.velocity <= 1.6 + 1e-9 && state.acceleration.abs() <= 1.0 + 1e-9));
+        assert!(samples.windows(2).all(|pair| pair[0].position <= pair[1].position));
+        assert!(samples.windows(2).all(|pair| (pair[1].acceleration - pair[0].acceleration).abs() <= 1.5 * STEP.as_secs_f64() + 1e-9));
+        assert_eq!(trip.state_at(trip.duration() + STEP), MotionState { position: 35.0, velocity: 0.0, acceleration: 0.0 });
+    }
+
+    #[test]
+    fn when_the_trip_is_one_floor_then_it_never_reaches_full_speed() {
+        let profile = MotionProfile::default();
+        let trip = SCurveTrip::new(3.5 * 4.0, 3.5 * 3.0, &profile);
+
+        assert!(trip.peak_speed() < profile.rated_speed);
+        let samples = sampled(&trip);
+        let fastest = samples.iter().map(|state| -state.velocity).fold(0.0, f64::max);
+        assert!((fastest - trip.peak_speed()).abs() < 1e-3, "{} {}", fastest, trip.peak_speed());
+        assert!(samples.iter().all(|state| state.velocity <= 0.0));
+        assert!((samples.last().unwrap().position - 10.5).abs() < 1e-9);
+
+        // Without a hold at the rated speed, the trip takes longer than the distance at that speed.
+        assert!(trip.duration().as_secs_f64() > 3.5 / 1.6);
+        assert!(profile.time_lost_per_stop() > Duration::ZERO);
+    }
+
+    #[test]
+    fn when_a_nearer_floor_is_called_then_only_a_trip_gone_alike_so_far_may_turn_into_it() {
+        let profile = MotionProfile::default();
+        let to_the_top = SCurveTrip::new(0.0, 35.0, &profile);
+        let to_floor_2 = SCurveTrip::new(0.0, 7.0, &profile);
+
+        assert!(to_the_top.may_turn_into(&to_floor_2, Duration::from_millis(500)));
+        assert!(!to_the_top.may_turn_into(&to_floor_2, Duration::from_secs(5)));
+        assert!(!to_the_top.may_turn_into(&SCurveTrip::new(3.5, 7.0, &profile), Duration::ZERO));
+    }
+}
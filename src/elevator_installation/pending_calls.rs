@@ -0,0 +1,178 @@
+use std::collections::BTreeSet;
+
+use crate::conversation::vocabulary::CallDirection;
+use crate::elevator_installation::travel_scheduler::TravelDirection;
+
+/// Calls waiting to be answered. Car calls (pressed inside the carriage) and hall calls
+/// (pressed on a landing, for going up or down) are kept apart, because a hall call is answered
+/// only by a carriage leaving that floor in the direction asked for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PendingCalls {
+    car_calls: BTreeSet<u8>,
+    hall_calls_up: BTreeSet<u8>,
+    hall_calls_down: BTreeSet<u8>,
+    in_order_of_arrival: Vec<u8>
+}
+
+impl PendingCalls {
+    pub fn new() -> Self {
+        PendingCalls::default()
+    }
+
+    pub fn add_car_call(&mut self, floor: u8) {
+        self.car_calls.insert(floor);
+        self.note_arrival(floor);
+    }
+
+    pub fn add_hall_call(&mut self, floor: u8, direction: CallDirection) {
+        match direction {
+            CallDirection::Up   => self.hall_calls_up.insert(floor),
+            CallDirection::Down => self.hall_calls_down.insert(floor)
+        };
+        self.note_arrival(floor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_order_of_arrival.is_empty()
+    }
+
+    /// Floors having at least one call, in the order in which they have been asked for.
+    pub fn floors(&self) -> &[u8] {
+        &self.in_order_of_arrival
+    }
+
+    pub fn has_car_call(&self, floor: u8) -> bool {
+        self.car_calls.contains(&floor)
+    }
+
+    pub fn has_hall_call(&self, floor: u8, direction: CallDirection) -> bool {
+        match direction {
+            CallDirection::Up   => self.hall_calls_up.contains(&floor),
+            CallDirection::Down => self.hall_calls_down.contains(&floor)
+        }
+    }
+
+    /// Should a carriage travelling in `direction` stop at `floor`, on its way?
+    pub fn wants_stop(&self, floor: u8, direction: TravelDirection) -> bool {
+        self.has_car_call(floor) || match direction {
+            TravelDirection::Up   => self.has_hall_call(floor, CallDirection::Up),
+            TravelDirection::Down => self.has_hall_call(floor, CallDirection::Down),
+            TravelDirection::Idle => self.in_order_of_arrival.contains(&floor)
+        }
+    }
+
+    /// The same calls, minus those at `floor`.
+    pub fn without_floor(&self, floor: u8) -> PendingCalls {
+        let mut calls = self.clone();
+        calls.car_calls.remove(&floor);
+        calls.hall_calls_up.remove(&floor);
+        calls.hall_calls_down.remove(&floor);
+        calls.in_order_of_arrival.retain(|f| *f != floor);
+        calls
+    }
+
+    /// The same calls, minus the hall calls: those a full carriage passes by.
+    pub fn car_calls_only(&self) -> PendingCalls {
+        let mut calls = PendingCalls::new();
+        self.in_order_of_arrival
+            .iter()
+            .filter(|floor| self.has_car_call(**floor))
+            .for_each(|floor| calls.add_car_call(*floor));
+        calls
+    }
+
+    pub fn serve_car_call(&mut self, floor: u8) {
+        self.car_calls.remove(&floor);
+        if !self.has_hall_call(floor, CallDirection::Up) && !self.has_hall_call(floor, CallDirection::Down) {
+            self.in_order_of_arrival.retain(|f| *f != floor);
+        }
+    }
+
+    /// The carriage stands at `floor` with its door open, and will leave in `direction`. The car
+    /// call and the hall call for that direction are answered (an idle carriage answers all).
+    /// Returns the hall calls that have been answered.
+    pub fn serve(&mut self, floor: u8, direction: TravelDirection) -> Vec<CallDirection> {
+        let mut answered = Vec::new();
+
+        self.car_calls.remove(&floor);
+        if direction != TravelDirection::Down && self.hall_calls_up.remove(&floor) {
+            answered.push(CallDirection::Up);
+        }
+        if direction != TravelDirection::Up && self.hall_calls_down.remove(&floor) {
+            answered.push(CallDirection::Down);
+        }
+
+        if !self.has_car_call(floor)
+            && !self.has_hall_call(floor, CallDirection::Up)
+            && !self.has_hall_call(floor, CallDirection::Down) {
+            self.in_order_of_arrival.retain(|f| *f != floor);
+        }
+
+        answered
+    }
+
+    fn note_arrival(&mut self, floor: u8) {
+        if !self.in_order_of_arrival.contains(&floor) {
+            self.in_order_of_arrival.push(floor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_carriage_leaves_upwards_then_only_up_hall_call_is_answered() {
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(4, CallDirection::Down);
+        calls.add_hall_call(4, CallDirection::Up);
+        calls.add_car_call(4);
+
+        assert_eq!(calls.serve(4, TravelDirection::Up), vec![CallDirection::Up]);
+        assert!(!calls.has_car_call(4));
+        assert!(calls.has_hall_call(4, CallDirection::Down));
+        assert_eq!(calls.floors(), &[4]);
+
+        assert_eq!(calls.serve(4, TravelDirection::Idle), vec![CallDirection::Down]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn when_hall_call_is_for_the_opposite_direction_then_carriage_does_not_stop_on_its_way() {
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(3, CallDirection::Down);
+        calls.add_hall_call(5, CallDirection::Up);
+
+        assert!(!calls.wants_stop(3, TravelDirection::Up));
+        assert!(calls.wants_stop(5, TravelDirection::Up));
+        assert!(calls.wants_stop(3, TravelDirection::Down));
+    }
+
+    #[test]
+    fn when_floors_are_asked_for_then_order_of_arrival_is_kept_without_duplicates() {
+        let calls = &mut PendingCalls::new();
+        calls.add_car_call(6);
+        calls.add_hall_call(2, CallDirection::Up);
+        calls.add_car_call(2);
+        calls.add_hall_call(6, CallDirection::Down);
+
+        assert_eq!(calls.floors(), &[6, 2]);
+        assert_eq!(calls.without_floor(6).floors(), &[2]);
+    }
+
+    #[test]
+    fn when_only_car_calls_are_kept_then_hall_calls_remain_pending() {
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(3, CallDirection::Up);
+        calls.add_car_call(5);
+        calls.add_car_call(3);
+
+        assert_eq!(calls.car_calls_only().floors(), &[3, 5]);
+        assert!(!calls.car_calls_only().has_hall_call(3, CallDirection::Up));
+
+        calls.serve_car_call(3);
+        assert!(calls.has_hall_call(3, CallDirection::Up));
+        assert_eq!(calls.floors(), &[3, 5]);
+    }
+}
@@ -1,10 +1,14 @@
 use async_trait::async_trait;
-use ractor::time::send_after;
 use ractor::concurrency::Duration;
 use ractor::{Actor, ActorProcessingErr, ActorRef};
-use tracing::info;
+use std::fmt;
+use tracing::{debug, info};
 
-use crate::conversation::vocabulary::{ElevatorVocabulary, PulleyVocabulary};
+use crate::conversation::vocabulary::{ElevatorVocabulary, Millimetres, PulleyVocabulary};
+use crate::elevator_installation::fault_policy::FaultPolicy;
+use crate::elevator_installation::motion_profile::{MotionProfile, MotionState, SCurveTrip};
+use crate::elevator_installation::shaft::FloorLevels;
+use crate::simulation_clock::SimulationClock;
 
 #[derive(Debug)]
 struct FloorData {
@@ -23,29 +27,82 @@ impl FloorData {
 }
 
 
+// The pulley reports where the carriage is, every so often (unless told otherwise).
+pub const PULLEY_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What can go wrong with a pulley: its elevator may not be there to hear it, and it may be told
+/// to go where the shaft does not reach.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PulleyError {
+    NotPoweredOn,          // no elevator has hooked it yet
+    ElevatorGone,          // nobody is left to tell where the carriage is
+    FloorOutOfRange(u8)    // the shaft has no such floor
+}
+
+impl PulleyError {
+    pub fn policy(&self) -> FaultPolicy {
+        match self {
+            PulleyError::NotPoweredOn | PulleyError::FloorOutOfRange(_) => FaultPolicy::LogAndContinue,
+            // A carriage that moves without its elevator knowing, must not move at all.
+            PulleyError::ElevatorGone => FaultPolicy::Escalate
+        }
+    }
+}
+
+impl fmt::Display for PulleyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PulleyError::NotPoweredOn => write!(f, "no elevator has powered the pulley on"),
+            PulleyError::ElevatorGone => write!(f, "the elevator is not there anymore"),
+            PulleyError::FloorOutOfRange(floor) => write!(f, "floor ({}) is not in the shaft", floor)
+        }
+    }
+}
+
+impl std::error::Error for PulleyError {}
+
 #[derive(Debug)]
 pub struct PulleyData {
-    dest_posn: (f64,f64),
-    current_posn: (f64,f64),
-    displacement: f64,
+    dest_level: Millimetres,
+    current_level: Millimetres,
+    motion: MotionState,        // in metres, up the shaft from the floor of the pit
+    trip: Option<SCurveTrip>,   // while moving
+    trip_time: Duration,        // since the trip started
+    profile: MotionProfile,
+    step_interval: Duration,
+    clock: SimulationClock,
     floor_data: FloorData,
-    floors_to_position_map: Vec<(f64 /* row as x-coord */, f64 /* col as y-coord */)>,
+    floor_levels: FloorLevels,
     elevator_controller_actor: Option<ActorRef<ElevatorVocabulary>>
 
 }
 
+// The motion of the carriage is worked out in metres; where it is, is told in millimetres.
+fn in_metres(level: Millimetres) -> f64 {
+    level as f64 / 1000.0
+}
+
+fn in_millimetres(position: f64) -> Millimetres {
+    (position * 1000.0).round().max(0.0) as Millimetres
+}
+
 impl PulleyData {
-    pub fn new(floors_to_position_map: Vec<(f64, f64)>) -> Self {
+    pub fn new(floor_levels: FloorLevels) -> Self {
 
         let currently_at = 0;
         let going_to     = 0; // At the start, pulley doesn't need to know
-        let start_posn = floors_to_position_map[0];
+        let start_level = floor_levels.level(currently_at);
         PulleyData { 
-            dest_posn: start_posn, 
-            current_posn: start_posn, 
-            displacement: 0.0,
+            dest_level: start_level, 
+            current_level: start_level, 
+            motion: MotionState { position: in_metres(start_level), ..MotionState::default() },
+            trip: None,
+            trip_time: Duration::ZERO,
+            profile: MotionProfile::default(),
+            step_interval: PULLEY_STEP_INTERVAL,
+            clock: SimulationClock::default(),
             floor_data: FloorData { current: currently_at, destination: going_to },
-            floors_to_position_map,
+            floor_levels,
             elevator_controller_actor: None
         }
     }
@@ -60,57 +117,122 @@ impl PulleyData {
         if self.floor_data.current == dest_f { self }
         else {
             self.floor_data.destination = dest_f; 
-            self.current_posn = self.floors_to_position_map[self.floor_data.current as usize];
-            self.dest_posn    = self.floors_to_position_map[self.floor_data.destination as usize];
-            if self.floor_data.going_up() {
-                self.displacement = 1.0; // Because the pulley pulls the carriage upwards
-            }
-            else {
-                self.displacement = -1.0; // Because the pulley releases the carriage downwards.
-            };
+            self.current_level = self.floor_levels.level(self.floor_data.current);
+            self.dest_level    = self.floor_levels.level(self.floor_data.destination);
+            self.trip = Some(SCurveTrip::new(in_metres(self.current_level), in_metres(self.dest_level), &self.profile));
+            self.trip_time = Duration::ZERO;
 
             self
         }
     }
 
+    // One step further along the trip. Once it is over, the carriage stands exactly at the floor.
     pub fn on_pulley_moving(&mut self) -> &mut Self {
-        self.current_posn.1 += self.displacement;
+        if let Some(trip) = &self.trip {
+            self.trip_time += self.step_interval;
+            self.motion = trip.state_at(self.trip_time);
+            self.current_level = if self.trip_time >= trip.duration() { self.dest_level } else { in_millimetres(self.motion.position) };
+        }
         self
     }
 
+    pub fn motion(&self) -> MotionState {
+        self.motion
+    }
+
     pub fn has_reached_dest(&self) -> bool {
-        self.current_posn.1 == self.dest_posn.1
+        self.trip.as_ref().is_none_or(|trip| self.trip_time >= trip.duration())
+    }
+
+    pub fn is_moving(&self) -> bool {
+        !self.has_reached_dest()
+    }
+
+    // A floor can be stopped at mid-trip, only if it lies ahead of the carriage, before the
+    // current destination, and the carriage can still brake for it as it would on a trip there.
+    pub fn can_stop_at(&self, floor: u8) -> bool {
+        let Some(trip) = self.trip.as_ref().filter(|_| self.is_moving()) else {
+            return false;
+        };
+        if !self.floor_levels.contains(floor) {
+            return false;
+        }
+
+        let floor_level = self.floor_levels.level(floor);
+        let going_up = self.floor_data.going_up();
+        let before_dest = if going_up { floor_level < self.dest_level } else { floor_level > self.dest_level };
+        let ahead       = if going_up { floor_level > self.current_level } else { floor_level < self.current_level };
+
+        before_dest && ahead && trip.may_turn_into(&SCurveTrip::new(trip.from(), in_metres(floor_level), &self.profile), self.trip_time)
+    }
+
+    pub fn divert_to(&mut self, floor: u8) -> &mut Self {
+        self.floor_data.destination = floor;
+        self.dest_level = self.floor_levels.level(floor);
+        self.trip = self.trip
+            .as_ref()
+            .map(|trip| SCurveTrip::new(trip.from(), in_metres(self.dest_level), &self.profile));
+        self
     }
 
     pub fn adjust_floor_data(&mut self) -> &mut Self {
         let currently_at = self.floor_data.destination;
         let adjusted_floor_data = FloorData { current: currently_at, destination: self.floor_data.destination };
         self.floor_data = adjusted_floor_data;
+        self.trip = None;
+        self.motion = MotionState { position: in_metres(self.floor_levels.level(currently_at)), ..MotionState::default() };
         self
     }
 
-    pub fn currently_at(&self) -> (u8,(f64,f64)) {
+    pub fn currently_at(&self) -> (u8, Millimetres) {
         let current_floor = self.floor_data.current;
-        (current_floor,(self.floors_to_position_map[current_floor as usize]))
+        (current_floor, self.floor_levels.level(current_floor))
+    }
+
+    pub fn tell_elevator(&self, message: ElevatorVocabulary) -> Result<(), PulleyError> {
+        self.elevator_controller_actor
+            .as_ref()
+            .ok_or(PulleyError::NotPoweredOn)?
+            .send_message(message)
+            .map_err(|_| PulleyError::ElevatorGone)
+    }
+
+    // The error is handled as its policy says; only an error to escalate, is given back. The
+    // carriage is halted first: it must not go on moving, with nobody the wiser.
+    pub fn on_error(&mut self, error: PulleyError) -> Result<(), PulleyError> {
+        match error.policy() {
+            FaultPolicy::Escalate | FaultPolicy::EnterFaultState => {
+                info!("Pulley: {}, halting at ({})mm", error, self.current_level);
+                self.trip = None;
+                Err(error)
+            },
+            FaultPolicy::LogAndContinue => {
+                info!("Pulley: {}, carrying on", error);
+                Ok(())
+            }
+        }
     }
 }
 
+/// The pulley of one car, as an actor: it moves the carriage along the motion profile, telling
+/// the elevator where the carriage is at every step, and when it has arrived.
 pub struct PulleyActor;
 
 #[async_trait]
 impl Actor for PulleyActor {
     type Msg       = PulleyVocabulary;
     type State     = PulleyData;
-    type Arguments = Vec<(f64 /* x at start */, f64 /* y at start */)>;
+    type Arguments = (FloorLevels, MotionProfile, Duration /* step interval */, SimulationClock);
 
     async fn pre_start(&self, _myself: ActorRef<Self::Msg>, args: Self::Arguments) -> 
         Result<Self::State, ActorProcessingErr> {
-            Ok(PulleyData::new(args))
+            let (floor_levels, profile, step_interval, clock) = args;
+            Ok(PulleyData { profile, step_interval, clock, ..PulleyData::new(floor_levels) })
     }
 
     async fn post_start( &self,_myself: ActorRef<Self::Msg>, data: &mut Self::State) ->
         Result<(), ActorProcessingErr> {
-            info!("floors as seen by pulley {:?}", data.floors_to_position_map);
+            info!("floors as seen by pulley {:?}", data.floor_levels);
             Ok(())
     }
 
@@ -121,169 +243,333 @@ impl Actor for PulleyActor {
         carriage: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
 
-        match message {
-           PulleyVocabulary::PowerOn(controller) => {
-                carriage.hook(controller);
-                info!("Pulley: is being powered on!");
-           },
-           PulleyVocabulary::PowerOff => {
-            // TODO: Abort timer, if it is still working
-            info!("Pulley: is being powered off");
-            myself.stop(Some(String::from("powered down")));
-           },
-           PulleyVocabulary::MoveToFloor(f) => {
-            info!("Pulley: needs to move to floor({})", f);
-             carriage.prepare_for_moving(f);
-             send_after(
-                Duration::from_millis(100),
-                myself.get_cell(),
-                || { PulleyVocabulary::PulleyHasMoved }
-            );
-           },
-           PulleyVocabulary::PulleyHasMoved => {
-                carriage.on_pulley_moving();
-                /* info!("Pulley: is passing by {},{}, dest_y {}",
-                                carriage.current_posn.0,
-                                carriage.current_posn.1,
-                                carriage.dest_posn.1
-                            ); */
-                carriage
-                .elevator_controller_actor
-                .as_ref()
-                .map(|controller| {
-                    controller.send_message(
-                        ElevatorVocabulary::CurrentCarriagePosn(
-                            (carriage.current_posn.0,carriage.current_posn.1))
-                        ).unwrap();
-                });
-
-                if !carriage.has_reached_dest() {
-                    send_after(
-                        Duration::from_millis(100),
-                        myself.get_cell(),
-                        || { PulleyVocabulary::PulleyHasMoved }
-                    ); 
-                }
-                else {
-                    
-                    carriage.adjust_floor_data();
-
-                    info!("Pulley: stops at destination, dest_y {}, currently at floor {}, floor_y {}",
-                        carriage.dest_posn.1,
-                        carriage.currently_at().0,
-                        carriage.currently_at().1.1
-                    );
-                    
-                    carriage.
-                    elevator_controller_actor
-                    .as_ref()
-                    .map(|controller| {
-                        controller.send_message(ElevatorVocabulary::Stop(carriage.floor_data.current))
-                        .unwrap()
-                    }).unwrap();
-                }   
+        if let Err(error) = on_message(&myself, message, carriage) {
+            carriage.on_error(error)?;
+        }
 
-            }
-        };
-       
         Ok(())
     }
 }
 
+fn on_message(
+    myself: &ActorRef<PulleyVocabulary>,
+    message: PulleyVocabulary,
+    carriage: &mut PulleyData
+) -> Result<(), PulleyError> {
+
+    match message {
+       PulleyVocabulary::PowerOn(controller) => {
+            carriage.hook(controller);
+            info!("Pulley: is being powered on!");
+       },
+       PulleyVocabulary::PowerOff => {
+        // TODO: Abort timer, if it is still working
+        info!("Pulley: is being powered off");
+        myself.stop(Some(String::from("powered down")));
+       },
+       PulleyVocabulary::MoveToFloor(f) => {
+        if carriage.elevator_controller_actor.is_none() {
+            return Err(PulleyError::NotPoweredOn);
+        }
+        if !carriage.floor_levels.contains(f) {
+            return Err(PulleyError::FloorOutOfRange(f));
+        }
+        info!("Pulley: needs to move to floor({})", f);
+         carriage.prepare_for_moving(f);
+         if let Some(trip) = carriage.trip.as_ref() {
+            info!("Pulley: off to floor({}) in ({:.3})s, at ({:.3})m/s at most", f, trip.duration().as_secs_f64(), trip.peak_speed());
+         }
+         carriage.clock.send_after(
+            carriage.step_interval,
+            myself.get_cell(),
+            || { PulleyVocabulary::PulleyHasMoved }
+        );
+       },
+       PulleyVocabulary::DivertToFloor(f) => {
+            if carriage.can_stop_at(f) {
+                info!("Pulley: diverting to floor({}), at ({})mm, ({:.3})m/s", f, carriage.current_level, carriage.motion.velocity);
+                carriage.divert_to(f);
+                carriage.tell_elevator(ElevatorVocabulary::DivertedTo(f))?;
+            }
+            else {
+                info!("Pulley: cannot stop at floor({}) anymore, at ({})mm", f, carriage.current_level);
+            }
+       },
+       PulleyVocabulary::PulleyHasMoved => {
+            carriage.on_pulley_moving();
+            let motion = carriage.motion();
+            debug!("Pulley: at ({:.3})m, ({:.3})m/s, ({:.3})m/s²",
+                            motion.position,
+                            motion.velocity,
+                            motion.acceleration
+                        );
+            carriage.tell_elevator(ElevatorVocabulary::CurrentCarriagePosn(carriage.current_level))?;
+
+            if !carriage.has_reached_dest() {
+                carriage.clock.send_after(
+                    carriage.step_interval,
+                    myself.get_cell(),
+                    || { PulleyVocabulary::PulleyHasMoved }
+                );
+            }
+            else {
+
+                carriage.adjust_floor_data();
+
+                info!("Pulley: stops at destination, at ({})mm, currently at floor {}, floor level ({})mm",
+                    carriage.current_level,
+                    carriage.currently_at().0,
+                    carriage.currently_at().1
+                );
+
+                carriage.tell_elevator(ElevatorVocabulary::Stop(carriage.floor_data.current))?;
+            }
+
+        }
+    };
+
+    Ok(())
+}
+
+#[cfg(test)]
 mod test {
 
-    use ractor::Actor;
-    use tokio::sync::mpsc;
-    use super::{PulleyActor, PulleyData};
+    use async_trait::async_trait;
+    use ractor::concurrency::Duration;
+    use ractor::{Actor, ActorProcessingErr, ActorRef, ActorStatus};
+    use proptest::prelude::*;
+    use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+    use tokio::time::timeout;
+    use super::{in_metres, PulleyActor, PulleyData, PULLEY_STEP_INTERVAL};
+    use crate::conversation::vocabulary::{ElevatorVocabulary, Millimetres, PulleyVocabulary};
+    use crate::elevator_installation::motion_profile::BRISK_MOTION;
+    use crate::simulation_clock::SimulationClock;
+    use crate::elevator_installation::generators::floor_levels;
+    use crate::elevator_installation::motion_profile::{MotionProfile, MotionState, SCurveTrip};
+    use crate::elevator_installation::shaft::{FloorLevels, PIT_DEPTH};
      
     #[tokio::test]
-    async fn when_powerd_on_then_should_be_on_0th_floor() -> () {
-        let floor_setting = vec! [
-            (0.0,0.0), (0.0,5.0), (0.0,10.0), (0.0,15.0) // x never changes between floors
-        ];
+    async fn when_powerd_on_then_should_be_on_0th_floor() {
+        let floor_levels = FloorLevels::evenly_spaced(4, 3.5); // 3.5 m apart, as the default motion profile has it
     
 
-        let pulley_data = PulleyData::new(floor_setting);
+        let pulley_data = PulleyData::new(floor_levels);
 
         let current_status = pulley_data.currently_at();
 
         assert_eq!(current_status.0, 0);
-        assert_eq!(current_status.1.0, 0.0);
-        assert_eq!(current_status.1.1, 0.0);
+        assert_eq!(current_status.1, PIT_DEPTH);
 
 
     }
 
+    // Steps the pulley until the carriage has reached its destination, noting where it was shown.
+    fn step_until_reached(pulley_data: &mut PulleyData) -> Vec<Millimetres> {
+        let mut actuals_carriage_posn_y: Vec<Millimetres> = Vec::new();
+        for _next in 0 .. 1000 {
+            if pulley_data.has_reached_dest() { break; }
+            pulley_data.on_pulley_moving();
+            actuals_carriage_posn_y.push(pulley_data.current_level);
+        }
+        actuals_carriage_posn_y
+    }
+
     #[tokio::test]
-    async fn when_moving_to_a_floor_then_pulley_must_know_it_has_reached() -> () {
-        let floor_setting = vec! [
-            (0.0,0.0), (0.0,5.0), (0.0,10.0), (0.0,15.0) // x never changes between floors
-        ];
+    async fn when_moving_to_a_floor_then_pulley_must_know_it_has_reached() {
+        let floor_levels = FloorLevels::evenly_spaced(4, 3.5); // 3.5 m apart, as the default motion profile has it
     
 
-        let pulley_data = &mut PulleyData::new(floor_setting.clone());
-
-        let current_status = pulley_data.currently_at();
+        let pulley_data = &mut PulleyData::new(floor_levels.clone());
 
         pulley_data.prepare_for_moving(2);
 
-        let pulley_needs_to_move_by = floor_setting[2].1 - floor_setting[0].1;
+        assert!(pulley_data.is_moving());
 
-        for _next in 0 .. pulley_needs_to_move_by as u16 {
-            pulley_data.on_pulley_moving();
-        }
+        step_until_reached(pulley_data);
 
-        assert_eq!(pulley_data.has_reached_dest(),true);
+        assert!(pulley_data.has_reached_dest());
+        assert_eq!(pulley_data.motion(), MotionState { position: 8.5, velocity: 0.0, acceleration: 0.0 });
 
     }
 
     #[tokio::test]
-    async fn when_moving_to_a_floor_then_pulley_must_emit_correct_y_displacement() -> () {
-        let floor_setting = vec! [
-            (0.0,0.0), (0.0,5.0), (0.0,10.0), (0.0,15.0) // x never changes between floors
-        ];
+    async fn when_moving_to_a_floor_then_pulley_must_emit_correct_y_displacement() {
+        let floor_levels = FloorLevels::evenly_spaced(4, 3.5); // 3.5 m apart, as the default motion profile has it
     
-        let pulley_data = &mut PulleyData::new(floor_setting.clone());
+        let pulley_data = &mut PulleyData::new(floor_levels.clone());
 
-        // when going up
+        // when going up, 7 m take as long as the S-curve, and the carriage ends up right at the floor
 
         pulley_data.prepare_for_moving(2);
 
-        let expcted_carriage_posn_1_up: [f64; 10] = [1.0,2.0,3.0,4.0,5.0,6.0,7.0,8.0,9.0,10.0];
+        let actuals_carriage_posn_y = step_until_reached(pulley_data);
+        let trip = SCurveTrip::new(1.5, 8.5, &MotionProfile::default());
 
-        let pulley_needs_to_move_by = floor_setting[2].1 - floor_setting[0].1;
-        let mut actuals_carriage_posn_y: Vec<f64> = Vec::new();
+        assert_eq!(actuals_carriage_posn_y.len() as u128, trip.duration().as_millis() / PULLEY_STEP_INTERVAL.as_millis() + 1);
+        assert!(actuals_carriage_posn_y.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(actuals_carriage_posn_y.last(), Some(&floor_levels.level(2)));
 
-        for _next in 0 .. pulley_needs_to_move_by as u16 {
-            pulley_data.on_pulley_moving();
-            actuals_carriage_posn_y.push(pulley_data.current_posn.1);
-            
+        // slow at first, fastest half way
+        let first_step = actuals_carriage_posn_y[0] - PIT_DEPTH;
+        let half_way = actuals_carriage_posn_y.len() / 2;
+        assert!(first_step < actuals_carriage_posn_y[half_way] - actuals_carriage_posn_y[half_way - 1]);
+
+        pulley_data.adjust_floor_data();
+
+
+        // when going down
+        pulley_data.prepare_for_moving(0); 
+
+        let actuals_carriage_posn_y = step_until_reached(pulley_data);
+
+        assert!(actuals_carriage_posn_y.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(actuals_carriage_posn_y.last(), Some(&PIT_DEPTH));
+
+    }
+
+    #[tokio::test]
+    async fn when_moving_then_pulley_can_stop_only_at_floors_it_can_brake_for() {
+        let floor_levels = FloorLevels::evenly_spaced(4, 3.5); // 3.5 m apart, as the default motion profile has it
+
+        let pulley_data = &mut PulleyData::new(floor_levels.clone());
+
+        assert!(!pulley_data.can_stop_at(1)); // not moving yet
+
+        pulley_data.prepare_for_moving(3);
+        for _next in 0 .. 10 {
+            pulley_data.on_pulley_moving(); // 1 s into the trip, still speeding up
         }
 
-        for next in expcted_carriage_posn_1_up.to_vec().into_iter().zip(actuals_carriage_posn_y.clone()).into_iter() {
-            assert_eq!(next.0,next.1);
+        assert!(!pulley_data.can_stop_at(0)); // behind the carriage
+        assert!(pulley_data.can_stop_at(1));
+        assert!(!pulley_data.can_stop_at(3)); // already the destination
+
+        for _next in 0 .. 10 {
+            pulley_data.on_pulley_moving(); // 2 s in, too fast to brake for floor 1
         }
 
-        assert_eq!(pulley_data.has_reached_dest(),true);
+        assert!(pulley_data.current_level < floor_levels.level(1));
+        assert!(!pulley_data.can_stop_at(1));
+        assert!(pulley_data.can_stop_at(2));
 
-        pulley_data.adjust_floor_data();
+        pulley_data.divert_to(2);
+        let actuals_carriage_posn_y = step_until_reached(pulley_data);
 
+        assert_eq!(actuals_carriage_posn_y.last(), Some(&floor_levels.level(2)));
+        assert!(pulley_data.has_reached_dest());
+        assert_eq!(pulley_data.adjust_floor_data().currently_at().0, 2);
+    }
 
-        // when going down
-        pulley_data.prepare_for_moving(0); 
+    proptest! {
+        // However tall the floors are, the carriage goes from any floor to any other in as many
+        // steps as its S-curve takes (to the next whole step), always toward it, and ends up right
+        // at its level.
+        #[test]
+        fn the_carriage_reaches_any_floor_in_as_many_steps_as_its_trip_takes(
+            (floor_levels, from, to) in floor_levels(8).prop_flat_map(|levels| {
+                let floor_count = levels.floor_count();
+                (Just(levels), 0..floor_count, 0..floor_count)
+            })
+        ) {
+            let pulley_data = &mut PulleyData::new(floor_levels.clone());
+            pulley_data.prepare_for_moving(from);
+            step_until_reached(pulley_data);
+            pulley_data.adjust_floor_data();
+
+            pulley_data.prepare_for_moving(to);
+            let actuals_carriage_posn_y = step_until_reached(pulley_data);
+
+            let (from_level, to_level) = (floor_levels.level(from), floor_levels.level(to));
+            let trip = SCurveTrip::new(in_metres(from_level), in_metres(to_level), &MotionProfile::default());
+            let steps = if from == to { 0 } else { trip.duration().as_nanos().div_ceil(PULLEY_STEP_INTERVAL.as_nanos()) };
+
+            prop_assert_eq!(actuals_carriage_posn_y.len() as u128, steps);
+            prop_assert!(actuals_carriage_posn_y.windows(2).all(|pair| to_level.abs_diff(pair[1]) <= to_level.abs_diff(pair[0])));
+            if from != to {
+                prop_assert_eq!(actuals_carriage_posn_y.last(), Some(&to_level));
+            }
+            prop_assert_eq!(pulley_data.adjust_floor_data().currently_at(), (to, to_level));
+        }
+    }
 
-        let expcted_carriage_posn_1_down: [f64; 10] = [9.0,8.0,7.0,6.0,5.0,4.0,3.0,2.0,1.0,0.0];
-        actuals_carriage_posn_y.clear();
+    // Stands in for the elevator: whatever the pulley tells it, but where the carriage is on the
+    // way, is passed on to the test.
+    struct ElevatorProbe;
 
-        for _next in 0 .. pulley_needs_to_move_by as u16 {
-            pulley_data.on_pulley_moving();
-            actuals_carriage_posn_y.push(pulley_data.current_posn.1);
-            
+    #[async_trait]
+    impl Actor for ElevatorProbe {
+        type Msg = ElevatorVocabulary;
+        type State = UnboundedSender<ElevatorVocabulary>;
+        type Arguments = UnboundedSender<ElevatorVocabulary>;
+
+        async fn pre_start(&self, _myself: ActorRef<Self::Msg>, tx: Self::Arguments) -> Result<Self::State, ActorProcessingErr> {
+            Ok(tx)
         }
 
-        for next in expcted_carriage_posn_1_down.to_vec().into_iter().zip(actuals_carriage_posn_y.clone()).into_iter() {
-            assert_eq!(next.0,next.1);
+        async fn handle(&self, _myself: ActorRef<Self::Msg>, message: Self::Msg, tx: &mut Self::State) -> Result<(), ActorProcessingErr> {
+            if !matches!(message, ElevatorVocabulary::CurrentCarriagePosn(_)) {
+                let _ = tx.send(message);
+            }
+            Ok(())
         }
+    }
 
+    async fn pulley_and_probe(test: u8) -> (ActorRef<PulleyVocabulary>, ActorRef<ElevatorVocabulary>, UnboundedReceiver<ElevatorVocabulary>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (probe_ref, _) = Actor::spawn(Some(format!("Test_elevator_probe-{}", test)), ElevatorProbe, tx)
+            .await
+            .expect("Failed to start elevator probe");
+        let (pulley_ref, _) = Actor::spawn(
+                Some(format!("Test_pulley_actor-{}", test)),
+                PulleyActor,
+                (FloorLevels::evenly_spaced(4, BRISK_MOTION.floor_height), BRISK_MOTION, PULLEY_STEP_INTERVAL, SimulationClock::default())
+            )
+            .await
+            .expect("Failed to create Pulley actor");
+        (pulley_ref, probe_ref, rx)
     }
-}
\ No newline at end of file
+
+    #[tokio::test(start_paused = true)]
+    async fn when_not_powered_on_then_the_pulley_does_not_move() {
+        let (pulley_ref, probe_ref, mut rx) = pulley_and_probe(4).await;
+
+        pulley_ref.send_message(PulleyVocabulary::MoveToFloor(2)).unwrap();
+        pulley_ref.send_message(PulleyVocabulary::PowerOn(probe_ref.clone())).unwrap();
+        assert!(timeout(Duration::from_secs(5), rx.recv()).await.is_err());
+
+        pulley_ref.send_message(PulleyVocabulary::MoveToFloor(2)).unwrap();
+        assert_eq!(timeout(Duration::from_secs(5), rx.recv()).await, Ok(Some(ElevatorVocabulary::Stop(2))));
+
+        pulley_ref.stop(None);
+        probe_ref.stop(None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_told_to_go_beyond_the_shaft_then_the_pulley_stays_where_it_is() {
+        let (pulley_ref, probe_ref, mut rx) = pulley_and_probe(5).await;
+
+        pulley_ref.send_message(PulleyVocabulary::PowerOn(probe_ref.clone())).unwrap();
+        pulley_ref.send_message(PulleyVocabulary::MoveToFloor(4)).unwrap();
+        assert!(timeout(Duration::from_secs(5), rx.recv()).await.is_err());
+        assert_eq!(pulley_ref.get_status(), ActorStatus::Running);
+
+        pulley_ref.send_message(PulleyVocabulary::MoveToFloor(3)).unwrap();
+        assert_eq!(timeout(Duration::from_secs(5), rx.recv()).await, Ok(Some(ElevatorVocabulary::Stop(3))));
+
+        pulley_ref.stop(None);
+        probe_ref.stop(None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_the_elevator_has_gone_then_the_pulley_halts_and_fails() {
+        let (pulley_ref, probe_ref, _rx) = pulley_and_probe(6).await;
+
+        pulley_ref.send_message(PulleyVocabulary::PowerOn(probe_ref.clone())).unwrap();
+        probe_ref.stop(None);
+        pulley_ref.send_message(PulleyVocabulary::MoveToFloor(3)).unwrap();
+
+        // The first step of the trip has nobody to tell.
+        tokio::time::sleep(PULLEY_STEP_INTERVAL * 2).await;
+        assert_eq!(pulley_ref.get_status(), ActorStatus::Stopped);
+    }
+}
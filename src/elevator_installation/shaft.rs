@@ -0,0 +1,100 @@
+use crate::conversation::vocabulary::Millimetres;
+
+// The lowest landing stands this high above the floor of the pit.
+pub const PIT_DEPTH: Millimetres = 1500;
+
+/// Where the landings are in the shaft, from the lowest up: in millimetres above the floor of the
+/// pit. The carriage is moved, and told where it is, in these units only; how the shaft is drawn
+/// on the screen is of no concern to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FloorLevels {
+    levels: Vec<Millimetres>
+}
+
+impl FloorLevels {
+    pub fn evenly_spaced(floor_count: u16, floor_height: f64 /* m */) -> Self {
+        let floor_height = (floor_height * 1000.0).round() as Millimetres;
+        FloorLevels {
+            levels: (0..floor_count as Millimetres).map(|floor| PIT_DEPTH + floor * floor_height).collect()
+        }
+    }
+
+    // Floors of any height: the levels of the landings, from the lowest up, each higher than the
+    // one below it.
+    pub fn with_levels(levels: Vec<Millimetres>) -> Result<Self, String> {
+        if levels.len() < 2 || levels.len() > u8::MAX as usize {
+            return Err(format!("A shaft must have 2 to {} landings, not ({})", u8::MAX, levels.len()));
+        }
+        if let Some(pair) = levels.windows(2).find(|pair| pair[0] >= pair[1]) {
+            return Err(format!("A landing at ({}) mm is not above the one at ({}) mm", pair[1], pair[0]));
+        }
+        Ok(FloorLevels { levels })
+    }
+
+    pub fn floor_count(&self) -> u8 {
+        self.levels.len() as u8
+    }
+
+    pub fn level(&self, floor: u8) -> Millimetres {
+        self.levels[floor as usize]
+    }
+
+    pub fn contains(&self, floor: u8) -> bool {
+        (floor as usize) < self.levels.len()
+    }
+
+    // The highest floor at or below the level, and the lowest one at or above it; none, beyond the
+    // ends of the shaft.
+    pub fn floor_at_or_below(&self, level: Millimetres) -> Option<u8> {
+        self.levels.iter().rposition(|floor_level| *floor_level <= level).map(|floor| floor as u8)
+    }
+
+    pub fn floor_at_or_above(&self, level: Millimetres) -> Option<u8> {
+        self.levels.iter().position(|floor_level| *floor_level >= level).map(|floor| floor as u8)
+    }
+
+    // Between two floors, how far up from the lower one (0.0 at it, 1.0 at the next one up). Below
+    // the lowest floor, or above the highest one, as far as that floor.
+    pub fn floors_above_lowest(&self, level: Millimetres) -> f64 {
+        let Some(below) = self.floor_at_or_below(level) else {
+            return 0.0;
+        };
+        match self.levels.get(below as usize + 1) {
+            Some(above) => {
+                let lower = self.level(below);
+                below as f64 + (level - lower) as f64 / (above - lower) as f64
+            },
+            None => below as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_floors_are_evenly_spaced_then_a_level_is_found_between_the_right_two() {
+        let shaft = FloorLevels::evenly_spaced(4, 3.5);
+
+        assert_eq!(shaft.level(0), PIT_DEPTH);
+        assert_eq!(shaft.level(3), PIT_DEPTH + 10_500);
+        assert_eq!(shaft.floor_at_or_below(PIT_DEPTH + 5_000), Some(1));
+        assert_eq!(shaft.floor_at_or_above(PIT_DEPTH + 5_000), Some(2));
+        assert_eq!(shaft.floor_at_or_above(PIT_DEPTH + 7_000), Some(2));
+        assert_eq!(shaft.floor_at_or_below(PIT_DEPTH - 1), None);
+        assert_eq!(shaft.floor_at_or_above(PIT_DEPTH + 10_501), None);
+
+        assert_eq!(shaft.floors_above_lowest(PIT_DEPTH + 5_250), 1.5);
+        assert_eq!(shaft.floors_above_lowest(PIT_DEPTH + 10_500), 3.0);
+        assert_eq!(shaft.floors_above_lowest(0), 0.0);
+        assert!(shaft.contains(3) && !shaft.contains(4));
+    }
+
+    #[test]
+    fn when_landings_are_not_one_above_the_other_then_there_is_no_shaft() {
+        assert_eq!(FloorLevels::with_levels(vec![PIT_DEPTH, PIT_DEPTH + 6_000, PIT_DEPTH + 9_000]).map(|shaft| shaft.floor_count()), Ok(3));
+        assert!(FloorLevels::with_levels(vec![PIT_DEPTH, PIT_DEPTH]).is_err());
+        assert!(FloorLevels::with_levels(vec![PIT_DEPTH]).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+
+use rust_fsm::StateMachineImpl;
+
+use crate::elevator_installation::elevator_operations::{ElevatorFSMInputs, ElevatorFSMStates, ElevatorStateMachine};
+
+/// The formats the diagram is drawn in, e.g. on the command line.
+pub const DIAGRAM_FORMATS: [&str; 2] = ["dot", "mermaid"];
+
+/// One transition of the elevator: from a state, on an input, to a state (with an output, if any).
+/// A floor is written as 'n', unless the transition is only for one floor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    pub from: ElevatorFSMStates,
+    pub input: String,
+    pub to: ElevatorFSMStates,
+    pub output: Option<String>
+}
+
+impl Edge {
+    fn label(&self) -> String {
+        match &self.output {
+            Some(output) => format!("{} / {}", self.input, output),
+            None => self.input.clone()
+        }
+    }
+}
+
+// Every input, one for every floor that makes a difference: the ground floor, and any other.
+const GROUND_FLOOR: u8 = 0;
+const ANY_OTHER_FLOOR: u8 = 1;
+
+fn inputs_at(floor: u8) -> [ElevatorFSMInputs; 8] {
+    use ElevatorFSMInputs::*;
+    [SwitchOn, SwitchOff, MoveTo(floor), Divert(floor), DoorClosed, Stop, Overload, LoadReduced]
+}
+
+// The input, or output, as it is written: its floor (if it has one) called as given.
+fn described<T: std::fmt::Debug>(named: &T, floor: u8, floor_called: &str) -> String {
+    format!("{:?}", named).replace(&format!("({})", floor), &format!("({})", floor_called))
+}
+
+fn edge(from: ElevatorFSMStates, input: &ElevatorFSMInputs, floor: u8, floor_called: &str) -> Option<Edge> {
+    ElevatorStateMachine::transition(&from, input).map(|to| Edge {
+        from,
+        input: described(input, floor, floor_called),
+        to,
+        output: ElevatorStateMachine::output(&from, input).map(|output| described(&output, floor, floor_called))
+    })
+}
+
+/// Every transition of the elevator's state machine, as the machine itself makes it: state by
+/// state, in the order of ElevatorFSMStates::ALL. An input for a floor that is taken alike at the
+/// ground floor and any other, is one edge; otherwise, one edge for each of them.
+pub fn edges() -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for from in ElevatorFSMStates::ALL {
+        for (at_ground, elsewhere) in inputs_at(GROUND_FLOOR).iter().zip(inputs_at(ANY_OTHER_FLOOR).iter()) {
+            let any_floor = edge(from, at_ground, GROUND_FLOOR, "n");
+            if any_floor.is_some() && any_floor == edge(from, elsewhere, ANY_OTHER_FLOOR, "n") {
+                edges.extend(any_floor);
+                continue;
+            }
+            edges.extend(edge(from, at_ground, GROUND_FLOOR, "0"));
+            if let Some(other) = edge(from, elsewhere, ANY_OTHER_FLOOR, "n > 0") {
+                if !edges.contains(&other) {
+                    edges.push(other);
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// The state machine in Graphviz DOT: the initial state is drawn with a double circle.
+pub fn to_dot(edges: &[Edge]) -> String {
+    let mut dot = String::from("digraph ElevatorStateMachine {\n    rankdir=LR;\n    node [shape=circle];\n");
+    for state in ElevatorFSMStates::ALL {
+        let shape = if state == ElevatorStateMachine::INITIAL_STATE { " [shape=doublecircle]" } else { "" };
+        let _ = writeln!(dot, "    {:?}{};", state, shape);
+    }
+    for edge in edges {
+        let _ = writeln!(dot, "    {:?} -> {:?} [label=\"{}\"];", edge.from, edge.to, edge.label());
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// The state machine as a Mermaid state diagram, e.g. for the README.
+pub fn to_mermaid(edges: &[Edge]) -> String {
+    let mut mermaid = String::from("stateDiagram-v2\n");
+    let _ = writeln!(mermaid, "    [*] --> {:?}", ElevatorStateMachine::INITIAL_STATE);
+    for state in ElevatorFSMStates::ALL {
+        if !edges.iter().any(|edge| edge.from == state || edge.to == state) {
+            let _ = writeln!(mermaid, "    {:?}", state);
+        }
+    }
+    for edge in edges {
+        let _ = writeln!(mermaid, "    {:?} --> {:?} : {}", edge.from, edge.to, edge.label());
+    }
+    mermaid
+}
+
+/// The state machine, drawn in one of DIAGRAM_FORMATS.
+pub fn diagram_in(format: &str) -> Option<String> {
+    match format {
+        "dot" => Some(to_dot(&edges())),
+        "mermaid" => Some(to_mermaid(&edges())),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ElevatorFSMStates::*;
+
+    fn find<'a>(edges: &'a [Edge], from: ElevatorFSMStates, input: &str) -> Option<&'a Edge> {
+        edges.iter().find(|edge| edge.from == from && edge.input == input)
+    }
+
+    #[test]
+    fn when_the_edges_are_listed_then_they_follow_the_state_machine_floor_for_floor() {
+        let edges = edges();
+
+        assert_eq!(find(&edges, PoweredOff, "SwitchOn"), Some(&Edge {
+            from: PoweredOff,
+            input: String::from("SwitchOn"),
+            to: ReadyForService,
+            output: Some(String::from("SettleAtHomeFloor"))
+        }));
+        assert_eq!(find(&edges, ReadyForService, "MoveTo(n)").unwrap().output.as_deref(), Some("NextDest(n)"));
+        assert_eq!(find(&edges, ShuttingDown, "MoveTo(n)").unwrap().to, ShuttingDown);
+        assert_eq!(find(&edges, ShuttingDown, "MoveTo(0)"), None);
+        assert_eq!(find(&edges, NonOperational, "DoorClosed").unwrap().output, None);
+        assert_eq!(edges.len(), 20);
+
+        let dot = to_dot(&edges);
+        assert!(dot.starts_with("digraph ElevatorStateMachine {"));
+        assert!(dot.contains("    PoweredOff [shape=doublecircle];"));
+        assert!(dot.contains("    Moving -> DoorIsOpen [label=\"Stop / Reached\"];"));
+
+        let mermaid = to_mermaid(&edges);
+        assert!(mermaid.starts_with("stateDiagram-v2\n    [*] --> PoweredOff\n    PoweredOff --> ReadyForService"));
+        assert!(mermaid.contains("    Overloaded --> DoorIsOpen : LoadReduced / ResumeClosingDoor"));
+    }
+
+    #[test]
+    fn the_diagram_in_the_readme_matches_the_state_machine() {
+        let readme = include_str!("../../README.md");
+        let start = readme.find("```mermaid\n").expect("The README has a Mermaid diagram") + "```mermaid\n".len();
+        let end = start + readme[start..].find("```").expect("The Mermaid diagram is closed");
+
+        assert_eq!(&readme[start..end], to_mermaid(&edges()), "Draw it again: cargo run -- --fsm-diagram mermaid");
+    }
+}
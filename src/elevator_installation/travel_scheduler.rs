@@ -0,0 +1,202 @@
+use crate::conversation::vocabulary::CallDirection;
+use crate::elevator_installation::dispatch_strategy::{DispatchContext, DispatchStrategy};
+use crate::elevator_installation::pending_calls::PendingCalls;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TravelDirection {
+    Up,
+    Down,
+    #[default]
+    Idle
+}
+
+/// Keeps track of the direction the carriage is travelling in. Which of the pending calls is
+/// answered next, is decided by the `DispatchStrategy`.
+#[derive(Debug)]
+pub struct TravelScheduler {
+    direction: TravelDirection,
+    top_floor: u8,
+    strategy: Box<dyn DispatchStrategy>,
+    answered_hall_calls: Vec<(u8, CallDirection)>
+}
+
+impl TravelScheduler {
+    pub fn new(strategy: Box<dyn DispatchStrategy>, top_floor: u8) -> Self {
+        TravelScheduler {
+            direction: TravelDirection::Idle,
+            top_floor,
+            strategy,
+            answered_hall_calls: Vec::new()
+        }
+    }
+
+    pub fn direction(&self) -> TravelDirection {
+        self.direction
+    }
+
+    pub fn strategy_name(&self) -> &'static str {
+        self.strategy.name()
+    }
+
+    /// The carriage has been instructed to travel from `from_floor` to `to_floor`.
+    pub fn on_departure(&mut self, from_floor: u8, to_floor: u8) {
+        self.direction =
+            if to_floor > from_floor { TravelDirection::Up }
+            else if to_floor < from_floor { TravelDirection::Down }
+            else { self.direction };
+    }
+
+    /// Picks the next floor to stop at, given that the carriage is standing at `from_floor`, with
+    /// its door open. The calls at `from_floor` for the direction it leaves in, are answered; so
+    /// are those for the direction it arrived in, if it reverses there.
+    pub fn next_stop(&mut self, from_floor: u8, pending_calls: &mut PendingCalls) -> Option<u8> {
+        let arrived_going = self.direction;
+        let context = DispatchContext {
+            current_floor: from_floor,
+            direction: self.direction,
+            top_floor: self.top_floor
+        };
+
+        let calls_elsewhere = pending_calls.without_floor(from_floor);
+
+        let next_stop =
+            if calls_elsewhere.is_empty() { None }
+            else {
+                self.strategy
+                    .next_destination(&context, &calls_elsewhere)
+                    .filter(|f| *f != from_floor && *f <= self.top_floor)
+            };
+
+        match next_stop {
+            Some(floor) => self.on_departure(from_floor, floor),
+            None        => self.direction = TravelDirection::Idle
+        };
+
+        // Otherwise, a hall call up at one floor and down at the one below, would keep the carriage
+        // shuttling between the two, reversing at each without answering either.
+        let mut answered = pending_calls.serve(from_floor, self.direction);
+        if arrived_going != self.direction && arrived_going != TravelDirection::Idle {
+            answered.extend(pending_calls.serve(from_floor, arrived_going));
+        }
+        for direction in answered {
+            self.answered_hall_calls.push((from_floor, direction));
+        }
+
+        next_stop
+    }
+
+    /// As `next_stop`, for a full carriage: it stops only where its passengers want to get off.
+    /// No hall call is answered, not even at `from_floor`; they all remain pending.
+    pub fn next_stop_bypassing_hall_calls(&mut self, from_floor: u8, pending_calls: &mut PendingCalls) -> Option<u8> {
+        let next_stop = self.next_stop(from_floor, &mut pending_calls.car_calls_only());
+        pending_calls.serve_car_call(from_floor);
+        next_stop
+    }
+
+    pub fn take_answered_hall_calls(&mut self) -> Vec<(u8, CallDirection)> {
+        std::mem::take(&mut self.answered_hall_calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elevator_installation::dispatch_strategy::LookStrategy;
+
+    fn stop_order(scheduler: &mut TravelScheduler, calls: &mut PendingCalls, mut from_floor: u8) -> Vec<u8> {
+        let mut stops = Vec::new();
+        while let Some(next) = scheduler.next_stop(from_floor, calls) {
+            stops.push(next);
+            from_floor = next;
+        }
+        stops
+    }
+
+    fn car_calls(floors: &[u8]) -> PendingCalls {
+        let mut calls = PendingCalls::new();
+        for f in floors {
+            calls.add_car_call(*f);
+        }
+        calls
+    }
+
+    #[test]
+    fn when_going_up_then_all_floors_above_are_served_before_reversing() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+        scheduler.on_departure(0, 3);
+
+        let calls = &mut car_calls(&[1, 5, 7, 2, 6]);
+
+        assert_eq!(stop_order(scheduler, calls, 3), vec![5, 6, 7, 2, 1]);
+        assert_eq!(scheduler.direction(), TravelDirection::Idle);
+    }
+
+    #[test]
+    fn when_going_down_then_all_floors_below_are_served_before_reversing() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+        scheduler.on_departure(7, 4);
+
+        let calls = &mut car_calls(&[6, 0, 5, 2, 7]);
+
+        assert_eq!(stop_order(scheduler, calls, 4), vec![2, 0, 5, 6, 7]);
+    }
+
+    #[test]
+    fn when_requests_arrive_in_zig_zag_order_then_carriage_does_not_zig_zag() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+
+        let calls = &mut car_calls(&[7, 1, 6]);
+
+        assert_eq!(stop_order(scheduler, calls, 0), vec![1, 6, 7]);
+    }
+
+    #[test]
+    fn when_idle_then_the_nearest_floor_is_chosen_first() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+
+        let calls = &mut car_calls(&[0, 6]);
+
+        assert_eq!(stop_order(scheduler, calls, 4), vec![6, 0]);
+    }
+
+    #[test]
+    fn when_request_is_for_the_current_floor_then_it_is_served_in_place() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+
+        let calls = &mut car_calls(&[3, 3]);
+        calls.add_hall_call(3, CallDirection::Down);
+
+        assert_eq!(scheduler.next_stop(3, calls), None);
+        assert!(calls.is_empty());
+        assert_eq!(scheduler.take_answered_hall_calls(), vec![(3, CallDirection::Down)]);
+    }
+
+    #[test]
+    fn when_carriage_reverses_at_a_hall_call_for_the_way_it_came_then_that_call_is_answered() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+        scheduler.on_departure(0, 3);
+
+        let calls = &mut PendingCalls::new();
+        calls.add_hall_call(3, CallDirection::Up);
+        calls.add_hall_call(2, CallDirection::Down);
+
+        assert_eq!(scheduler.next_stop(3, calls), Some(2));
+        assert_eq!(scheduler.take_answered_hall_calls(), vec![(3, CallDirection::Up)]);
+        assert_eq!(scheduler.next_stop(2, calls), None);
+        assert_eq!(scheduler.take_answered_hall_calls(), vec![(2, CallDirection::Down)]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn when_carriage_is_full_then_hall_calls_are_passed_by() {
+        let scheduler = &mut TravelScheduler::new(Box::new(LookStrategy), 7);
+
+        let calls = &mut car_calls(&[6]);
+        calls.add_hall_call(2, CallDirection::Up);
+        calls.add_hall_call(0, CallDirection::Up);
+
+        assert_eq!(scheduler.next_stop_bypassing_hall_calls(0, calls), Some(6));
+        assert_eq!(scheduler.take_answered_hall_calls(), vec![]);
+        assert_eq!(calls.floors(), &[6, 2, 0]);
+    }
+}
@@ -0,0 +1,476 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ractor::Actor;
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+use crate::app::AppResult;
+use elevator_ratatui::building::Building;
+use elevator_ratatui::conversation::vocabulary::{CallDirection, CarIndex, ElevatorVocabulary, GroupVocabulary};
+use elevator_ratatui::elevator_installation::dispatch_strategy::{dispatch_strategy_named, DispatchStrategy};
+use elevator_ratatui::elevator_installation::group_controller::{car_letter, GroupControllerActor, OperatingTimes};
+use crate::journal::{Journal, JournalEntry, JournalHeader};
+use crate::metrics::{MetricsReport, ServiceMetrics};
+use elevator_ratatui::passenger::{Passenger, PassengerFlow};
+use elevator_ratatui::simulation_clock::{ClockMode, SimulationClock};
+
+// Once every call has been made, the run is over when no car has had anything to do for this long
+// (in simulated time, as are all the times of a run).
+const SETTLING_TIME: Duration = Duration::from_millis(500);
+
+// Every run has a group of its own; runs may go on side by side (in tests, for example).
+static RUNS_STARTED: AtomicUsize = AtomicUsize::new(0);
+
+/// A call made by a passenger, as the operator would have made it on the terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Call {
+    Hall { floor: u8, direction: CallDirection },
+    Car { car: CarIndex, floor: u8 },
+    Destination { from_floor: u8, to_floor: u8 }
+}
+
+impl Call {
+    fn to_group_message(self) -> GroupVocabulary {
+        match self {
+            Call::Hall { floor, direction } => GroupVocabulary::HallCall { floor, direction },
+            Call::Car { car, floor } => GroupVocabulary::ToCar(car, ElevatorVocabulary::CarCall { floor }),
+            Call::Destination { from_floor, to_floor } => GroupVocabulary::DestinationCall { from_floor, to_floor }
+        }
+    }
+}
+
+/// A call, and when it is made: counted from the start of the run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScriptedCall {
+    pub at: Duration,
+    pub call: Call
+}
+
+// One call per line, made so many milliseconds after the start; floors go by their names, e.g.:
+//
+//      # millis  call
+//      0         hall 3 down
+//      500       car A 5
+//      1200      dest G 6
+//
+pub fn parse_script(text: &str, building: &Building) -> AppResult<Vec<ScriptedCall>> {
+    let floor_named = |name: &str| building.floor_named(name).ok_or(format!("There is no floor named ({})", name));
+    let car_named = |name: &str| {
+        let letter = name.to_ascii_uppercase();
+        (0..building.car_count())
+            .find(|car| letter == car_letter(*car).to_string())
+            .ok_or(format!("There is no car ({})", name))
+    };
+
+    let mut calls = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let call = match words.as_slice() {
+            [_, "hall", floor, "up"]   => Call::Hall { floor: floor_named(floor)?, direction: CallDirection::Up },
+            [_, "hall", floor, "down"] => Call::Hall { floor: floor_named(floor)?, direction: CallDirection::Down },
+            [_, "car", car, floor]     => Call::Car { car: car_named(car)?, floor: floor_named(floor)? },
+            [_, "dest", from, to]      => Call::Destination { from_floor: floor_named(from)?, to_floor: floor_named(to)? },
+            _ => return Err(format!("Line ({}): cannot make out the call ({})", line_no + 1, line).into())
+        };
+        let at = words[0]
+            .parse::<u64>()
+            .map_err(|_| format!("Line ({}): ({}) is not a number of milliseconds", line_no + 1, words[0]))?;
+
+        calls.push(ScriptedCall { at: Duration::from_millis(at), call });
+    }
+
+    calls.sort_by_key(|scripted| scripted.at);
+    Ok(calls)
+}
+
+// Passengers turn up one after the other, `mean_interval` apart on average, and enter their
+// destination at a kiosk. The same seed gives the same passengers.
+pub fn random_traffic(building: &Building, passengers: usize, mean_interval: Duration, seed: u64) -> Vec<ScriptedCall> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    // In u16: with 255 floors, a floor plus the floors to go past it, is beyond a u8.
+    let floor_count = building.floor_count();
+    let mut at = Duration::ZERO;
+
+    (0..passengers)
+        .map(|_| {
+            at += mean_interval.mul_f64(rng.gen_range(0.0..2.0));
+            let from_floor = rng.gen_range(0..floor_count);
+            let to_floor = (from_floor + rng.gen_range(1..floor_count)) % floor_count;
+            ScriptedCall { at, call: Call::Destination { from_floor: from_floor as u8, to_floor: to_floor as u8 } }
+        })
+        .collect()
+}
+
+/// What a car has done during a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CarSummary {
+    pub trips: usize,
+    pub stops: usize,
+    pub floors_travelled: usize
+}
+
+/// What happened during a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunSummary {
+    pub duration: Duration,
+    pub calls_made: usize,
+    pub hall_calls_answered: usize,
+    pub destinations_assigned: usize,
+    pub cars: Vec<CarSummary>,
+    pub passengers: Vec<Passenger>,
+    pub service: MetricsReport,
+    pub timed_out: bool
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Summary: ({}) calls made, in ({:.3})s{}",
+            self.calls_made,
+            self.duration.as_secs_f64(),
+            if self.timed_out { ", before running out of time" } else { "" })?;
+        writeln!(f, "  hall calls answered: ({}), destinations assigned: ({})",
+            self.hall_calls_answered,
+            self.destinations_assigned)?;
+        if !self.passengers.is_empty() {
+            writeln!(f, "  passengers delivered: ({}) of ({})",
+                self.passengers.iter().filter(|passenger| passenger.has_arrived()).count(),
+                self.passengers.len())?;
+        }
+        for (car, summary) in self.cars.iter().enumerate() {
+            writeln!(f, "  car {}: ({}) trips, ({}) stops, ({}) floors travelled",
+                car_letter(car),
+                summary.trips,
+                summary.stops,
+                summary.floors_travelled)?;
+        }
+        write!(f, "{}", self.service)
+    }
+}
+
+// Where a car is, and whether it is busy, as far as its operator can tell.
+#[derive(Debug, Default)]
+struct CarProgress {
+    at_floor: u8,
+    moving: bool,
+    door_open: bool,
+    out_of_service: bool
+}
+
+/// Runs the group of cars of the building without a terminal: the calls are made when the script
+/// says so, or by the passengers as they turn up, and everything the cars tell their operator is
+/// written to `out`. Once every car has settled, the cars are taken out of service.
+pub struct HeadlessRun {
+    pub building: Building,
+    pub operating_times: OperatingTimes,
+    pub strategy: String,
+    pub calls: Vec<ScriptedCall>,
+    pub passengers: Vec<Passenger>,
+    pub time_limit: Duration,
+    pub clock_mode: ClockMode,
+    pub journal: Option<PathBuf>,
+    pub verbose: bool // carriage positions are written out too
+}
+
+impl HeadlessRun {
+    pub async fn run(self, out: &mut dyn Write) -> AppResult<RunSummary> {
+        let car_count = self.building.car_count();
+        let dispatch_strategies = (0..car_count)
+            .map(|_| dispatch_strategy_named(&self.strategy).ok_or(format!("Unknown dispatch strategy ({})", self.strategy)))
+            .collect::<Result<Vec<Box<dyn DispatchStrategy>>, String>>()?;
+
+        let mut journal = match &self.journal {
+            Some(path) => Some(Journal::create(path, &JournalHeader::new(self.building.clone(), &self.strategy, self.operating_times))?),
+            None => None
+        };
+        let clock = SimulationClock::new(self.clock_mode);
+        let (car_tx, mut car_rx) = mpsc::unbounded_channel();
+
+        let (group_ref, group_handle) = Actor::spawn(
+            Some(format!("Headless-{}", RUNS_STARTED.fetch_add(1, Ordering::Relaxed))),
+            GroupControllerActor,
+            (self.building.clone(), self.operating_times, clock.clone(), dispatch_strategies, Some(car_tx))
+        )
+        .await?;
+
+        let elapsed = || clock.elapsed().as_secs_f64();
+        let mut metrics = ServiceMetrics::new(&self.building);
+        let mut record = |entry: JournalEntry| {
+            metrics.on_entry(clock.elapsed(), &entry);
+            if let Some(journal) = journal.as_mut() {
+                journal.record(clock.elapsed(), entry);
+            }
+        };
+
+        record(JournalEntry::ToGroup(GroupVocabulary::PowerOn));
+        group_ref.send_message(GroupVocabulary::PowerOn)?;
+
+        let mut summary = RunSummary { cars: vec![CarSummary::default(); car_count], ..RunSummary::default() };
+        let mut cars: Vec<CarProgress> = (0..car_count).map(|_| CarProgress::default()).collect();
+        let mut next_call = 0;
+        let mut flow = PassengerFlow::new(self.passengers.clone(), self.building.rated_load());
+        let mut next_passenger = 0;
+        let mut powering_off = false;
+
+        loop {
+            let next_call_at = self.calls.get(next_call).map_or(Duration::ZERO, |scripted| scripted.at);
+            let next_arrival_at = flow.next_arrival_at(next_passenger).unwrap_or(Duration::ZERO);
+            let all_settled = next_call == self.calls.len()
+                && next_passenger == flow.passengers.len()
+                && flow.is_everybody_delivered(next_passenger)
+                && cars.iter().all(|car| !car.moving && !car.door_open);
+
+            tokio::select! {
+                report = car_rx.recv() => {
+                    let Some((car, report)) = report else { break };
+                    record(JournalEntry::FromCar(car, report.clone()));
+                    if self.verbose || !matches!(report, ElevatorVocabulary::CurrentCarriagePosn(_)) {
+                        writeln!(out, "{:>9.3}s  car {}  {:?}", elapsed(), car_letter(car), report)?;
+                    }
+
+                    // The car is weighed first: a passenger left behind by a full car, does not
+                    // want it back.
+                    let buttons = flow.on_report(clock.elapsed(), car, &report);
+                    for (car, load) in flow.take_weighed_loads() {
+                        let weighed = GroupVocabulary::ToCar(car, ElevatorVocabulary::LoadWeighed(load));
+                        record(JournalEntry::ToGroup(weighed.clone()));
+                        group_ref.send_message(weighed)?;
+                    }
+                    for button in buttons {
+                        writeln!(out, "{:>9.3}s  passenger presses {:?}", elapsed(), button)?;
+                        record(JournalEntry::ToGroup(button.to_group_message()));
+                        group_ref.send_message(button.to_group_message())?;
+                    }
+
+                    let progress = &mut cars[car];
+                    match report {
+                        ElevatorVocabulary::MovingTo(_) => {
+                            if !progress.moving {
+                                summary.cars[car].trips += 1;
+                            }
+                            progress.moving = true;
+                        },
+                        ElevatorVocabulary::OpenTheDoor(floor) => {
+                            summary.cars[car].stops += 1;
+                            summary.cars[car].floors_travelled += floor.abs_diff(progress.at_floor) as usize;
+                            *progress = CarProgress { at_floor: floor, moving: false, door_open: true, ..*progress };
+                        },
+                        // The door closes on its own, after the dwell (and not while the car is
+                        // overloaded).
+                        ElevatorVocabulary::DoorClosed(_) => progress.door_open = false,
+                        ElevatorVocabulary::Stay(_) => progress.moving = false,
+                        ElevatorVocabulary::HallCallAnswered { .. } => summary.hall_calls_answered += 1,
+                        ElevatorVocabulary::DestinationAssigned { .. } => summary.destinations_assigned += 1,
+                        ElevatorVocabulary::ElevatorOutOfService => progress.out_of_service = true,
+                        _ => {}
+                    }
+
+                    if cars.iter().all(|car| car.out_of_service) {
+                        break;
+                    }
+                },
+                _ = clock.sleep_until(next_call_at), if next_call < self.calls.len() => {
+                    let call = self.calls[next_call].call;
+                    writeln!(out, "{:>9.3}s  call   {:?}", elapsed(), call)?;
+                    record(JournalEntry::ToGroup(call.to_group_message()));
+                    group_ref.send_message(call.to_group_message())?;
+                    summary.calls_made += 1;
+                    next_call += 1;
+                },
+                _ = clock.sleep_until(next_arrival_at), if next_passenger < flow.passengers.len() => {
+                    let passenger = &flow.passengers[next_passenger];
+                    writeln!(out, "{:>9.3}s  passenger ({}) turns up at floor ({}), going to floor ({})",
+                        elapsed(),
+                        passenger.id,
+                        self.building.floor_name(passenger.origin),
+                        self.building.floor_name(passenger.destination))?;
+                    if let Some(button) = flow.on_arrival(next_passenger) {
+                        record(JournalEntry::ToGroup(button.to_group_message()));
+                        group_ref.send_message(button.to_group_message())?;
+                        summary.calls_made += 1;
+                    }
+                    next_passenger += 1;
+                },
+                _ = clock.sleep(SETTLING_TIME), if all_settled && !powering_off => {
+                    writeln!(out, "{:>9.3}s  all cars have settled, powering off", elapsed())?;
+                    record(JournalEntry::ToGroup(GroupVocabulary::PowerOff));
+                    group_ref.send_message(GroupVocabulary::PowerOff)?;
+                    powering_off = true;
+                },
+                _ = clock.sleep_until(self.time_limit) => {
+                    writeln!(out, "{:>9.3}s  out of time", elapsed())?;
+                    summary.timed_out = true;
+                    group_ref.stop(Some(String::from("Out of time")));
+                    break;
+                }
+            }
+        }
+
+        summary.duration = clock.elapsed();
+        summary.passengers = flow.passengers;
+        summary.service = metrics.report(summary.duration);
+        let _ = group_handle.await;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elevator_ratatui::passenger::{TrafficGenerator, TrafficPattern};
+    use elevator_ratatui::elevator_installation::motion_profile::BRISK_MOTION;
+
+    #[test]
+    fn when_script_is_read_then_floors_and_cars_go_by_their_names() {
+        let building = Building::with_floor_count(8).unwrap();
+        let calls = parse_script("
+            # millis  call
+            500   car b 5
+            0     hall 3 down   # the first call
+            1200  dest G 6
+        ", &building).unwrap();
+
+        assert_eq!(calls, vec![
+            ScriptedCall { at: Duration::ZERO, call: Call::Hall { floor: 3, direction: CallDirection::Down } },
+            ScriptedCall { at: Duration::from_millis(500), call: Call::Car { car: 1, floor: 5 } },
+            ScriptedCall { at: Duration::from_millis(1200), call: Call::Destination { from_floor: 0, to_floor: 6 } }
+        ]);
+        assert!(parse_script("0 hall 9 up", &building).is_err());
+        assert!(parse_script("0 car C 1", &building).is_err());
+        assert!(parse_script("soon hall 1 up", &building).is_err());
+    }
+
+    #[test]
+    fn when_seed_is_the_same_then_traffic_is_the_same() {
+        let building = Building::with_floor_count(8).unwrap();
+        let traffic = random_traffic(&building, 50, Duration::from_secs(1), 7);
+
+        assert_eq!(traffic, random_traffic(&building, 50, Duration::from_secs(1), 7));
+        assert!(traffic.iter().all(|scripted| match scripted.call {
+            Call::Destination { from_floor, to_floor } => from_floor != to_floor && from_floor < 8 && to_floor < 8,
+            _ => false
+        }));
+        assert!(traffic.windows(2).all(|pair| pair[0].at <= pair[1].at));
+    }
+
+    #[test]
+    fn when_building_is_as_tall_as_can_be_then_traffic_stays_inside_it() {
+        let building = Building::with_floor_count(255).unwrap();
+        let traffic = random_traffic(&building, 500, Duration::from_secs(1), 7);
+
+        assert!(traffic.iter().all(|scripted| match scripted.call {
+            Call::Destination { from_floor, to_floor } => from_floor != to_floor && from_floor < 255 && to_floor < 255,
+            _ => false
+        }));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_script_is_run_then_every_call_is_answered_and_cars_go_out_of_service() {
+        let building = Building::with_floor_count(4).unwrap();
+        let operating_times = OperatingTimes {
+            pulley_step_interval: Duration::from_millis(10),
+            door_opening: Duration::from_millis(20),
+            door_dwell: Duration::from_millis(100),
+            door_closing: Duration::from_millis(20),
+            motion: BRISK_MOTION
+        };
+        let calls = parse_script("0 hall 3 down \n 50 dest G 2", &building).unwrap();
+        let run = HeadlessRun {
+            building,
+            operating_times,
+            strategy: String::from("look"),
+            calls,
+            passengers: Vec::new(),
+            time_limit: Duration::from_secs(30),
+            clock_mode: ClockMode::RealTime,
+            journal: None,
+            verbose: false
+        };
+
+        let mut out = Vec::new();
+        let summary = run.run(&mut out).await.unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(!summary.timed_out, "{}", out);
+        assert_eq!(summary.calls_made, 2);
+        assert_eq!(summary.hall_calls_answered, 1);
+        assert_eq!(summary.destinations_assigned, 1);
+        assert!(summary.cars.iter().map(|car| car.floors_travelled).sum::<usize>() >= 5, "{}", out);
+        assert!(out.contains("OpenTheDoor(3)") && out.contains("OpenTheDoor(2)"), "{}", out);
+        assert_eq!(out.matches("ElevatorOutOfService").count(), 2);
+    }
+
+    async fn hour_of_random_traffic(seed: u64) -> (RunSummary, String) {
+        let building = Building::with_floor_count(8).unwrap();
+        let run = HeadlessRun {
+            calls: random_traffic(&building, 120, Duration::from_secs(30), seed),
+            building,
+            operating_times: OperatingTimes::default(),
+            strategy: String::from("look"),
+            passengers: Vec::new(),
+            time_limit: Duration::from_secs(2 * 3600),
+            clock_mode: ClockMode::DiscreteEvent,
+            journal: None,
+            verbose: false
+        };
+
+        let mut out = Vec::new();
+        let summary = run.run(&mut out).await.unwrap();
+        (summary, String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_time_is_discrete_then_an_hour_of_traffic_is_over_at_once_and_alike_every_time() {
+        let wall = std::time::Instant::now();
+
+        let (summary, out) = hour_of_random_traffic(11).await;
+
+        assert!(!summary.timed_out, "{}", out);
+        assert_eq!(summary.destinations_assigned, 120);
+        assert!(summary.duration > Duration::from_secs(50 * 60));
+        assert!(wall.elapsed() < Duration::from_secs(10));
+        assert_eq!(hour_of_random_traffic(11).await, (summary, out));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_passengers_turn_up_at_morning_peak_then_every_one_of_them_is_delivered() {
+        let building = Building::with_floor_count(8).unwrap();
+        let run = HeadlessRun {
+            passengers: TrafficGenerator::new(TrafficPattern::UpPeak, Duration::from_secs(20), 5).passengers(&building, 60),
+            building,
+            operating_times: OperatingTimes::default(),
+            strategy: String::from("look"),
+            calls: Vec::new(),
+            time_limit: Duration::from_secs(2 * 3600),
+            clock_mode: ClockMode::DiscreteEvent,
+            journal: None,
+            verbose: false
+        };
+
+        let mut out = Vec::new();
+        let summary = run.run(&mut out).await.unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(!summary.timed_out, "{}", out);
+        assert_eq!(summary.passengers.len(), 60);
+        assert!(summary.passengers.iter().all(|passenger| {
+            passenger.arrived_at <= passenger.boarded_at.unwrap() && passenger.boarded_at < passenger.alighted_at
+        }), "{}", out);
+        assert!(summary.to_string().contains("passengers delivered: (60) of (60)"));
+
+        // Every hall call is a request, answered by a car that took its passengers where they went.
+        let service = &summary.service;
+        assert!(service.requests > 0 && service.answered == service.requests, "{}", summary);
+        assert_eq!(service.journey.count, service.requests, "{}", summary);
+        assert!(service.wait.max <= service.journey.max && service.round_trip.count > 0, "{}", summary);
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::app::AppResult;
+use crate::app_own_event::AppOwnEvent;
+use elevator_ratatui::building::Building;
+use elevator_ratatui::conversation::vocabulary::{CarIndex, ElevatorVocabulary, GroupVocabulary};
+use elevator_ratatui::elevator_installation::group_controller::OperatingTimes;
+
+// Bumped whenever a journal written before could no longer be read alike.
+pub const JOURNAL_SCHEMA_VERSION: u32 = 3;
+
+/// The first line of every journal: what the run was made of.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalHeader {
+    pub schema_version: u32,
+    pub building: Building,
+    pub strategy: String,
+    pub operating_times: OperatingTimes
+}
+
+impl JournalHeader {
+    pub fn new(building: Building, strategy: &str, operating_times: OperatingTimes) -> Self {
+        JournalHeader {
+            schema_version: JOURNAL_SCHEMA_VERSION,
+            building,
+            strategy: strategy.to_owned(),
+            operating_times
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JournalEntry {
+    Input(AppOwnEvent),                   // a key pressed, or a mouse clicked, by the operator
+    ToGroup(GroupVocabulary),             // what the operator has asked of the group of cars
+    FromCar(CarIndex, ElevatorVocabulary) // what a car has told its operator
+}
+
+/// Every line after the header: an entry, and when it was recorded (simulated time, since the start).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalLine {
+    pub at_ms: u64,
+    pub entry: JournalEntry
+}
+
+/// Records a run, one JSON object per line. Every line is written out as soon as it is recorded,
+/// so that the journal is complete up to the moment the application stopped, however it stopped.
+pub struct Journal {
+    writer: LineWriter<File>
+}
+
+impl Journal {
+    pub fn create(path: &Path, header: &JournalHeader) -> AppResult<Journal> {
+        let file = File::create(path)
+            .map_err(|e| format!("Cannot create journal ({}): {}", path.display(), e))?;
+        let mut journal = Journal { writer: LineWriter::new(file) };
+
+        writeln!(journal.writer, "{}", serde_json::to_string(header)?)?;
+        Ok(journal)
+    }
+
+    // A journal that cannot be written to anymore, must not bring the elevators down.
+    pub fn record(&mut self, at: Duration, entry: JournalEntry) {
+        let line = JournalLine { at_ms: at.as_millis() as u64, entry };
+        serde_json::to_string(&line)
+            .map_err(std::io::Error::from)
+            .and_then(|text| writeln!(self.writer, "{}", text))
+            .unwrap_or_else(|e| {
+                info!("Journal: cannot record ({:?}), {}", line.entry, e);
+            });
+    }
+}
+
+pub fn read_journal(path: &Path) -> AppResult<(JournalHeader, Vec<JournalLine>)> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read journal ({}): {}", path.display(), e))?;
+    parse_journal(&text)
+}
+
+// The schema version is checked first: a journal of another version is refused as such, rather
+// than for whatever it has come to hold.
+pub fn parse_journal(text: &str) -> AppResult<(JournalHeader, Vec<JournalLine>)> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, first_line) = lines.next().ok_or("The journal is empty")?;
+    let schema_version = serde_json::from_str::<serde_json::Value>(first_line)?
+        .get("schema_version")
+        .and_then(|version| version.as_u64())
+        .ok_or("The journal has no schema version")?;
+    if schema_version != JOURNAL_SCHEMA_VERSION as u64 {
+        return Err(format!("Journal schema version ({}) cannot be read, only ({})", schema_version, JOURNAL_SCHEMA_VERSION).into());
+    }
+    let header: JournalHeader = serde_json::from_str(first_line)?;
+
+    let entries = lines
+        .map(|(line_no, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Journal line ({}): {}", line_no + 1, e))
+        })
+        .collect::<Result<Vec<JournalLine>, String>>()?;
+
+    Ok((header, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+    use elevator_ratatui::conversation::vocabulary::{CallDirection, DoorState};
+
+    #[test]
+    fn when_journal_is_read_back_then_every_entry_is_as_recorded() {
+        let path = std::env::temp_dir().join(format!("journal-test-{}.jsonl", std::process::id()));
+        let header = JournalHeader::new(Building::with_floor_count(4).unwrap(), "look", OperatingTimes::default());
+        let entries = vec![
+            JournalEntry::Input(AppOwnEvent::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE))),
+            JournalEntry::ToGroup(GroupVocabulary::HallCall { floor: 3, direction: CallDirection::Down }),
+            JournalEntry::FromCar(1, ElevatorVocabulary::CurrentCarriagePosn(14_000)),
+            JournalEntry::FromCar(1, ElevatorVocabulary::DoorStateChanged(3, DoorState::Nudging))
+        ];
+
+        let mut journal = Journal::create(&path, &header).unwrap();
+        for (at, entry) in entries.iter().enumerate() {
+            journal.record(Duration::from_millis(100 * at as u64), entry.clone());
+        }
+        drop(journal);
+
+        let (header_read, lines) = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(header_read, header);
+        assert_eq!(lines.iter().map(|line| line.entry.clone()).collect::<Vec<_>>(), entries);
+        assert_eq!(lines[3].at_ms, 300);
+    }
+
+    #[test]
+    fn when_schema_version_is_another_then_journal_is_refused() {
+        let header = serde_json::to_string(&JournalHeader::new(Building::default(), "look", OperatingTimes::default()))
+            .unwrap()
+            .replace(r#""schema_version":3"#, r#""schema_version":99"#);
+
+        let refusal = parse_journal(&header).unwrap_err().to_string();
+
+        assert!(refusal.contains("(99)"), "{}", refusal);
+        assert!(parse_journal("").is_err());
+        assert!(parse_journal(r#"{"building": {}}"#).is_err());
+    }
+}
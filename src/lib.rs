@@ -0,0 +1,73 @@
+//! The elevator model behind the terminal simulator, for other tools to embed.
+//!
+//! A building ([`Building`]) has a group of cars, owned by a [`GroupControllerActor`]: it assigns
+//! every hall call to the car estimated to arrive there the soonest. Every car is an elevator
+//! ([`PassengerLiftActor`]) with a pulley ([`PulleyActor`]) and a door ([`DoorActor`]) of its own,
+//! each an actor speaking its own vocabulary. The elevator is driven by an [`ElevatorController`]:
+//! whatever it hears, is an input ([`ElevatorFSMInputs`]) to a finite-state machine
+//! ([`ElevatorStateMachine`]), whose states ([`ElevatorFSMStates`]) and outputs
+//! ([`ElevatorFSMOutputs`]) decide what the car does next. Every timer of the simulation is set on
+//! a [`SimulationClock`], which may run in real time, faster, or from one event to the next.
+//!
+//! Nothing that goes wrong in a car, panics: every part has an error of its own ([`ElevatorError`],
+//! [`PulleyError`], [`DoorError`], [`GroupError`]), handled by the [`FaultPolicy`] that the error
+//! calls for. A car that fails, is taken out of service; the other cars carry on.
+//!
+//! Whatever the cars tell their operator, reaches whoever spawned the group (on a channel), and
+//! every [`Observer`] that has subscribed to it:
+//!
+//! ```no_run
+//! use elevator_ratatui::{dispatch_strategy_named, Building, CallDirection, GroupControllerActor,
+//!     GroupVocabulary, OperatingTimes, SimulationClock};
+//! use ractor::Actor;
+//!
+//! # async fn example() -> elevator_ratatui::AppResult<()> {
+//! let building = Building::with_floor_count(6).unwrap();
+//! let strategies = (0..building.car_count())
+//!     .map(|_| dispatch_strategy_named("look"))
+//!     .collect::<Option<Vec<_>>>()
+//!     .ok_or("There is no such dispatch strategy")?;
+//! let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+//! let (group, _) = Actor::spawn(
+//!     Some(String::from("Group")),
+//!     GroupControllerActor,
+//!     (building, OperatingTimes::default(), SimulationClock::default(), strategies, Some(tx))
+//! ).await?;
+//!
+//! group.send_message(GroupVocabulary::PowerOn)?;
+//! group.send_message(GroupVocabulary::HallCall { floor: 3, direction: CallDirection::Down })?;
+//! while let Some((car, report)) = rx.recv().await {
+//!     println!("Car ({}): {:?}", car, report);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod building;
+pub mod conversation;
+pub mod elevator_installation;
+pub mod passenger;
+pub mod simulation_clock;
+
+use std::error;
+
+pub use building::{Building, RatedLoad};
+pub use conversation::vocabulary::{
+    CallDirection, CarIndex, CarLoad, DoorState, DoorVocabulary, ElevatorVocabulary, GroupVocabulary, Millimetres,
+    Observer, PulleyVocabulary,
+};
+pub use elevator_installation::carriage_machinery::CarriageData;
+pub use elevator_installation::dispatch_strategy::{dispatch_strategy_named, DispatchStrategy};
+pub use elevator_installation::door_machinery::{DoorActor, DoorError, DoorTimes};
+pub use elevator_installation::elevator_operations::{
+    ElevatorController, ElevatorError, ElevatorFSMInputs, ElevatorFSMOutputs, ElevatorFSMStates, ElevatorStateMachine,
+};
+pub use elevator_installation::fault_policy::FaultPolicy;
+pub use elevator_installation::elevator_service::PassengerLiftActor;
+pub use elevator_installation::group_controller::{GroupControllerActor, GroupError, OperatingTimes};
+pub use elevator_installation::motion_profile::MotionProfile;
+pub use elevator_installation::pulley_machinery::{PulleyActor, PulleyError};
+pub use elevator_installation::shaft::FloorLevels;
+pub use simulation_clock::{ClockMode, SimulationClock};
+
+pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
@@ -3,18 +3,29 @@ mod app;
 mod elevator_infra;
 mod tui_layout;
 mod ui;
-mod conversation;
-mod elevator_installation;
+mod ui_actor;
+mod door_widget;
 mod app_own_event;
+mod cli;
+mod headless;
+mod journal;
+mod metrics;
+mod replay;
 
 
 use std::error::Error;
 use std::io;
-use std::path::PathBuf;
+use std::path::Path;
 
 
 use app::App;
+use clap::Parser;
+use cli::CommandLine;
+use replay::Replay;
+use elevator_ratatui::ClockMode;
 use elevator_infra::ElevatorVisualInfra;
+use elevator_ratatui::FloorLevels;
+use elevator_ratatui::elevator_installation::state_diagram;
 use log::info;
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
@@ -25,11 +36,53 @@ use tui_layout::TuiLayout;
 use ui::DisplayManager;
 
 
-#[tokio::main(flavor = "multi_thread", worker_threads = 2)]
-async fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = CommandLine::parse();
 
+    if let Some(format) = &options.fsm_diagram {
+        print!("{}", state_diagram::diagram_in(format).ok_or(format!("Unknown diagram format ({})", format))?);
+        return Ok(());
+    }
 
-    initialize_logging()?;
+    // In discrete time, a timer fires as soon as there is nothing else left to do: the runtime
+    // must have a single thread, and its time must stand still in between.
+    let runtime =
+        if options.clock_mode() == ClockMode::DiscreteEvent {
+            tokio::runtime::Builder::new_current_thread().enable_all().start_paused(true).build()?
+        } else {
+            tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build()?
+        };
+
+    runtime.block_on(run(options))
+}
+
+async fn run(options: CommandLine) -> Result<(), Box<dyn Error>> {
+    initialize_logging(&options.log_file, options.log_level.as_deref())?;
+
+    // A recorded run is played again on the building it was recorded on.
+    let replay = match &options.replay {
+        Some(path) => {
+            let (header, lines) = journal::read_journal(path)?;
+            Some(Replay::new(header, lines))
+        },
+        None => None
+    };
+    let building = match &replay {
+        Some(replay) => replay.header.building.clone(),
+        None => options.building()?
+    };
+    info!("Building: ({}) floors, ({}) cars", building.floor_count(), building.car_count());
+
+    // Without a terminal, the calls are made by a script (or at random), and what the cars do is
+    // written out as it happens.
+    if options.headless {
+        let summary = options.headless_run(building)?.run(&mut io::stdout()).await?;
+        println!("\n{}", summary);
+        if let Some(path) = &options.metrics {
+            summary.service.write_to(path)?;
+        }
+        return Ok(());
+    }
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stdout());
@@ -39,40 +92,61 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     tui_layout.log_window_corners(); // For easier debugging.
 
+    // The shaft is drawn as the floors stand in it, however large the terminal is; the doors, as
+    // fast as they open and close.
+    let operating_times = match &replay {
+        Some(replay) => replay.header.operating_times,
+        None => options.operating_times()
+    };
     let floor_and_carriage_screen_segment = 
           ElevatorVisualInfra::new(
-            tui_layout.motion_window[tui_layout.motion_window_index as usize]);
-
-    let mut user_input = String::new();
-    println!("\nAll set. Press any key to start.\n");
-    let stdin = io::stdin(); // We get `Stdin` here.
-    stdin.read_line(&mut user_input).unwrap();      
+            tui_layout.motion_window[tui_layout.motion_window_index as usize],
+            &building,
+            FloorLevels::evenly_spaced(building.floor_count(), operating_times.motion.floor_height),
+            operating_times.door_times());
+
+    if !options.no_prompt {
+        let mut user_input = String::new();
+        println!("\nAll set. Press any key to start.\n");
+        let stdin = io::stdin(); // We get `Stdin` here.
+        stdin.read_line(&mut user_input)?;
+    }
 
      // Create an application. This is what holds everything together and runs the elevator.
      let mut  app = App::new(
                 floor_and_carriage_screen_segment, 
-                1.0, 
-                30.0,
+                &options,
                 terminal,
                 tui_layout,
-                DisplayManager::new())
-              .await;
+                DisplayManager::new(),
+                replay)
+              .await?;
 
     app.init()?;
 
-    app.start()?;
+    let (service, divergence) = app.run().await?;
 
-    app.run().await.unwrap();
+    if let Some(report) = divergence {
+        println!("\n{}", report);
+    }
 
+    if let Some(path) = &options.metrics {
+        service.write_to(path)?;
+    }
 
     Ok(())
 }
 
-pub fn initialize_logging() -> Result<(), Box<dyn Error>> {
-    let directory = PathBuf::from("./");
-    std::fs::create_dir_all(directory.clone())?;
-    let log_path = directory.join("elevator.log");
+// Without a level, RUST_LOG decides what is logged.
+pub fn initialize_logging(log_path: &Path, log_level: Option<&str>) -> Result<(), Box<dyn Error>> {
+    if let Some(directory) = log_path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+        std::fs::create_dir_all(directory)?;
+    }
     let log_file = std::fs::File::create(log_path)?;
+    let log_filter = match log_level {
+        Some(level) => tracing_subscriber::filter::EnvFilter::try_new(level)?,
+        None => tracing_subscriber::filter::EnvFilter::from_default_env()
+    };
    /*  std::env::set_var(
       "RUST_LOG",
       std::env::var("RUST_LOG")
@@ -85,7 +159,7 @@ pub fn initialize_logging() -> Result<(), Box<dyn Error>> {
       .with_writer(log_file)
       .with_target(false)
       .with_ansi(false)
-      .with_filter(tracing_subscriber::filter::EnvFilter::from_default_env());
+      .with_filter(log_filter);
     
     tracing_subscriber::registry()
     .with(file_subscriber)
@@ -0,0 +1,469 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::app::AppResult;
+use elevator_ratatui::building::Building;
+use elevator_ratatui::conversation::vocabulary::{CallDirection, CarIndex, ElevatorVocabulary, GroupVocabulary};
+use crate::journal::JournalEntry;
+
+// A passenger kept waiting longer than this at the landing, has waited too long.
+pub const LONG_WAIT: Duration = Duration::from_secs(60);
+
+/// A request for a ride: a hall call (or a destination call), from the moment it is made, until
+/// the car answering it opens its door at the floor the passenger is going to. The floor to go to
+/// is known from the start for a destination call; for a hall call, it is the first button
+/// pressed in the car that answered it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceRequest {
+    pub from_floor: u8,
+    pub direction: CallDirection,
+    pub to_floor: Option<u8>,
+    pub car: Option<CarIndex>,
+    pub called_at: Duration,
+    pub answered_at: Option<Duration>, // the car answering it sets off for the floor
+    pub reached_at: Option<Duration>,  // ... and opens its door there
+    pub departed_at: Option<Duration>, // ... and closes it, with the passenger on board
+    pub arrived_at: Option<Duration>   // the door opens at the floor to go to
+}
+
+impl ServiceRequest {
+    fn new(at: Duration, from_floor: u8, direction: CallDirection, to_floor: Option<u8>) -> Self {
+        ServiceRequest {
+            from_floor,
+            direction,
+            to_floor,
+            car: None,
+            called_at: at,
+            answered_at: None,
+            reached_at: None,
+            departed_at: None,
+            arrived_at: None
+        }
+    }
+
+    pub fn time_to_answer(&self) -> Option<Duration> {
+        self.answered_at.map(|at| at - self.called_at)
+    }
+
+    pub fn wait_time(&self) -> Option<Duration> {
+        self.reached_at.map(|at| at - self.called_at)
+    }
+
+    pub fn ride_time(&self) -> Option<Duration> {
+        self.departed_at.zip(self.arrived_at).map(|(departed_at, arrived_at)| arrived_at - departed_at)
+    }
+
+    pub fn journey_time(&self) -> Option<Duration> {
+        self.arrived_at.map(|at| at - self.called_at)
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.departed_at.is_none()
+    }
+
+    fn is_riding_in(&self, car: CarIndex) -> bool {
+        self.car == Some(car) && self.departed_at.is_some() && self.arrived_at.is_none()
+    }
+}
+
+// What the metrics keep of a car: where it has stopped last, and since when it is away from the lobby.
+#[derive(Debug, Default)]
+struct CarTrips {
+    set_off_for: Vec<(u8, Duration)>,    // the floors it has set off for, since it last stopped there
+    stopped_at: Option<(u8, Duration, Option<Duration>)>, // floor, door opened, when it set off for it
+    door_closed_at: Option<Duration>,
+    car_calls_at_stop: Vec<u8>,          // pressed since it stopped, and not yet taken by a request
+    left_lobby_at: Option<Duration>,
+    stopped_elsewhere: bool
+}
+
+/// Collects the service quality of a run, from the calls made, and what the cars tell their
+/// operator about them: everything that is journaled, as it is journaled.
+#[derive(Debug)]
+pub struct ServiceMetrics {
+    lobby_floor: u8,
+    requests: Vec<ServiceRequest>,
+    cars: Vec<CarTrips>,
+    round_trips: Vec<Duration>
+}
+
+impl ServiceMetrics {
+    pub fn new(building: &Building) -> Self {
+        ServiceMetrics {
+            lobby_floor: building.lobby_floor(),
+            requests: Vec::new(),
+            cars: (0..building.car_count()).map(|_| CarTrips::default()).collect(),
+            round_trips: Vec::new()
+        }
+    }
+
+    pub fn on_entry(&mut self, at: Duration, entry: &JournalEntry) {
+        match entry {
+            JournalEntry::ToGroup(GroupVocabulary::HallCall { floor, direction }) => self.on_hall_call(at, *floor, *direction),
+            JournalEntry::ToGroup(GroupVocabulary::DestinationCall { from_floor, to_floor }) if from_floor != to_floor => {
+                let direction = if to_floor > from_floor { CallDirection::Up } else { CallDirection::Down };
+                self.requests.push(ServiceRequest::new(at, *from_floor, direction, Some(*to_floor)));
+            },
+            JournalEntry::ToGroup(GroupVocabulary::ToCar(car, ElevatorVocabulary::CarCall { floor })) if *car < self.cars.len() =>
+                self.on_car_call(*car, *floor),
+            JournalEntry::FromCar(car, report) if *car < self.cars.len() => self.on_report(at, *car, report),
+            _ => {}
+        }
+    }
+
+    // Pressing a lit button again, makes no new request.
+    fn on_hall_call(&mut self, at: Duration, floor: u8, direction: CallDirection) {
+        let is_lit = self.requests
+            .iter()
+            .any(|request| request.is_waiting() && request.from_floor == floor && request.direction == direction && request.to_floor.is_none());
+        if !is_lit {
+            self.requests.push(ServiceRequest::new(at, floor, direction, None));
+        }
+    }
+
+    fn on_car_call(&mut self, car: CarIndex, floor: u8) {
+        self.cars[car].car_calls_at_stop.push(floor);
+        self.take_destinations(car);
+    }
+
+    fn on_door_closed(&mut self, at: Duration, car: CarIndex, floor: u8) {
+        let trips = &mut self.cars[car];
+        trips.door_closed_at = Some(at);
+
+        // A round trip: from leaving the lobby, until leaving it again, having stopped elsewhere.
+        if floor == self.lobby_floor {
+            if let (Some(left_at), true) = (trips.left_lobby_at, trips.stopped_elsewhere) {
+                self.round_trips.push(at - left_at);
+            }
+            trips.left_lobby_at = Some(at);
+            trips.stopped_elsewhere = false;
+        }
+    }
+
+    fn on_report(&mut self, at: Duration, car: CarIndex, report: &ElevatorVocabulary) {
+        match report {
+            ElevatorVocabulary::DoorClosed(floor) => self.on_door_closed(at, car, *floor),
+            ElevatorVocabulary::MovingTo(floor) => {
+                let trips = &mut self.cars[car];
+                if !trips.set_off_for.iter().any(|(set_off_for, _)| set_off_for == floor) {
+                    trips.set_off_for.push((*floor, at));
+                }
+            },
+            ElevatorVocabulary::OpenTheDoor(floor) => {
+                let trips = &mut self.cars[car];
+                let set_off_at = trips.set_off_for.iter().find(|(set_off_for, _)| set_off_for == floor).map(|(_, at)| *at);
+                trips.set_off_for.retain(|(set_off_for, _)| set_off_for != floor);
+                trips.stopped_at = Some((*floor, at, set_off_at));
+                trips.door_closed_at = None;
+                trips.car_calls_at_stop.clear();
+                trips.stopped_elsewhere |= *floor != self.lobby_floor;
+
+                self.requests
+                    .iter_mut()
+                    .filter(|request| request.is_riding_in(car) && request.to_floor == Some(*floor))
+                    .for_each(|request| request.arrived_at = Some(at));
+            },
+            ElevatorVocabulary::DestinationAssigned { from_floor, to_floor } => {
+                if let Some(request) = self.requests
+                    .iter_mut()
+                    .find(|request| request.car.is_none() && request.from_floor == *from_floor && request.to_floor == Some(*to_floor)) {
+                    request.car = Some(car);
+                }
+            },
+            ElevatorVocabulary::HallCallAnswered { floor, direction } => self.on_hall_call_answered(at, car, *floor, *direction),
+            // The kiosk presses the button for a passenger, as they board the car they were told to take.
+            ElevatorVocabulary::CarCall { floor: to_floor } => {
+                let boarding = self.requests
+                    .iter()
+                    .find(|request| request.is_waiting()
+                        && request.car == Some(car)
+                        && request.to_floor == Some(*to_floor)
+                        && self.cars[car].stopped_at.is_some_and(|(stopped_at, _, _)| stopped_at == request.from_floor))
+                    .map(|request| (request.from_floor, request.direction));
+                match boarding {
+                    Some((from_floor, direction)) => self.on_hall_call_answered(at, car, from_floor, direction),
+                    None => self.on_car_call(car, *to_floor)
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // The car leaves the floor with the passengers of every request waiting there, for its direction.
+    fn on_hall_call_answered(&mut self, at: Duration, car: CarIndex, floor: u8, direction: CallDirection) {
+        let trips = &self.cars[car];
+        let (reached_at, set_off_at) = match trips.stopped_at {
+            Some((stopped_at, opened_at, set_off_at)) if stopped_at == floor => (opened_at, set_off_at.unwrap_or(opened_at)),
+            _ => (at, at)
+        };
+        let departed_at = trips.door_closed_at.unwrap_or(at);
+
+        self.requests
+            .iter_mut()
+            .filter(|request| request.is_waiting()
+                && request.from_floor == floor
+                && request.direction == direction
+                && (request.car.is_none() || request.car == Some(car)))
+            .for_each(|request| {
+                request.car = Some(car);
+                request.answered_at = Some(set_off_at.max(request.called_at));
+                request.reached_at = Some(reached_at.max(request.called_at));
+                request.departed_at = Some(departed_at.max(request.called_at));
+            });
+
+        self.take_destinations(car);
+    }
+
+    // The buttons pressed in the car, go to the requests riding in it that have no floor to go to
+    // yet, in the order they were made.
+    fn take_destinations(&mut self, car: CarIndex) {
+        let car_calls = &mut self.cars[car].car_calls_at_stop;
+        for request in self.requests.iter_mut().filter(|request| request.is_riding_in(car) && request.to_floor.is_none()) {
+            let is_ahead = |floor: &u8| match request.direction {
+                CallDirection::Up => *floor > request.from_floor,
+                CallDirection::Down => *floor < request.from_floor
+            };
+            if let Some(index) = car_calls.iter().position(is_ahead) {
+                request.to_floor = Some(car_calls.remove(index));
+            }
+        }
+    }
+
+    /// The service quality so far: requests still waiting at `now` count among the long waits, if
+    /// they have waited long enough already.
+    pub fn report(&self, now: Duration) -> MetricsReport {
+        let distribution_of = |time_of: fn(&ServiceRequest) -> Option<Duration>| {
+            Distribution::of(&self.requests.iter().filter_map(time_of).collect::<Vec<Duration>>())
+        };
+
+        MetricsReport {
+            requests: self.requests.len(),
+            answered: self.requests.iter().filter(|request| request.reached_at.is_some()).count(),
+            delivered: self.requests.iter().filter(|request| request.arrived_at.is_some()).count(),
+            time_to_answer: distribution_of(ServiceRequest::time_to_answer),
+            wait: distribution_of(ServiceRequest::wait_time),
+            ride: distribution_of(ServiceRequest::ride_time),
+            journey: distribution_of(ServiceRequest::journey_time),
+            calls_over_60s: self.requests
+                .iter()
+                .filter(|request| request.wait_time().unwrap_or(now.saturating_sub(request.called_at)) > LONG_WAIT)
+                .count(),
+            round_trip: Distribution::of(&self.round_trips)
+        }
+    }
+}
+
+/// How a measure is spread, in seconds; the percentiles are by nearest rank.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64
+}
+
+impl Distribution {
+    pub fn of(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Distribution::default();
+        }
+
+        let mut sorted: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| sorted[((p / 100.0 * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+
+        Distribution {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: sorted[sorted.len() - 1]
+        }
+    }
+}
+
+/// The service quality of a run, as it is shown, and written out at shutdown.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MetricsReport {
+    pub requests: usize,
+    pub answered: usize,
+    pub delivered: usize,
+    pub time_to_answer: Distribution,
+    pub wait: Distribution,
+    pub ride: Distribution,
+    pub journey: Distribution,
+    pub calls_over_60s: usize,
+    pub round_trip: Distribution
+}
+
+impl MetricsReport {
+    pub fn measures(&self) -> [(&'static str, &Distribution); 5] {
+        [
+            ("answer", &self.time_to_answer),
+            ("wait", &self.wait),
+            ("ride", &self.ride),
+            ("journey", &self.journey),
+            ("round trip", &self.round_trip)
+        ]
+    }
+
+    // One line per measure, in CSV; or the whole report, in JSON if the file ends with '.json'.
+    pub fn write_to(&self, path: &Path) -> AppResult<()> {
+        let text = if path.extension().is_some_and(|extension| extension == "json") {
+            serde_json::to_string_pretty(self)?
+        } else {
+            let mut text = String::from("measure,count,mean_s,p50_s,p90_s,p99_s,max_s\n");
+            for (name, distribution) in self.measures() {
+                text.push_str(&format!("{},{},{:.3},{:.3},{:.3},{:.3},{:.3}\n",
+                    name.replace(' ', "_"),
+                    distribution.count,
+                    distribution.mean,
+                    distribution.p50,
+                    distribution.p90,
+                    distribution.p99,
+                    distribution.max));
+            }
+            text.push_str(&format!("calls_over_60s,{},,,,,\n", self.calls_over_60s));
+            text
+        };
+
+        std::fs::write(path, text).map_err(|e| format!("Cannot write metrics ({}): {}", path.display(), e).into())
+    }
+}
+
+impl fmt::Display for MetricsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Service: ({}) requests, ({}) answered, ({}) delivered, ({}) waited over {}s",
+            self.requests,
+            self.answered,
+            self.delivered,
+            self.calls_over_60s,
+            LONG_WAIT.as_secs())?;
+        writeln!(f, "  {:<10} {:>5} {:>8} {:>8} {:>8} {:>8} {:>8}", "(seconds)", "n", "mean", "p50", "p90", "p99", "max")?;
+        for (name, distribution) in self.measures() {
+            writeln!(f, "  {:<10} {:>5} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1}",
+                name,
+                distribution.count,
+                distribution.mean,
+                distribution.p50,
+                distribution.p90,
+                distribution.p99,
+                distribution.max)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn command(metrics: &mut ServiceMetrics, at: u64, command: GroupVocabulary) {
+        metrics.on_entry(secs(at), &JournalEntry::ToGroup(command));
+    }
+
+    fn report(metrics: &mut ServiceMetrics, at: u64, car: CarIndex, report: ElevatorVocabulary) {
+        metrics.on_entry(secs(at), &JournalEntry::FromCar(car, report));
+    }
+
+    #[test]
+    fn when_samples_are_spread_then_percentiles_are_by_nearest_rank() {
+        let samples: Vec<Duration> = (1..=100).rev().map(secs).collect();
+
+        let distribution = Distribution::of(&samples);
+
+        assert_eq!(distribution.count, 100);
+        assert_eq!((distribution.mean, distribution.p50, distribution.p90, distribution.p99, distribution.max), (50.5, 50.0, 90.0, 99.0, 100.0));
+        assert_eq!(Distribution::of(&[secs(7)]).p99, 7.0);
+        assert_eq!(Distribution::of(&[]), Distribution::default());
+    }
+
+    #[test]
+    fn when_a_hall_call_is_served_then_its_times_are_taken_from_the_car_reports() {
+        let metrics = &mut ServiceMetrics::new(&Building::with_floor_count(8).unwrap());
+
+        command(metrics, 10, GroupVocabulary::HallCall { floor: 3, direction: CallDirection::Up });
+        command(metrics, 11, GroupVocabulary::HallCall { floor: 3, direction: CallDirection::Up });
+        report(metrics, 12, 1, ElevatorVocabulary::MovingTo(3));
+        report(metrics, 20, 1, ElevatorVocabulary::OpenTheDoor(3));
+        command(metrics, 22, GroupVocabulary::ToCar(1, ElevatorVocabulary::CarCall { floor: 6 }));
+        report(metrics, 25, 1, ElevatorVocabulary::DoorClosed(3));
+        report(metrics, 25, 1, ElevatorVocabulary::HallCallAnswered { floor: 3, direction: CallDirection::Up });
+        report(metrics, 25, 1, ElevatorVocabulary::MovingTo(6));
+        report(metrics, 34, 1, ElevatorVocabulary::OpenTheDoor(6));
+
+        let request = &metrics.requests[0];
+        assert_eq!(metrics.requests.len(), 1);
+        assert_eq!((request.car, request.to_floor), (Some(1), Some(6)));
+        assert_eq!(request.time_to_answer(), Some(secs(2)));
+        assert_eq!(request.wait_time(), Some(secs(10)));
+        assert_eq!(request.ride_time(), Some(secs(9)));
+        assert_eq!(request.journey_time(), Some(secs(24)));
+
+        let report = metrics.report(secs(40));
+        assert_eq!((report.requests, report.answered, report.delivered, report.calls_over_60s), (1, 1, 1, 0));
+        assert_eq!(report.journey.max, 24.0);
+    }
+
+    #[test]
+    fn when_a_destination_call_is_served_then_the_passenger_boards_as_the_kiosk_presses_the_button() {
+        let metrics = &mut ServiceMetrics::new(&Building::with_floor_count(8).unwrap());
+
+        command(metrics, 0, GroupVocabulary::DestinationCall { from_floor: 5, to_floor: 0 });
+        report(metrics, 0, 1, ElevatorVocabulary::DestinationAssigned { from_floor: 5, to_floor: 0 });
+        report(metrics, 0, 1, ElevatorVocabulary::MovingTo(5));
+        report(metrics, 4, 1, ElevatorVocabulary::OpenTheDoor(5));
+        report(metrics, 6, 1, ElevatorVocabulary::DoorClosed(5));
+        report(metrics, 6, 1, ElevatorVocabulary::CarCall { floor: 0 });
+        report(metrics, 11, 1, ElevatorVocabulary::OpenTheDoor(0));
+
+        let request = &metrics.requests[0];
+        assert_eq!(request.time_to_answer(), Some(secs(0)));
+        assert_eq!(request.wait_time(), Some(secs(4)));
+        assert_eq!(request.ride_time(), Some(secs(5)));
+        assert_eq!(metrics.report(secs(11)).delivered, 1);
+    }
+
+    #[test]
+    fn when_a_call_waits_too_long_then_it_is_counted_even_before_it_is_answered() {
+        let metrics = &mut ServiceMetrics::new(&Building::with_floor_count(8).unwrap());
+
+        command(metrics, 0, GroupVocabulary::DestinationCall { from_floor: 2, to_floor: 0 });
+        report(metrics, 1, 0, ElevatorVocabulary::DestinationAssigned { from_floor: 2, to_floor: 0 });
+
+        assert_eq!(metrics.report(secs(30)).calls_over_60s, 0);
+        assert_eq!(metrics.report(secs(61)).calls_over_60s, 1);
+
+        // Another car, stopping there, does not take a passenger told to take car A.
+        report(metrics, 70, 1, ElevatorVocabulary::OpenTheDoor(2));
+        report(metrics, 72, 1, ElevatorVocabulary::HallCallAnswered { floor: 2, direction: CallDirection::Down });
+        assert_eq!(metrics.report(secs(72)).answered, 0);
+    }
+
+    #[test]
+    fn when_a_car_leaves_the_lobby_again_then_its_round_trip_is_taken() {
+        let metrics = &mut ServiceMetrics::new(&Building::with_floor_count(8).unwrap());
+
+        report(metrics, 5, 0, ElevatorVocabulary::DoorClosed(0));
+        report(metrics, 20, 0, ElevatorVocabulary::OpenTheDoor(4));
+        report(metrics, 22, 0, ElevatorVocabulary::DoorClosed(4));
+        report(metrics, 40, 0, ElevatorVocabulary::OpenTheDoor(0));
+        report(metrics, 45, 0, ElevatorVocabulary::DoorClosed(0));
+        // Re-opening at the lobby, without going anywhere, is no round trip.
+        report(metrics, 46, 0, ElevatorVocabulary::OpenTheDoor(0));
+        report(metrics, 50, 0, ElevatorVocabulary::DoorClosed(0));
+
+        let round_trip = metrics.report(secs(50)).round_trip;
+        assert_eq!((round_trip.count, round_trip.max), (1, 40.0));
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::building::{Building, RatedLoad};
+use crate::conversation::vocabulary::{CallDirection, CarIndex, CarLoad, ElevatorVocabulary, GroupVocabulary};
+
+pub type PassengerId = usize;
+
+// Unless told otherwise, every passenger weighs as much.
+pub const AVERAGE_PASSENGER_KG: u32 = 75;
+
+pub const TRAFFIC_PATTERN_NAMES: [&str; 4] = ["up-peak", "down-peak", "lunch", "inter-floor"];
+
+/// Somebody who turns up at a floor, takes a car, and gets off at another floor. The times are
+/// counted from the start of the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Passenger {
+    pub id: PassengerId,
+    pub origin: u8,
+    pub destination: u8,
+    pub weight_kg: u32,
+    pub arrived_at: Duration,
+    pub boarded_at: Option<Duration>,
+    pub alighted_at: Option<Duration>,
+    pub car: Option<CarIndex>
+}
+
+impl Passenger {
+    pub fn new(id: PassengerId, origin: u8, destination: u8, arrived_at: Duration) -> Self {
+        Passenger {
+            id,
+            origin,
+            destination,
+            weight_kg: AVERAGE_PASSENGER_KG,
+            arrived_at,
+            boarded_at: None,
+            alighted_at: None,
+            car: None
+        }
+    }
+
+    pub fn direction(&self) -> CallDirection {
+        if self.destination > self.origin { CallDirection::Up } else { CallDirection::Down }
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.boarded_at.is_none()
+    }
+
+    pub fn is_riding(&self) -> bool {
+        self.boarded_at.is_some() && self.alighted_at.is_none()
+    }
+
+    pub fn has_arrived(&self) -> bool {
+        self.alighted_at.is_some()
+    }
+}
+
+/// Where the passengers of the building want to go: mostly up from the lobby in the morning,
+/// mostly down to it in the evening, both ways at lunch, or between any two floors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrafficPattern {
+    UpPeak,
+    DownPeak,
+    Lunch,
+    InterFloor
+}
+
+pub fn traffic_pattern_named(name: &str) -> Option<TrafficPattern> {
+    match name {
+        "up-peak" => Some(TrafficPattern::UpPeak),
+        "down-peak" => Some(TrafficPattern::DownPeak),
+        "lunch" => Some(TrafficPattern::Lunch),
+        "inter-floor" => Some(TrafficPattern::InterFloor),
+        _ => None
+    }
+}
+
+/// Passengers turn up as a Poisson process: `mean_interval` apart on average, and independently
+/// of each other. The same seed gives the same passengers.
+pub struct TrafficGenerator {
+    pattern: TrafficPattern,
+    mean_interval: Duration,
+    rng: StdRng
+}
+
+impl TrafficGenerator {
+    pub fn new(pattern: TrafficPattern, mean_interval: Duration, seed: u64) -> Self {
+        TrafficGenerator { pattern, mean_interval, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn passengers(&mut self, building: &Building, count: usize) -> Vec<Passenger> {
+        let mut at = Duration::ZERO;
+        (0..count)
+            .map(|id| {
+                at += self.next_interval();
+                let (origin, destination) = self.next_trip(building);
+                Passenger { weight_kg: self.rng.gen_range(50..=110), ..Passenger::new(id, origin, destination, at) }
+            })
+            .collect()
+    }
+
+    // Between the arrivals of a Poisson process, the time is exponentially distributed.
+    fn next_interval(&mut self) -> Duration {
+        let uniform: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        self.mean_interval.mul_f64(-uniform.ln())
+    }
+
+    fn next_trip(&mut self, building: &Building) -> (u8, u8) {
+        let lobby = building.lobby_floor();
+        let share_via_lobby = match self.pattern {
+            TrafficPattern::UpPeak | TrafficPattern::DownPeak => 0.85,
+            TrafficPattern::Lunch => 0.9,
+            TrafficPattern::InterFloor => 0.0
+        };
+
+        if !self.rng.gen_bool(share_via_lobby) {
+            let origin = self.any_floor_but(building, None);
+            return (origin, self.any_floor_but(building, Some(origin)));
+        }
+
+        let other_floor = self.any_floor_but(building, Some(lobby));
+        let going_up_from_lobby = match self.pattern {
+            TrafficPattern::UpPeak => true,
+            TrafficPattern::DownPeak => false,
+            _ => self.rng.gen_bool(0.5)
+        };
+        if going_up_from_lobby { (lobby, other_floor) } else { (other_floor, lobby) }
+    }
+
+    // The sum is taken in u16: near the top of a 255-floor building, it does not fit in a u8.
+    fn any_floor_but(&mut self, building: &Building, but: Option<u8>) -> u8 {
+        let floor_count = building.floor_count();
+        let floor = match but {
+            Some(but) => (but as u16 + self.rng.gen_range(1..floor_count)) % floor_count,
+            None => self.rng.gen_range(0..floor_count)
+        };
+        floor as u8
+    }
+}
+
+/// What a passenger does, as a person in the building would: presses the hall button on turning
+/// up (unless it is lit already), boards the car that answers it (unless it is full), presses the
+/// button of the destination inside, and gets off when the door opens there. Whenever passengers
+/// board or alight, the load-weighing device of the car reads its load anew.
+#[derive(Debug)]
+pub struct PassengerFlow {
+    pub passengers: Vec<Passenger>,
+    rated_load: RatedLoad,
+    lit_hall_buttons: HashSet<(u8, CallDirection)>,
+    weighed_cars: Vec<CarIndex>
+}
+
+/// A button a passenger presses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ButtonPressed {
+    Hall { floor: u8, direction: CallDirection },
+    Car { car: CarIndex, floor: u8 }
+}
+
+impl ButtonPressed {
+    pub fn to_group_message(self) -> GroupVocabulary {
+        match self {
+            ButtonPressed::Hall { floor, direction } => GroupVocabulary::HallCall { floor, direction },
+            ButtonPressed::Car { car, floor } => GroupVocabulary::ToCar(car, ElevatorVocabulary::CarCall { floor })
+        }
+    }
+}
+
+impl PassengerFlow {
+    pub fn new(passengers: Vec<Passenger>, rated_load: RatedLoad) -> Self {
+        PassengerFlow { passengers, rated_load, lit_hall_buttons: HashSet::new(), weighed_cars: Vec::new() }
+    }
+
+    pub fn load_of(&self, car: CarIndex) -> CarLoad {
+        self.passengers
+            .iter()
+            .filter(|passenger| passenger.is_riding() && passenger.car == Some(car))
+            .fold(CarLoad::default(), |load, passenger| CarLoad {
+                persons: load.persons + 1,
+                kg: load.kg + passenger.weight_kg
+            })
+    }
+
+    // The cars whose load has changed, since this was asked last, with their load now.
+    pub fn take_weighed_loads(&mut self) -> Vec<(CarIndex, CarLoad)> {
+        std::mem::take(&mut self.weighed_cars)
+            .into_iter()
+            .map(|car| (car, self.load_of(car)))
+            .collect()
+    }
+
+    fn on_load_changed(&mut self, car: CarIndex) {
+        if !self.weighed_cars.contains(&car) {
+            self.weighed_cars.push(car);
+        }
+    }
+
+    pub fn next_arrival_at(&self, next: PassengerId) -> Option<Duration> {
+        self.passengers.get(next).map(|passenger| passenger.arrived_at)
+    }
+
+    pub fn on_arrival(&mut self, id: PassengerId) -> Option<ButtonPressed> {
+        let passenger = &self.passengers[id];
+        let button = (passenger.origin, passenger.direction());
+
+        self.lit_hall_buttons
+            .insert(button)
+            .then_some(ButtonPressed::Hall { floor: button.0, direction: button.1 })
+    }
+
+    pub fn on_report(&mut self, at: Duration, car: CarIndex, report: &ElevatorVocabulary) -> Vec<ButtonPressed> {
+        match report {
+            ElevatorVocabulary::HallCallAnswered { floor, direction } => {
+                self.lit_hall_buttons.remove(&(*floor, *direction));
+
+                // In the order they have turned up, for as long as there is room in the car.
+                let mut load = self.load_of(car);
+                let mut buttons = Vec::new();
+                for passenger in self.passengers.iter_mut() {
+                    let is_waiting_here = passenger.is_waiting()
+                        && passenger.arrived_at <= at
+                        && passenger.origin == *floor
+                        && passenger.direction() == *direction;
+                    if !is_waiting_here {
+                        continue;
+                    }
+
+                    let load_on_boarding = CarLoad { persons: load.persons + 1, kg: load.kg + passenger.weight_kg };
+                    let button = if self.rated_load.is_overloaded(&load_on_boarding) {
+                        // Left behind: the button is pressed again, for the next car.
+                        ButtonPressed::Hall { floor: *floor, direction: *direction }
+                    } else {
+                        load = load_on_boarding;
+                        passenger.boarded_at = Some(at);
+                        passenger.car = Some(car);
+                        ButtonPressed::Car { car, floor: passenger.destination }
+                    };
+                    if !buttons.contains(&button) {
+                        buttons.push(button);
+                    }
+                }
+
+                if buttons.iter().any(|button| matches!(button, ButtonPressed::Car { .. })) {
+                    self.on_load_changed(car);
+                }
+                if buttons.iter().any(|button| matches!(button, ButtonPressed::Hall { .. })) {
+                    self.lit_hall_buttons.insert((*floor, *direction));
+                }
+                buttons
+            },
+            ElevatorVocabulary::OpenTheDoor(floor) => {
+                let mut anybody_alighted = false;
+                self.passengers
+                    .iter_mut()
+                    .filter(|passenger| passenger.is_riding() && passenger.car == Some(car) && passenger.destination == *floor)
+                    .for_each(|passenger| {
+                        passenger.alighted_at = Some(at);
+                        anybody_alighted = true;
+                    });
+                if anybody_alighted {
+                    self.on_load_changed(car);
+                }
+                Vec::new()
+            },
+            _ => Vec::new()
+        }
+    }
+
+    // Whether everybody who has turned up so far, has got where they wanted to go.
+    pub fn is_everybody_delivered(&self, turned_up: usize) -> bool {
+        self.passengers.iter().take(turned_up).all(Passenger::has_arrived)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_seed_is_the_same_then_passengers_are_the_same() {
+        let building = Building::with_floor_count(10).unwrap();
+        let passengers = TrafficGenerator::new(TrafficPattern::Lunch, Duration::from_secs(5), 3).passengers(&building, 200);
+
+        assert_eq!(passengers, TrafficGenerator::new(TrafficPattern::Lunch, Duration::from_secs(5), 3).passengers(&building, 200));
+        assert!(passengers.iter().all(|passenger| passenger.origin != passenger.destination && passenger.destination < 10));
+        assert!(passengers.windows(2).all(|pair| pair[0].arrived_at <= pair[1].arrived_at));
+
+        // Over many arrivals, the mean interval is about as asked.
+        let mean_interval = passengers.last().unwrap().arrived_at / 200;
+        assert!(mean_interval > Duration::from_secs(4) && mean_interval < Duration::from_secs(6), "{:?}", mean_interval);
+    }
+
+    #[test]
+    fn when_building_is_as_tall_as_can_be_then_passengers_stay_inside_it() {
+        let building = Building::with_floor_count(255).unwrap();
+        let passengers = TrafficGenerator::new(TrafficPattern::InterFloor, Duration::from_secs(5), 5).passengers(&building, 1000);
+
+        assert!(passengers.iter().all(|passenger| passenger.origin != passenger.destination && passenger.destination < 255));
+    }
+
+    #[test]
+    fn when_it_is_up_peak_then_most_passengers_leave_the_lobby() {
+        let building = Building::with_floor_count(10).unwrap();
+        let from_lobby = |pattern| TrafficGenerator::new(pattern, Duration::from_secs(5), 11)
+            .passengers(&building, 1000)
+            .iter()
+            .filter(|passenger| passenger.origin == building.lobby_floor())
+            .count();
+
+        assert!(from_lobby(TrafficPattern::UpPeak) > 800);
+        assert!(from_lobby(TrafficPattern::DownPeak) < 100);
+        assert!(from_lobby(TrafficPattern::InterFloor) < 200);
+    }
+
+    #[test]
+    fn when_car_answers_the_hall_call_then_passengers_board_and_press_their_floors() {
+        let mut flow = PassengerFlow::new(vec![
+            Passenger::new(0, 0, 3, Duration::from_secs(1)),
+            Passenger::new(1, 0, 5, Duration::from_secs(2)),
+            Passenger::new(2, 4, 1, Duration::from_secs(3))
+        ], Building::default().rated_load());
+
+        assert_eq!(flow.on_arrival(0), Some(ButtonPressed::Hall { floor: 0, direction: CallDirection::Up }));
+        assert_eq!(flow.on_arrival(1), None); // the button is lit already
+        assert_eq!(flow.on_arrival(2), Some(ButtonPressed::Hall { floor: 4, direction: CallDirection::Down }));
+
+        let answered = ElevatorVocabulary::HallCallAnswered { floor: 0, direction: CallDirection::Up };
+        assert_eq!(flow.on_report(Duration::from_secs(4), 1, &answered), vec![
+            ButtonPressed::Car { car: 1, floor: 3 },
+            ButtonPressed::Car { car: 1, floor: 5 }
+        ]);
+        assert!(flow.passengers[0].is_riding() && flow.passengers[2].is_waiting());
+        assert_eq!(flow.take_weighed_loads(), vec![(1, CarLoad { persons: 2, kg: 2 * AVERAGE_PASSENGER_KG })]);
+
+        flow.on_report(Duration::from_secs(9), 0, &ElevatorVocabulary::OpenTheDoor(3));
+        assert!(!flow.passengers[0].has_arrived()); // another car
+        flow.on_report(Duration::from_secs(10), 1, &ElevatorVocabulary::OpenTheDoor(3));
+        assert_eq!(flow.passengers[0].alighted_at, Some(Duration::from_secs(10)));
+        assert_eq!(flow.take_weighed_loads(), vec![(1, CarLoad { persons: 1, kg: AVERAGE_PASSENGER_KG })]);
+        assert!(!flow.is_everybody_delivered(3));
+    }
+
+    #[test]
+    fn when_car_is_full_then_passengers_left_behind_press_the_button_again() {
+        let rated_load = RatedLoad { persons: 2, kg: 1000 };
+        let mut flow = PassengerFlow::new((0..3).map(|id| Passenger::new(id, 2, 0, Duration::ZERO)).collect(), rated_load);
+        (0..3).for_each(|id| { flow.on_arrival(id); });
+
+        let answered = ElevatorVocabulary::HallCallAnswered { floor: 2, direction: CallDirection::Down };
+        assert_eq!(flow.on_report(Duration::from_secs(5), 0, &answered), vec![
+            ButtonPressed::Car { car: 0, floor: 0 },
+            ButtonPressed::Hall { floor: 2, direction: CallDirection::Down }
+        ]);
+        assert!(flow.passengers[2].is_waiting());
+        assert_eq!(flow.load_of(0), CarLoad { persons: 2, kg: 2 * AVERAGE_PASSENGER_KG });
+        assert_eq!(flow.on_arrival(2), None); // lit again
+    }
+}
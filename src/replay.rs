@@ -0,0 +1,280 @@
+use std::fmt;
+use std::time::Duration;
+
+use elevator_ratatui::conversation::vocabulary::{CarIndex, ElevatorVocabulary, GroupVocabulary};
+use elevator_ratatui::elevator_installation::group_controller::car_letter;
+use crate::journal::{JournalEntry, JournalHeader, JournalLine};
+
+// While seeking, the run is played this many times faster than real time.
+pub const SEEKING_SPEED: f64 = 50.0;
+
+const SLOWEST_SPEED: f64 = 0.125;
+const FASTEST_SPEED: f64 = 64.0;
+
+// Carriage positions are left out of the comparison: a car that stops at the same floors, at
+// about the same times, has run alike.
+fn is_compared(report: &ElevatorVocabulary) -> bool {
+    !matches!(report, ElevatorVocabulary::CurrentCarriagePosn(_))
+}
+
+/// A recorded run, played again: the commands of the operator are fed to a fresh group of cars,
+/// when they were given in the recording. What the cars tell their operator this time, is
+/// compared with what they told it then.
+#[derive(Debug)]
+pub struct Replay {
+    pub header: JournalHeader,
+    commands: Vec<(Duration, GroupVocabulary)>,
+    recorded_reports: Vec<Vec<(Duration, ElevatorVocabulary)>>,
+    live_reports: Vec<Vec<(Duration, ElevatorVocabulary)>>,
+    next_command: usize,
+    ends_at: Duration,
+    pub speed: f64,       // when not paused
+    pub stepping: bool,   // pauses at the next event
+    pub seeking_to: Option<Duration>,
+    pub seek_entry: Option<String>,
+    pub has_ended: bool
+}
+
+impl Replay {
+    pub fn new(header: JournalHeader, lines: Vec<JournalLine>) -> Self {
+        let car_count = header.building.car_count();
+        let mut commands = Vec::new();
+        let mut recorded_reports = vec![Vec::new(); car_count];
+
+        for line in lines.iter() {
+            let at = Duration::from_millis(line.at_ms);
+            match &line.entry {
+                JournalEntry::ToGroup(command) => commands.push((at, command.clone())),
+                JournalEntry::FromCar(car, report) if *car < car_count && is_compared(report) =>
+                    recorded_reports[*car].push((at, report.clone())),
+                _ => {}
+            }
+        }
+
+        Replay {
+            header,
+            commands,
+            recorded_reports,
+            live_reports: vec![Vec::new(); car_count],
+            next_command: 0,
+            ends_at: lines.last().map_or(Duration::ZERO, |line| Duration::from_millis(line.at_ms)),
+            speed: 1.0,
+            stepping: false,
+            seeking_to: None,
+            seek_entry: None,
+            has_ended: false
+        }
+    }
+
+    pub fn ends_at(&self) -> Duration {
+        self.ends_at
+    }
+
+    // Where the playing is to be paused next: where it was sought, or else the end of the recording.
+    pub fn stop_at(&self) -> Option<Duration> {
+        match (self.seeking_to, self.has_ended) {
+            (Some(to), false) => Some(to.min(self.ends_at)),
+            (Some(to), true) => Some(to),
+            (None, false) => Some(self.ends_at),
+            (None, true) => None
+        }
+    }
+
+    // Played again from the start, by a fresh group of cars.
+    pub fn start_over(&mut self) {
+        self.next_command = 0;
+        self.has_ended = false;
+        self.live_reports.iter_mut().for_each(|reports| reports.clear());
+    }
+
+    pub fn next_command_at(&self) -> Option<Duration> {
+        self.commands.get(self.next_command).map(|(at, _)| *at)
+    }
+
+    pub fn take_next_command(&mut self) -> Option<GroupVocabulary> {
+        let command = self.commands.get(self.next_command).map(|(_, command)| command.clone());
+        self.next_command += 1;
+        command
+    }
+
+    // Whether the report counts as an event (for stepping, and for the comparison).
+    pub fn on_live_report(&mut self, at: Duration, car: CarIndex, report: &ElevatorVocabulary) -> bool {
+        if car >= self.live_reports.len() || !is_compared(report) {
+            return false;
+        }
+        self.live_reports[car].push((at, report.clone()));
+        true
+    }
+
+    pub fn faster(&mut self) -> f64 {
+        self.speed = (self.speed * 2.0).min(FASTEST_SPEED);
+        self.speed
+    }
+
+    pub fn slower(&mut self) -> f64 {
+        self.speed = (self.speed / 2.0).max(SLOWEST_SPEED);
+        self.speed
+    }
+
+    pub fn divergence(&self) -> DivergenceReport {
+        DivergenceReport {
+            cars: self.recorded_reports
+                .iter()
+                .zip(self.live_reports.iter())
+                .map(|(recorded, live)| compare(recorded, live))
+                .collect()
+        }
+    }
+}
+
+/// How the reports of a car, played again, compare with the recorded ones.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CarDivergence {
+    AsRecorded { events: usize, largest_time_difference: Duration },
+    // The first report that is not as recorded; `None` when there is no report at all.
+    Diverged {
+        event: usize,
+        recorded: Option<(Duration, ElevatorVocabulary)>,
+        live: Option<(Duration, ElevatorVocabulary)>
+    }
+}
+
+fn compare(recorded: &[(Duration, ElevatorVocabulary)], live: &[(Duration, ElevatorVocabulary)]) -> CarDivergence {
+    let first_difference = (0..recorded.len().max(live.len()))
+        .find(|event| recorded.get(*event).map(|(_, report)| report) != live.get(*event).map(|(_, report)| report));
+
+    match first_difference {
+        Some(event) => CarDivergence::Diverged {
+            event,
+            recorded: recorded.get(event).cloned(),
+            live: live.get(event).cloned()
+        },
+        None => CarDivergence::AsRecorded {
+            events: recorded.len(),
+            largest_time_difference: recorded
+                .iter()
+                .zip(live.iter())
+                .map(|((recorded_at, _), (live_at, _))| recorded_at.abs_diff(*live_at))
+                .max()
+                .unwrap_or(Duration::ZERO)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DivergenceReport {
+    pub cars: Vec<CarDivergence>
+}
+
+impl DivergenceReport {
+    pub fn is_as_recorded(&self) -> bool {
+        self.cars.iter().all(|car| matches!(car, CarDivergence::AsRecorded { .. }))
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let describe = |report: &Option<(Duration, ElevatorVocabulary)>| match report {
+            Some((at, report)) => format!("{:?} at ({:.3})s", report, at.as_secs_f64()),
+            None => String::from("nothing")
+        };
+
+        self.cars
+            .iter()
+            .enumerate()
+            .map(|(car, divergence)| match divergence {
+                CarDivergence::AsRecorded { events, largest_time_difference } =>
+                    format!("Car {}: ({}) events as recorded, ({})ms apart at most.",
+                        car_letter(car), events, largest_time_difference.as_millis()),
+                CarDivergence::Diverged { event, recorded, live } =>
+                    format!("Car {}: diverged at event ({}): recorded {}, live {}.",
+                        car_letter(car), event + 1, describe(recorded), describe(live))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Replay: {}", if self.is_as_recorded() { "as recorded." } else { "diverged from the recording!" })?;
+        for line in self.lines() {
+            writeln!(f, "  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elevator_ratatui::building::Building;
+    use elevator_ratatui::conversation::vocabulary::CallDirection;
+    use elevator_ratatui::elevator_installation::group_controller::OperatingTimes;
+
+    fn line(at_ms: u64, entry: JournalEntry) -> JournalLine {
+        JournalLine { at_ms, entry }
+    }
+
+    fn recording() -> Replay {
+        Replay::new(
+            JournalHeader::new(Building::with_floor_count(4).unwrap(), "look", OperatingTimes::default()),
+            vec![
+                line(0, JournalEntry::ToGroup(GroupVocabulary::PowerOn)),
+                line(0, JournalEntry::FromCar(0, ElevatorVocabulary::MoveToHomeFloor(0))),
+                line(900, JournalEntry::ToGroup(GroupVocabulary::HallCall { floor: 2, direction: CallDirection::Up })),
+                line(900, JournalEntry::FromCar(1, ElevatorVocabulary::MovingTo(2))),
+                line(1000, JournalEntry::FromCar(1, ElevatorVocabulary::CurrentCarriagePosn(2_500))),
+                line(1800, JournalEntry::FromCar(1, ElevatorVocabulary::OpenTheDoor(2))),
+                line(3800, JournalEntry::ToGroup(GroupVocabulary::ToCar(1, ElevatorVocabulary::DoorCloseButton))),
+                line(4800, JournalEntry::FromCar(1, ElevatorVocabulary::DoorClosed(2)))
+            ]
+        )
+    }
+
+    #[test]
+    fn when_replayed_then_commands_are_fed_when_they_were_given() {
+        let replay = &mut recording();
+
+        assert_eq!(replay.ends_at(), Duration::from_millis(4800));
+        assert_eq!(replay.next_command_at(), Some(Duration::ZERO));
+        assert_eq!(replay.take_next_command(), Some(GroupVocabulary::PowerOn));
+        assert_eq!(replay.next_command_at(), Some(Duration::from_millis(900)));
+        assert!(replay.take_next_command().is_some());
+        assert_eq!(replay.next_command_at(), Some(Duration::from_millis(3800)));
+        assert_eq!(replay.take_next_command(), Some(GroupVocabulary::ToCar(1, ElevatorVocabulary::DoorCloseButton)));
+        assert_eq!(replay.next_command_at(), None);
+
+        replay.start_over();
+        assert_eq!(replay.take_next_command(), Some(GroupVocabulary::PowerOn));
+    }
+
+    #[test]
+    fn when_live_reports_are_as_recorded_then_only_timing_differs() {
+        let replay = &mut recording();
+        replay.on_live_report(Duration::from_millis(5), 0, &ElevatorVocabulary::MoveToHomeFloor(0));
+        replay.on_live_report(Duration::from_millis(910), 1, &ElevatorVocabulary::MovingTo(2));
+        assert!(!replay.on_live_report(Duration::from_millis(920), 1, &ElevatorVocabulary::CurrentCarriagePosn(4_500)));
+        replay.on_live_report(Duration::from_millis(1830), 1, &ElevatorVocabulary::OpenTheDoor(2));
+        replay.on_live_report(Duration::from_millis(4810), 1, &ElevatorVocabulary::DoorClosed(2));
+
+        let report = replay.divergence();
+
+        assert!(report.is_as_recorded());
+        assert_eq!(report.cars[1], CarDivergence::AsRecorded { events: 3, largest_time_difference: Duration::from_millis(30) });
+    }
+
+    #[test]
+    fn when_a_car_stops_elsewhere_then_the_first_difference_is_reported() {
+        let replay = &mut recording();
+        replay.on_live_report(Duration::from_millis(5), 0, &ElevatorVocabulary::MoveToHomeFloor(0));
+        replay.on_live_report(Duration::from_millis(910), 1, &ElevatorVocabulary::MovingTo(3));
+
+        let report = replay.divergence();
+
+        assert!(!report.is_as_recorded());
+        assert_eq!(report.cars[1], CarDivergence::Diverged {
+            event: 0,
+            recorded: Some((Duration::from_millis(900), ElevatorVocabulary::MovingTo(2))),
+            live: Some((Duration::from_millis(910), ElevatorVocabulary::MovingTo(3)))
+        });
+        assert!(report.lines()[1].starts_with("Car B: diverged at event (1)"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use ractor::concurrency::{Duration, JoinHandle};
+use ractor::{ActorCell, Message, MessagingErr};
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// How simulated time relates to the time on the wall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockMode {
+    RealTime,
+    // So many simulated seconds go by, every second on the wall (10.0 runs ten times faster).
+    Scaled(f64),
+    // Nobody waits: whenever every actor is idle, time jumps straight to the next timer. The
+    // tokio runtime must be single-threaded, with its time paused (see `start_paused`).
+    DiscreteEvent
+}
+
+// Simulated time goes by at `speed` (none at all, while paused), since it was `simulated_then`
+// at `then` on the runtime.
+#[derive(Clone, Copy, Debug)]
+struct Pace {
+    speed: f64,
+    then: Instant,
+    simulated_then: Duration
+}
+
+impl Pace {
+    fn simulated_now(&self) -> Duration {
+        self.simulated_then + self.then.elapsed().mul_f64(self.speed)
+    }
+}
+
+/// Every timer of the simulation (pulley steps, door dwell, calls of a script ...) is set on this
+/// clock, in simulated time. The clock decides how long that is on the wall. Its clones are the
+/// same clock: when it is paused, or its speed is changed, every timer set on it follows.
+#[derive(Clone, Debug)]
+pub struct SimulationClock {
+    pace: Arc<watch::Sender<Pace>>
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        SimulationClock::new(ClockMode::RealTime)
+    }
+}
+
+impl SimulationClock {
+    pub fn new(mode: ClockMode) -> Self {
+        let speed = match mode {
+            ClockMode::Scaled(speed) => speed,
+            ClockMode::RealTime | ClockMode::DiscreteEvent => 1.0
+        };
+        let (pace, _) = watch::channel(Pace { speed, then: Instant::now(), simulated_then: Duration::ZERO });
+        SimulationClock { pace: Arc::new(pace) }
+    }
+
+    /// Simulated time, since the clock was started.
+    pub fn elapsed(&self) -> Duration {
+        self.pace.borrow().simulated_now()
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.pace.borrow().speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.speed() == 0.0
+    }
+
+    // Simulated time carries on from where it is now, at the new speed.
+    pub fn set_speed(&self, speed: f64) {
+        self.pace.send_modify(|pace| {
+            *pace = Pace { speed: speed.max(0.0), then: Instant::now(), simulated_then: pace.simulated_now() };
+        });
+    }
+
+    pub fn pause(&self) {
+        self.set_speed(0.0)
+    }
+
+    pub async fn sleep(&self, duration: Duration) {
+        self.sleep_until(self.elapsed() + duration).await
+    }
+
+    /// Sleeps until `at` (simulated time, since the clock was started). Whenever the clock is
+    /// paused, or its speed is changed, the time left is worked out again.
+    pub async fn sleep_until(&self, at: Duration) {
+        let mut pace_changes = self.pace.subscribe();
+        loop {
+            let pace = *pace_changes.borrow_and_update();
+            let simulated_now = pace.simulated_now();
+            if simulated_now >= at {
+                return;
+            }
+
+            if pace.speed == 0.0 {
+                let _ = pace_changes.changed().await;
+            } else {
+                tokio::select! {
+                    _ = tokio::time::sleep((at - simulated_now).div_f64(pace.speed)) => {},
+                    _ = pace_changes.changed() => {}
+                }
+            }
+        }
+    }
+
+    pub fn send_after<TMessage, F>(&self, period: Duration, actor: ActorCell, msg: F)
+        -> JoinHandle<Result<(), MessagingErr<TMessage>>>
+    where
+        TMessage: Message,
+        F: FnOnce() -> TMessage + Send + 'static
+    {
+        let clock = self.clone();
+        tokio::spawn(async move {
+            clock.sleep(period).await;
+            actor.send_message::<TMessage>(msg())
+        })
+    }
+
+    // As above, at `at` (simulated time, since the clock was started).
+    pub fn send_at<TMessage, F>(&self, at: Duration, actor: ActorCell, msg: F)
+        -> JoinHandle<Result<(), MessagingErr<TMessage>>>
+    where
+        TMessage: Message,
+        F: FnOnce() -> TMessage + Send + 'static
+    {
+        let clock = self.clone();
+        tokio::spawn(async move {
+            clock.sleep_until(at).await;
+            actor.send_message::<TMessage>(msg())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn when_time_is_discrete_then_an_hour_goes_by_at_once() {
+        let clock = SimulationClock::new(ClockMode::DiscreteEvent);
+        let wall = std::time::Instant::now();
+
+        clock.sleep(Duration::from_secs(3600)).await;
+
+        assert_eq!(clock.elapsed(), Duration::from_secs(3600));
+        assert!(wall.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_time_is_scaled_then_the_runtime_waits_less() {
+        let clock = SimulationClock::new(ClockMode::Scaled(10.0));
+        let runtime = Instant::now();
+
+        clock.sleep_until(Duration::from_secs(60)).await;
+
+        assert_eq!(runtime.elapsed(), Duration::from_secs(6));
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn when_paused_then_timers_wait_until_the_clock_goes_on() {
+        let clock = SimulationClock::new(ClockMode::RealTime);
+        let timer = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep(Duration::from_secs(10)).await }
+        });
+
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        clock.pause();
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        assert!(!timer.is_finished());
+        assert_eq!(clock.elapsed(), Duration::from_secs(4));
+
+        clock.set_speed(2.0);
+        let runtime = Instant::now();
+        timer.await.unwrap();
+
+        assert_eq!(runtime.elapsed(), Duration::from_secs(3));
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+    }
+}
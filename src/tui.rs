@@ -1,23 +1,21 @@
-use crate::app::AppResult;
 use crate::app_own_event::AppOwnEvent;
 use crate::elevator_infra::ElevatorVisualInfra;
 use crate::tui_layout::TuiLayout;
 use crate::ui::DisplayManager;
+use crate::ui_actor::UiVocabulary;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent, KeyEventKind};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use futures::{FutureExt, StreamExt};
+use ractor::ActorRef;
 use ratatui::prelude::Backend;
 use ratatui::Terminal;
 use std::collections::VecDeque;
 use std::io;
 use std::panic;
-use std::time::Duration;
 use tokio_util::sync::CancellationToken;
+use tracing::info;
 
-use tokio::{
-    sync::mpsc::UnboundedSender,
-    task::JoinHandle,
-};
+use tokio::task::JoinHandle;
 
 #[derive(Debug)]
 pub struct Tui<CrosstermBackend: ratatui::backend::Backend> {
@@ -29,7 +27,6 @@ pub struct Tui<CrosstermBackend: ratatui::backend::Backend> {
 
     pub task: JoinHandle<()>,
     pub cancellation_token: CancellationToken,
-    pub event_tx: UnboundedSender<AppOwnEvent>,
     pub frame_rate: f64,
     pub tick_rate: f64,
 
@@ -38,7 +35,13 @@ pub struct Tui<CrosstermBackend: ratatui::backend::Backend> {
 
 impl<B: Backend> Tui<B> {
 
-    pub fn new(terminal: Terminal<B>, tui_layout: TuiLayout, ui: DisplayManager, event_tx:UnboundedSender<AppOwnEvent>) -> Self {
+    pub fn new(
+        terminal: Terminal<B>,
+        tui_layout: TuiLayout,
+        ui: DisplayManager,
+        tick_rate: f64,
+        frame_rate: f64
+    ) -> Self {
 
         let cancellation_token = CancellationToken::new();
         let task = tokio::spawn(async {});
@@ -47,14 +50,14 @@ impl<B: Backend> Tui<B> {
             layout: tui_layout,
             task,
             cancellation_token,
-            event_tx,
-            frame_rate: 10.0,
-            tick_rate: 1.0,
+            frame_rate,
+            tick_rate,
             ui
         }
     }
 
-    pub fn start(&mut self) -> AppResult<()> {
+    // Whatever happens on the terminal, and every tick and frame, reaches the console as a message.
+    pub fn start(&mut self, console: ActorRef<UiVocabulary>) -> io::Result<()> {
 
         let tick_delay = std::time::Duration::from_secs_f64(1.0 / self.tick_rate);
         let frame_delay = std::time::Duration::from_secs_f64(1.0 / self.frame_rate);
@@ -62,7 +65,7 @@ impl<B: Backend> Tui<B> {
         self.cancellation_token.cancel();
         self.cancellation_token = CancellationToken::new();
 
-        let sharable_tx = self.event_tx.clone();
+        let to_console = move |event: AppOwnEvent| console.send_message(UiVocabulary::Terminal(event));
         let sharable_cancellation_token = self.cancellation_token.clone();
 
         self.task = tokio::spawn(async move {
@@ -71,7 +74,11 @@ impl<B: Backend> Tui<B> {
             let mut tick_interval = tokio::time::interval(tick_delay);
             let mut frame_interval = tokio::time::interval(frame_delay);
 
-            sharable_tx.send(AppOwnEvent::Init).unwrap();
+            // Once the console has stopped, nobody is left to hear the terminal.
+            if let Err(e) = to_console(AppOwnEvent::Init) {
+                info!("Tui: console is not there anymore, {:?}", e);
+                return;
+            }
 
             loop {
 
@@ -79,40 +86,28 @@ impl<B: Backend> Tui<B> {
                 let next_frame_at = frame_interval.tick();
                 let crossterm_event = event_reader.next().fuse();
 
-                tokio::select! {
+                let event = tokio::select! {
                     _ = sharable_cancellation_token.cancelled() => {
                         break;
                       },
 
                     maybe_event = crossterm_event => {
                         match maybe_event {
-                                Some(Ok(evt)) => {
-                                    match evt {
-                                        CrosstermEvent::Key(key) => {
-                                            if key.kind == KeyEventKind::Press {
-                                                sharable_tx.send(AppOwnEvent::Key(key)).unwrap();
-                                            }
-                                        },
-                                        CrosstermEvent::Mouse(mouse) => {
-                                            sharable_tx.send(AppOwnEvent::Mouse(mouse)).unwrap();
-                                        },
-                                        _ => {}
-                                    }
-                            },
-                            Some(Err(_)) => {
-                                sharable_tx.send(AppOwnEvent::Error).unwrap();
-                            },
-                            None => {},
+                            Some(Ok(CrosstermEvent::Key(key))) if key.kind == KeyEventKind::Press => Some(AppOwnEvent::Key(key)),
+                            Some(Ok(CrosstermEvent::Mouse(mouse))) => Some(AppOwnEvent::Mouse(mouse)),
+                            Some(Err(_)) => Some(AppOwnEvent::Error),
+                            _ => None,
                         }
                     },
-                    _ = next_tick_at => {
-                        sharable_tx.send(AppOwnEvent::Tick).unwrap();
-                    },
-                    _ = next_frame_at => {
-                        sharable_tx.send(AppOwnEvent::Render).unwrap();
-                    },
+                    _ = next_tick_at => Some(AppOwnEvent::Tick),
+                    _ = next_frame_at => Some(AppOwnEvent::Render),
+                };
+
+                if let Some(Err(e)) = event.map(&to_console) {
+                    info!("Tui: console is not there anymore, {:?}", e);
+                    break;
                 }
-                      
+
             }
 
         });
@@ -124,7 +119,7 @@ impl<B: Backend> Tui<B> {
     /// Initializes the terminal interface.
     ///
     /// It enables the raw mode and sets terminal properties.
-    pub fn init(&mut self) -> AppResult<()> {
+    pub fn init(&mut self) -> io::Result<()> {
         terminal::enable_raw_mode()?;
         crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
 
@@ -149,7 +144,7 @@ impl<B: Backend> Tui<B> {
             &mut self, 
             inner_infra: &ElevatorVisualInfra, 
             messages_for_opes: &VecDeque<String>
-        ) -> AppResult<()> {
+        ) -> io::Result<()> {
             self.terminal
                 .draw(|frame| self.ui
                     .render_working(
@@ -166,25 +161,25 @@ impl<B: Backend> Tui<B> {
     ///
     /// This function is also used for the panic hook to revert
     /// the terminal properties if unexpected errors occur.
-    fn reset() -> AppResult<()> {
+    fn reset() -> io::Result<()> {
         terminal::disable_raw_mode()?;
         crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture)?;
         Ok(())
     }
 
-    pub fn stop(&self) -> AppResult<()> {
+    // The events stop once the task reading them has finished; it runs on the same runtime as the
+    // console, so it is waited for, not spun on.
+    pub async fn stop(&mut self) -> io::Result<()> {
         self.cancel();
-        while !self.task.is_finished() {
-          let _ = tokio::time::sleep(Duration::from_millis(1));
-        }
+        let _ = (&mut self.task).await;
         Ok(())
     }
 
     /// Exits the terminal interface.
     ///
     /// It disables the raw mode and reverts back the terminal properties.
-    pub fn exit(&mut self) -> AppResult<()> {
-        self.stop()?;
+    pub async fn exit(&mut self) -> io::Result<()> {
+        self.stop().await?;
         Self::reset()?;
         self.terminal.show_cursor()?;
         Ok(())
@@ -1,21 +1,22 @@
-use std::rc::Rc;
-
-use ratatui::{backend::Backend, layout::{Constraint, Direction, Layout, Rect},Terminal};
+use ratatui::{backend::Backend, layout::{Constraint, Direction, Layout, Margin, Rect},Terminal};
 use tracing::info;
 
 use crate::app::AppResult;
 
 #[derive(Debug)]
 pub struct TuiLayout {
-    pub motion_window: Rc<[Rect]>,
-    pub info_window: Rc<[Rect]>,
-    pub button_windows: Rc<[Rect]>,
+    // Kept as they were split, to be drawn by the console (on whichever thread it runs).
+    pub motion_window: Vec<Rect>,
+    pub info_window: Vec<Rect>,
+    pub button_windows: Vec<Rect>,
+    pub car_panel_window: Rect,
+    pub stats_window: Rect,
     pub motion_window_index: u16,
-    pub info_window_index: u16,
     pub start_button_index: u16,
     pub stop_button_index: u16,
     pub current_floor_index: u16,
-    pub next_stop_index: u16
+    pub next_stop_index: u16,
+    pub kiosk_index: u16
     
 }
 
@@ -33,30 +34,39 @@ impl TuiLayout {
             .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref());
         let screen_chunks_2 = layout.split(screen_chunks_1[0]);
 
+        // The service statistics, and the car operating panel, sit beside the elevator monitor.
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(30), Constraint::Percentage(20)].as_ref());
+        let screen_chunks_4 = layout.split(screen_chunks_2[0]);
+
         let layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
-                    Constraint::Percentage(25), 
-                    Constraint::Percentage(25), 
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25)
+                    Constraint::Percentage(20), 
+                    Constraint::Percentage(20), 
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(20)
                     ].as_ref());
         let screen_chunks_3 = layout.split(screen_chunks_2[1]);
     
-        Ok(TuiLayout {  motion_window: screen_chunks_1, 
-                        info_window: screen_chunks_2,
-                        button_windows: screen_chunks_3,
+        Ok(TuiLayout {  motion_window: screen_chunks_1.to_vec(), 
+                        info_window: vec![screen_chunks_4[0], screen_chunks_2[1]],
+                        button_windows: screen_chunks_3.to_vec(),
+                        car_panel_window: screen_chunks_4[2],
+                        stats_window: screen_chunks_4[1],
                         motion_window_index: 1,
-                        info_window_index: 0,
                         start_button_index: 0,
                         stop_button_index: 1,
                         current_floor_index: 2,
-                        next_stop_index: 3
+                        next_stop_index: 3,
+                        kiosk_index: 4
                         
                  })
     }
 
-    pub fn log_window_corners(&self) -> () {
+    pub fn log_window_corners(&self) {
 
         info!("Elevator Info window: {:?}",               self.info_window[0]);
         info!("Elevator Motion window: {:?}",             self.motion_window[1]);
@@ -64,6 +74,33 @@ impl TuiLayout {
         info!("Stop Button window: {:?}",                 self.button_windows[1]);
         info!("Current Floor display window: {:?}",       self.button_windows[2]);
         info!("Destination Floor display window: {:?}",   self.button_windows[3]);
+        info!("Destination kiosk window: {:?}",           self.button_windows[4]);
+        info!("Car operating panel window: {:?}",         self.car_panel_window);
+        info!("Service statistics window: {:?}",          self.stats_window);
+    }
+
+    // The bottom line of the car operating panel shows how loaded the car is.
+    pub fn car_load_gauge(&self) -> Rect {
+        let panel = self.car_panel_window.inner(&Margin { horizontal: 1, vertical: 1 });
+        Rect { y: panel.y + panel.height.saturating_sub(1), height: std::cmp::min(panel.height, 1), ..panel }
+    }
+
+    // One button per floor, inside the car operating panel (above its load gauge); the topmost
+    // button is for the top floor. The buttons are returned in the order of floors, ground floor first.
+    pub fn car_panel_buttons(&self, mx_floors: u16) -> Vec<Rect> {
+        let panel = self.car_panel_window.inner(&Margin { horizontal: 1, vertical: 1 });
+        let panel = Rect { height: panel.height.saturating_sub(1), ..panel };
+        let button_height = std::cmp::max(panel.height / mx_floors, 1);
+
+        (0..mx_floors)
+            .map(|floor| Rect {
+                x:      panel.x,
+                y:      panel.y + (mx_floors - 1 - floor) * button_height,
+                width:  panel.width,
+                height: button_height
+            })
+            .map(|button| button.intersection(panel))
+            .collect()
     }
 
 
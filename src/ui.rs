@@ -1,19 +1,23 @@
 use std::collections::VecDeque;
 
 use ratatui::{
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Rect},
     style::{Color, Modifier, Style, Stylize},
     symbols::Marker,
     text::Line as TextLine,
     widgets::{
         canvas::{Canvas, Line, Rectangle},
-        Block, Borders, Paragraph,
+        Block, Borders, Cell, LineGauge, Paragraph, Row, Table,
     },
     Frame,
 };
-use tracing::info;
 
-use crate::{elevator_infra::ElevatorVisualInfra, tui_layout::TuiLayout};
+use elevator_ratatui::{conversation::vocabulary::CarIndex, elevator_installation::group_controller::car_letter};
+use crate::{
+    door_widget::CarriageWithDoors,
+    elevator_infra::ElevatorVisualInfra,
+    tui_layout::TuiLayout,
+};
 
 #[derive(Debug)]
 pub struct DisplayManager {
@@ -43,6 +47,7 @@ impl DisplayManager {
         let elevator_stop_button = layout.button_windows[layout.stop_button_index as usize];
         let elevator_current_floor = layout.button_windows[layout.current_floor_index as usize];
         let elevator_next_floor = layout.button_windows[layout.next_stop_index as usize];
+        let destination_kiosk = layout.button_windows[layout.kiosk_index as usize];
 
         let scroll_by = self.compute_scroll_extent(messages_for_ops, &elevator_monitor_layout);
 
@@ -61,11 +66,19 @@ impl DisplayManager {
 
         self.render_stop_button(elevator_stop_button, f);
 
-        self.render_currently_at_kiosk(label_currently_at, elevator_current_floor, f);
+        self.render_currently_at_kiosk(label_currently_at, infra.selected_car, elevator_current_floor, f);
+
+        self.render_next_stop_kiosk(label_next_stop, infra.selected_car, elevator_next_floor, f );
+
+        self.render_destination_kiosk(infra, destination_kiosk, f);
+
+        self.render_car_operating_panel(infra, layout, f);
 
-        self.render_next_stop_kiosk(label_next_stop, elevator_next_floor, f );
+        self.render_car_load_gauge(infra, layout, f);
 
-        let output_chunks = layout.motion_window.clone();
+        self.render_stats_panel(infra, layout.stats_window, f);
+
+        let _output_chunks = layout.motion_window.clone();
 
         let floors_as_rectangles: Vec<Rectangle> =
             DisplayManager::translate_floor_coords_to_viewport_rectangles(infra, (0.0, 0.0));
@@ -80,20 +93,50 @@ impl DisplayManager {
             )
             .marker(Marker::HalfBlock)
             .paint(|ctx| {
-                ctx.draw(&Line {
-                    x1: (0.0 + (infra.carriage_playground.width as f64 / 2.0)),
-                    y1: 0.0,
-                    x2: (0.0 + (infra.carriage_playground.width as f64 / 2.0)),
-                    y2: (0.0 + infra.carriage_playground.height as f64),
-                    color: Color::Black,
-                });
+                // One shaft per car, side by side, to the right of the floors.
+                for car in 0..infra.cars.len() {
+                    let shaft_x = (infra.each_floor_width + car as u16 * infra.each_shaft_width) as f64;
+                    ctx.draw(&Line {
+                        x1: shaft_x,
+                        y1: 0.0,
+                        x2: shaft_x,
+                        y2: (0.0 + infra.carriage_playground.height as f64),
+                        color: Color::Black,
+                    });
+                }
 
                 for each_floor_as_rectangle in &floors_as_rectangles {
                     ctx.draw(each_floor_as_rectangle);
                 }
 
-                if infra.should_show_carriage() {
-                    ctx.draw(&self.bring_carriage_to_screen(infra));
+                for (floor_index, next) in infra.floor_as_rects.iter().enumerate() {
+                    let button_y = next.height as f64 * floor_index as f64 + next.height as f64 / 2.0;
+                    let quarter_width = infra.each_floor_width as f64 / 4.0;
+                    if floor_index < infra.floor_as_rects.len() - 1 {
+                        ctx.print(quarter_width, button_y, "▲".black());
+                    }
+                    if floor_index > 0 {
+                        ctx.print(3.0 * quarter_width, button_y, "▼".black());
+                    }
+                    ctx.print(2.0 * quarter_width - 1.0, button_y, infra.building.floor_name(floor_index as u8).to_owned().black().bold());
+                }
+
+                for car in 0..infra.cars.len() {
+                    if infra.should_show_carriage(car) {
+                        ctx.draw(&self.bring_carriage_to_screen(infra, car));
+                    }
+                }
+
+                ctx.layer();
+
+                for car in 0..infra.cars.len() {
+                    let shaft_x = (infra.each_floor_width + car as u16 * infra.each_shaft_width) as f64;
+                    let label = format!("{}", car_letter(car));
+                    ctx.print(
+                        shaft_x + infra.each_shaft_width as f64 / 2.0,
+                        infra.carriage_playground.height as f64 - 1.0,
+                        if car == infra.selected_car { label.black().bold() } else { label.dark_gray() }
+                    );
                 }
             })
             .x_bounds([self.floors_origin_x, infra.carriage_playground.width as f64])
@@ -108,67 +151,65 @@ impl DisplayManager {
     }
 
     fn create_label_for_current_floor(&self, infra: &ElevatorVisualInfra) -> String {
-        let label_currently_at = infra
+        infra.cars[infra.selected_car]
             .current_floor
-            .and_then(|v| {
-                if v == 0 {
-                    Some("Ground floor".to_owned())
-                } else {
-                    Some(format!("Floor {}", v))
-                }
-            })
-            .or(Some(format!("Unknown at the moment")))
-            .unwrap();
-
-        label_currently_at
+            .map(|v| infra.building.floor_label(v as u8))
+            .unwrap_or_else(|| String::from("Unknown at the moment"))
     }
 
     fn create_label_for_dest_floor(&self, infra: &ElevatorVisualInfra) -> String {
-        let label_next_stop = infra
+        infra.cars[infra.selected_car]
             .dest_floor
-            .and_then(|v| {
-                if v == 0 {
-                    Some("Ground floor".to_owned())
-                } else {
-                    Some(format!("Floor {}", v))
-                }
-            })
-            .or(Some(format!("Unknown at the moment")))
-            .unwrap();
-
-        label_next_stop
+            .map(|v| infra.building.floor_label(v as u8))
+            .unwrap_or_else(|| String::from("Unknown at the moment"))
     }
 
-    fn bring_carriage_to_screen(&self, infra: &ElevatorVisualInfra) -> Rectangle {
-        //info!("crriage bo {:?}", infra.carriage_box);
-        Rectangle {
-            x: self.floors_origin_x + infra.carriage_box.bottom_left_x_offset_from_origin,
-            y: self.floors_origin_y + infra.carriage_box.bottom_left_y_offset_from_origin,
-            width: infra.carriage_box.width,
-            height: infra.carriage_box.height,
-            color: Color::LightGreen,
+    // The carriage, with its door as far open as it is in the frame being drawn.
+    fn bring_carriage_to_screen(&self, infra: &ElevatorVisualInfra, car: CarIndex) -> CarriageWithDoors {
+        let carriage_box = &infra.cars[car].carriage_box;
+        //info!("crriage bo {:?}", carriage_box);
+        CarriageWithDoors {
+            x: self.floors_origin_x + carriage_box.bottom_left_x_offset_from_origin,
+            y: self.floors_origin_y + infra.carriage_offset_from_origin(car),
+            width: carriage_box.width,
+            height: carriage_box.height,
+            openness: infra.door_openness(car),
+            lamp: infra.cars[car].door.lamp(),
         }
     }
 
+    // Each floor is drawn as two halves: the landing buttons for going up (left) and for going
+    // down (right). A half is lit, while its hall call is waiting to be answered.
     fn translate_floor_coords_to_viewport_rectangles(
         infra: &ElevatorVisualInfra,
         origin: (f64, f64),
     ) -> Vec<Rectangle> {
+        let half_width = infra.each_floor_width as f64 / 2.0;
+        let lamp_colour = |is_lit: bool| if is_lit { Color::LightGreen } else { Color::Gray };
+
         let f = infra
             .floor_as_rects
             .iter()
             .enumerate()
             .rev()
-            .map(|(index, next)| Rectangle {
-                x: origin.0,
-                y: (origin.1 + (next.height as f64 * index as f64)),
-                width: (infra.carriage_playground.width as f64 / 2.0),
-                height: infra.each_floor_height as f64,
-                color: if infra.floors_having_passengers[index] {
-                    Color::LightGreen
-                } else {
-                    Color::Gray
-                },
+            .flat_map(|(index, next)| {
+                let y = origin.1 + (next.height as f64 * index as f64);
+                [
+                    Rectangle {
+                        x: origin.0,
+                        y,
+                        width: half_width,
+                        height: infra.each_floor_height as f64,
+                        color: lamp_colour(infra.hall_calls_up[index]),
+                    },
+                    Rectangle {
+                        x: origin.0 + half_width,
+                        y,
+                        width: half_width,
+                        height: infra.each_floor_height as f64,
+                        color: lamp_colour(infra.hall_calls_down[index]),
+                    },
+                ]
             })
             .collect();
         f
@@ -195,13 +236,13 @@ impl DisplayManager {
         elevator_monitor_layout: Rect,
         scroll_by: u16,
         f: &mut Frame,
-    ) -> () {
+    ) {
         f.render_widget(
             Paragraph::new(
                 messages_for_ops
                     .clone()
                     .drain(0..)
-                    .map(|n| TextLine::from(n))
+                    .map(TextLine::from)
                     .collect::<Vec<TextLine>>(),
             )
             .block(
@@ -214,7 +255,130 @@ impl DisplayManager {
         );
     }
 
-    fn render_start_button(&self, elevator_start_button: Rect, f: &mut Frame) -> () {
+    fn render_car_operating_panel(
+        &self,
+        infra: &ElevatorVisualInfra,
+        layout: &TuiLayout,
+        f: &mut Frame,
+    ) {
+        f.render_widget(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!("Car {} panel", car_letter(infra.selected_car)))
+                .bg(Color::DarkGray)
+                .fg(Color::White),
+            layout.car_panel_window,
+        );
+
+        let car_calls = &infra.cars[infra.selected_car].car_calls;
+
+        for (floor_index, button) in layout
+            .car_panel_buttons(car_calls.len() as u16)
+            .into_iter()
+            .enumerate()
+        {
+            f.render_widget(
+                Paragraph::new(format!("( {} )", infra.building.floor_name(floor_index as u8)))
+                    .alignment(Alignment::Center)
+                    .style(if car_calls[floor_index] {
+                        Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().bg(Color::DarkGray).fg(Color::White)
+                    }),
+                button,
+            );
+        }
+    }
+
+    // The service quality so far, in seconds: one row per measure.
+    fn render_stats_panel(&self, infra: &ElevatorVisualInfra, stats_window: Rect, f: &mut Frame) {
+        let service = &infra.service;
+        let seconds = |value: f64| Cell::from(format!("{:.1}", value));
+        let rows = service.measures().into_iter().map(|(name, distribution)| {
+            Row::new(vec![
+                Cell::from(name),
+                Cell::from(distribution.count.to_string()),
+                seconds(distribution.mean),
+                seconds(distribution.p50),
+                seconds(distribution.p90),
+                seconds(distribution.p99),
+                seconds(distribution.max),
+            ])
+        });
+        let widths = [
+            Constraint::Length(10),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+        ];
+
+        f.render_widget(
+            Table::new(rows, widths)
+                .header(Row::new(vec!["(s)", "n", "mean", "p50", "p90", "p99", "max"]).style(Style::default().add_modifier(Modifier::BOLD)))
+                .block(
+                    Block::new()
+                        .borders(Borders::ALL)
+                        .title(format!("Service: ({}) requests, ({}) delivered, ({}) over 60s",
+                            service.requests,
+                            service.delivered,
+                            service.calls_over_60s)),
+                ),
+            stats_window,
+        );
+    }
+
+    // How loaded the selected car is, against its rated load: red when overloaded, yellow when
+    // full (it answers no more hall calls).
+    fn render_car_load_gauge(&self, infra: &ElevatorVisualInfra, layout: &TuiLayout, f: &mut Frame) {
+        let load = &infra.cars[infra.selected_car].load;
+        let rated_load = infra.building.rated_load();
+        let colour =
+            if rated_load.is_overloaded(load) { Color::Red }
+            else if rated_load.is_full(load) { Color::Yellow }
+            else { Color::Green };
+
+        f.render_widget(
+            LineGauge::default()
+                .ratio((load.kg as f64 / rated_load.kg as f64).min(1.0))
+                .label(format!("{}/{}p {}kg", load.persons, rated_load.persons, load.kg))
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+                .gauge_style(Style::default().fg(colour).bg(Color::DarkGray).add_modifier(Modifier::BOLD)),
+            layout.car_load_gauge(),
+        );
+    }
+
+    fn render_destination_kiosk(&self, infra: &ElevatorVisualInfra, destination_kiosk: Rect, f: &mut Frame) {
+        let kiosk = &infra.kiosk;
+        let label = if !kiosk.is_active {
+            String::from("Press here for kiosk.")
+        } else if !kiosk.entry.is_empty() {
+            format!("Going to: {}_", kiosk.entry)
+        } else {
+            kiosk
+                .last_assignment
+                .map(|(to_floor, car)| format!("Floor {}: take car {}", infra.building.floor_name(to_floor as u8), car_letter(car)))
+                .unwrap_or(String::from("Going to: _"))
+        };
+
+        f.render_widget(
+            Paragraph::new(label)
+                .style(Style::default().add_modifier(Modifier::BOLD))
+                .alignment(Alignment::Center)
+                .block(
+                    Block::new()
+                        .borders(Borders::ALL)
+                        .title(format!("Destination kiosk, at floor {}", infra.building.floor_name(infra.building.lobby_floor())))
+                        .bg(if kiosk.is_active { Color::LightYellow } else { Color::Gray })
+                        .fg(Color::Black),
+                ),
+            destination_kiosk,
+        );
+    }
+
+    fn render_start_button(&self, elevator_start_button: Rect, f: &mut Frame) {
         f.render_widget(
             Paragraph::new("Press here to start.").block(
                 Block::new()
@@ -226,7 +390,7 @@ impl DisplayManager {
         );
     }
 
-    fn render_stop_button(&self, elevator_stop_button: Rect, f: &mut Frame) -> () {
+    fn render_stop_button(&self, elevator_stop_button: Rect, f: &mut Frame) {
         f.render_widget(
             Paragraph::new("Press here to stop.").block(
                 Block::new()
@@ -241,9 +405,10 @@ impl DisplayManager {
     fn render_currently_at_kiosk(
         &self,
         label_currently_at: String,
+        car: CarIndex,
         elevator_current_floor: Rect,
         f: &mut Frame,
-    ) -> () {
+    ) {
         f.render_widget(
             Paragraph::new(label_currently_at)
                 .style(Style::default().add_modifier(Modifier::BOLD))
@@ -251,7 +416,7 @@ impl DisplayManager {
                 .block(
                     Block::new()
                         .borders(Borders::ALL)
-                        .title("Currently at floor")
+                        .title(format!("Car {} currently at floor", car_letter(car)))
                         .bg(Color::LightBlue)
                         .fg(Color::Black),
                 ),
@@ -262,9 +427,10 @@ impl DisplayManager {
     fn render_next_stop_kiosk(
         &self,
         label_next_stop: String,
+        car: CarIndex,
         elevator_next_floor: Rect,
         f: &mut Frame,
-    ) -> () {
+    ) {
         f.render_widget(
             Paragraph::new(label_next_stop)
                 .style(Style::default().add_modifier(Modifier::BOLD))
@@ -272,7 +438,7 @@ impl DisplayManager {
                 .block(
                     Block::new()
                         .borders(Borders::ALL)
-                        .title("Next stop at floor")
+                        .title(format!("Car {} next stop at floor", car_letter(car)))
                         .bg(Color::LightMagenta)
                         .fg(Color::Black),
                 ),
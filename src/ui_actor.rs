@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+use ractor_cluster::RactorMessage;
+use ratatui::backend::Backend;
+use tokio::sync::oneshot;
+use tracing::info;
+
+use crate::app::App;
+use crate::app_own_event::AppOwnEvent;
+use elevator_ratatui::conversation::vocabulary::{CarIndex, ElevatorVocabulary};
+use crate::metrics::MetricsReport;
+use crate::replay::DivergenceReport;
+
+#[derive(RactorMessage, Debug)]
+pub enum UiVocabulary {
+    FromCar(usize /* group generation */, CarIndex, ElevatorVocabulary), // whatever a car tells its operator, passed on by the group
+    Terminal(AppOwnEvent), // keys and clicks of the operator; ticks and frames
+    ReplayIsDue            // a recorded command is due, or the replay is to stop
+}
+
+pub struct ConsoleState<B: Backend> {
+    app: App<B>,
+    outcome: Option<oneshot::Sender<(MetricsReport, Option<DivergenceReport>)>>
+}
+
+/// The operator's console, an actor of its own. It has subscribed to the group of cars, and hears
+/// whatever they tell their operator; the keys and clicks of the operator reach it as messages
+/// too, and it gives the group its commands as messages. It stops once the operator quits.
+pub struct UiActor<B: Backend> {
+    backend: PhantomData<fn() -> B>
+}
+
+impl<B: Backend> UiActor<B> {
+    pub fn new() -> Self {
+        UiActor { backend: PhantomData }
+    }
+}
+
+#[async_trait]
+impl<B: Backend + Send + 'static> Actor for UiActor<B> {
+    type Msg = UiVocabulary;
+    type State = ConsoleState<B>;
+    type Arguments = (App<B>, oneshot::Sender<(MetricsReport, Option<DivergenceReport>)>);
+
+    async fn pre_start(&self, myself: ActorRef<Self::Msg>, args: Self::Arguments) ->
+        Result<Self::State, ActorProcessingErr> {
+            let (mut app, outcome) = args;
+            if let Err(error) = app.start(&myself) {
+                app.on_error(error).await?;
+            }
+            Ok(ConsoleState { app, outcome: Some(outcome) })
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        console: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let app = &mut console.app;
+
+        let handled = match message {
+            UiVocabulary::FromCar(group_generation, car, report) if app.is_current_group(group_generation) => {
+                app.on_report_from_car(&myself, car, report);
+                Ok(())
+            },
+            UiVocabulary::FromCar(..) => Ok(()),
+            UiVocabulary::Terminal(event) => app.handle_app_own_event(event),
+            UiVocabulary::ReplayIsDue => {
+                app.on_replay_due();
+                Ok(())
+            }
+        };
+        if let Err(error) = handled {
+            app.on_error(error).await?;
+        }
+
+        match app.carry_on(&myself).await {
+            Ok(true) => {},
+            Ok(false) => {
+                info!("Console: the operator has quit");
+                myself.stop(None);
+            },
+            Err(error) => app.on_error(error).await?
+        }
+        Ok(())
+    }
+
+    async fn post_stop(&self, _myself: ActorRef<Self::Msg>, console: &mut Self::State) -> Result<(), ActorProcessingErr> {
+        if let Some(outcome) = console.outcome.take() {
+            let _ = outcome.send((console.app.service_report(), console.app.divergence()));
+        }
+        Ok(())
+    }
+}